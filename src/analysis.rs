@@ -0,0 +1,403 @@
+/*
+ * Rustgammon
+ *
+ * Game analysis
+ * Replay a game record and compare every checker play and cube decision with the best one
+ * found by an evaluator. The equity lost by a decision is tagged doubtful, an error or a
+ * blunder by thresholds, and summed per player into an error rate and a performance
+ * rating, which is 500 times the average equity lost per unforced decision.
+ * Checker plays are compared by cubeless equity, and cube decisions by the money equities
+ * of the cube module in units of the cube before the decision.
+ *
+ * @author ryutaroikeda94@gmail.com
+ *
+ */
+
+use std::fmt;
+
+use cube;
+use cube::Cube;
+use cube::CubeDecision;
+use evaluator::ProbabilityEvaluator;
+use evaluator::rank_moves;
+use gamerecord::GameRecord;
+use notation::format_move_from;
+use packed::PackedPosition;
+use race::CubeAction;
+use rustgammon::Backgammon;
+use rustgammon::Color;
+use rustgammon::DiceRoll;
+use rustgammon::Move;
+
+pub const DOUBTFUL_THRESHOLD: f64 = 0.04;
+pub const ERROR_THRESHOLD: f64 = 0.08;
+pub const BLUNDER_THRESHOLD: f64 = 0.16;
+
+// The number of best moves kept with each checker play.
+pub const DEFAULT_CANDIDATES: usize = 5;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Doubtful,
+    Error,
+    Blunder,
+}
+
+pub struct Analyzer<E: ProbabilityEvaluator> {
+    pub evaluator: E,
+    // The equity lost from which a decision is doubtful, an error and a blunder.
+    pub thresholds: [f64; 3],
+    pub candidates: usize,
+    pub cube_efficiency: f64,
+    // Whether to analyse the cube decisions of a game with no doubles. Games with a double
+    // are known to have been played with the cube and are always analysed.
+    pub analyze_cube: bool,
+}
+
+#[derive(Clone)]
+pub struct MoveAnalysis {
+    pub turn: usize,
+    pub color: Color,
+    pub roll: DiceRoll,
+    // The position before the move.
+    pub position: Backgammon,
+    pub played: Move,
+    pub played_equity: f64,
+    // The best moves with their equities, best first.
+    pub candidates: Vec<(Move, f64)>,
+    // The number of distinct legal moves. A move is forced if there is at most one.
+    pub legal_moves: usize,
+    pub error: f64,
+    pub severity: Option<Severity>,
+}
+
+#[derive(Clone)]
+pub struct CubeAnalysis {
+    // The index of the turn before which the decision is made.
+    pub turn: usize,
+    // The player who may double.
+    pub color: Color,
+    pub position: Backgammon,
+    pub cube: Cube,
+    pub decision: CubeDecision,
+    pub doubled: bool,
+    // Whether the opponent took, if there was a double.
+    pub taken: Option<bool>,
+    pub doubler_error: f64,
+    pub taker_error: f64,
+    pub doubler_severity: Option<Severity>,
+    pub taker_severity: Option<Severity>,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct PlayerSummary {
+    pub checker_decisions: usize,
+    pub checker_error: f64,
+    pub cube_decisions: usize,
+    pub cube_error: f64,
+    pub doubtful: usize,
+    pub errors: usize,
+    pub blunders: usize,
+}
+
+#[derive(Clone)]
+pub struct GameAnalysis {
+    pub moves: Vec<MoveAnalysis>,
+    pub cube_decisions: Vec<CubeAnalysis>,
+    pub red: PlayerSummary,
+    pub white: PlayerSummary,
+}
+
+fn classify(thresholds: &[f64; 3], error: f64) -> Option<Severity> {
+    if thresholds[2] <= error {
+        return Some(Severity::Blunder);
+    }
+    if thresholds[1] <= error {
+        return Some(Severity::Error);
+    }
+    if thresholds[0] <= error {
+        return Some(Severity::Doubtful);
+    }
+    return None;
+}
+
+impl<E: ProbabilityEvaluator> Analyzer<E> {
+    pub fn new(evaluator: E) -> Analyzer<E> {
+        return Analyzer {
+            evaluator: evaluator,
+            thresholds: [DOUBTFUL_THRESHOLD, ERROR_THRESHOLD, BLUNDER_THRESHOLD],
+            candidates: DEFAULT_CANDIDATES,
+            cube_efficiency: cube::DEFAULT_CUBE_EFFICIENCY,
+            analyze_cube: false,
+        };
+    }
+
+    pub fn analyze(&self, record: &GameRecord) -> GameAnalysis {
+        let mut analysis = GameAnalysis {
+            moves: Vec::with_capacity(record.turns.len()),
+            cube_decisions: Vec::new(),
+            red: Default::default(),
+            white: Default::default(),
+        };
+        let mut game = record.start;
+        let mut cube: Cube = Default::default();
+        let cube_in_play = self.analyze_cube || !record.doubles.is_empty();
+        for turn_idx in 0..(record.turns.len() + 1) {
+            let turn = record.turns.get(turn_idx);
+            // The player on roll, or the player who doubled after the last turn.
+            let double = record.doubles.iter().find(|double| double.turn == turn_idx);
+            let color = match (turn, double) {
+                (Some(turn), _) => turn.color,
+                (None, Some(double)) => double.color,
+                (None, None) => break,
+            };
+            if game.winner().is_some() {
+                break;
+            }
+            if cube_in_play {
+                if let Some(cube_analysis) = self.analyze_cube_decision(&game, color, &cube, turn_idx,
+                                                                        double.map(|double| double.taken)) {
+                    analysis.add_cube(cube_analysis);
+                }
+            }
+            if let Some(double) = double {
+                if !double.taken {
+                    break;
+                }
+                cube = Cube { value: 2 * cube.value, owner: Some(double.color.opposite()) };
+            }
+            let turn = match turn {
+                Some(turn) => turn,
+                None => break,
+            };
+            let move_analysis = self.analyze_move(&game, turn_idx, turn.color, turn.roll, &turn.mov);
+            analysis.add_move(move_analysis);
+            game.do_move(turn.color, &turn.mov);
+        }
+        return analysis;
+    }
+
+    pub fn analyze_move(&self, game: &Backgammon, turn: usize, color: Color, roll: DiceRoll,
+                        played: &Move) -> MoveAnalysis {
+        let ranked = rank_moves(game, color, roll, &self.evaluator);
+        let mut next = *game;
+        next.do_move(color, played);
+        let played_position = PackedPosition::new(&next);
+        let played_equity = ranked.iter().find(|candidate| {
+            let mut candidate_game = *game;
+            candidate_game.do_move(color, &candidate.0);
+            PackedPosition::new(&candidate_game) == played_position
+        }).map(|&(_, equity)| equity).unwrap_or_else(|| self.evaluator.evaluate(&next, color));
+        let best_equity = ranked.first().map(|&(_, equity)| equity).unwrap_or(played_equity);
+        let error = (best_equity - played_equity).max(0.0);
+        let legal_moves = ranked.len();
+        return MoveAnalysis {
+            turn: turn,
+            color: color,
+            roll: roll,
+            position: *game,
+            played: played.clone(),
+            played_equity: played_equity,
+            candidates: ranked.into_iter().take(self.candidates).collect(),
+            legal_moves: legal_moves,
+            error: error,
+            severity: if legal_moves <= 1 { None } else { classify(&self.thresholds, error) },
+        };
+    }
+
+    // The cube decision of the player before the turn, where taken says whether there was a
+    // double and the response. None if the player cannot double.
+    pub fn analyze_cube_decision(&self, game: &Backgammon, color: Color, cube: &Cube, turn: usize,
+                                 taken: Option<bool>) -> Option<CubeAnalysis> {
+        let probabilities = cube::on_roll_probabilities(game, color, &self.evaluator);
+        let decision = cube::money_decision(&probabilities, cube, color, self.cube_efficiency)?;
+        let double_equity = decision.double_take.min(decision.double_pass);
+        let best = decision.no_double.max(double_equity);
+        let doubler_error = if taken.is_some() { best - double_equity } else { best - decision.no_double };
+        let taker_error = match taken {
+            Some(true) => (decision.double_take - decision.double_pass).max(0.0),
+            Some(false) => (decision.double_pass - decision.double_take).max(0.0),
+            None => 0.0,
+        };
+        return Some(CubeAnalysis {
+            turn: turn,
+            color: color,
+            position: *game,
+            cube: *cube,
+            decision: decision,
+            doubled: taken.is_some(),
+            taken: taken,
+            doubler_error: doubler_error.max(0.0),
+            taker_error: taker_error,
+            doubler_severity: classify(&self.thresholds, doubler_error),
+            taker_severity: classify(&self.thresholds, taker_error),
+        });
+    }
+}
+
+impl GameAnalysis {
+    pub fn summary(&self, color: Color) -> &PlayerSummary {
+        return match color {
+            Color::Red => &self.red,
+            Color::White => &self.white,
+        };
+    }
+
+    fn summary_mut(&mut self, color: Color) -> &mut PlayerSummary {
+        return match color {
+            Color::Red => &mut self.red,
+            Color::White => &mut self.white,
+        };
+    }
+
+    fn add_move(&mut self, analysis: MoveAnalysis) {
+        if 1 < analysis.legal_moves {
+            let summary = self.summary_mut(analysis.color);
+            summary.checker_decisions += 1;
+            summary.checker_error += analysis.error;
+            summary.count(analysis.severity);
+        }
+        self.moves.push(analysis);
+    }
+
+    // Count the decisions where there was a double or a double was right, so that the
+    // many obvious decisions not to double do not dilute the error rate.
+    fn add_cube(&mut self, analysis: CubeAnalysis) {
+        let is_close = analysis.doubled || analysis.decision.action != CubeAction::NoDouble ||
+            0.0 < analysis.doubler_error;
+        if !is_close {
+            return;
+        }
+        {
+            let doubler = self.summary_mut(analysis.color);
+            doubler.cube_decisions += 1;
+            doubler.cube_error += analysis.doubler_error;
+            doubler.count(analysis.doubler_severity);
+        }
+        if analysis.doubled {
+            let taker = self.summary_mut(analysis.color.opposite());
+            taker.cube_decisions += 1;
+            taker.cube_error += analysis.taker_error;
+            taker.count(analysis.taker_severity);
+        }
+        self.cube_decisions.push(analysis);
+    }
+}
+
+impl PlayerSummary {
+    fn count(&mut self, severity: Option<Severity>) {
+        match severity {
+            Some(Severity::Doubtful) => self.doubtful += 1,
+            Some(Severity::Error) => self.errors += 1,
+            Some(Severity::Blunder) => self.blunders += 1,
+            None => (),
+        }
+    }
+
+    pub fn decisions(&self) -> usize {
+        return self.checker_decisions + self.cube_decisions;
+    }
+
+    pub fn total_error(&self) -> f64 {
+        return self.checker_error + self.cube_error;
+    }
+
+    // The average equity lost per unforced decision.
+    pub fn error_rate(&self) -> f64 {
+        if self.decisions() == 0 {
+            return 0.0;
+        }
+        return self.total_error() / self.decisions() as f64;
+    }
+
+    pub fn performance_rating(&self) -> f64 {
+        return 500.0 * self.error_rate();
+    }
+
+    pub fn rating_name(&self) -> &'static str {
+        let rating = self.performance_rating();
+        return if rating < 2.5 {
+            "awesome"
+        } else if rating < 5.0 {
+            "world class"
+        } else if rating < 7.5 {
+            "expert"
+        } else if rating < 12.5 {
+            "advanced"
+        } else if rating < 17.5 {
+            "intermediate"
+        } else if rating < 22.5 {
+            "casual"
+        } else if rating < 30.0 {
+            "beginner"
+        } else {
+            "distracted"
+        };
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match *self {
+            Severity::Doubtful => "doubtful",
+            Severity::Error => "error",
+            Severity::Blunder => "blunder",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+impl MoveAnalysis {
+    // The move played and any better move, with the points numbered from the perspective.
+    pub fn describe(&self, perspective: Color) -> String {
+        let mut text = format!("turn {} {} {}-{}: {} {:+.3}", self.turn + 1, self.color, self.roll.0,
+                               self.roll.1, format_move_from(perspective, &self.position, self.color,
+                                                             &self.played), self.played_equity);
+        if let Some(&(ref best, equity)) = self.candidates.first() {
+            if 0.0 < self.error {
+                text += &format!(", best {} {:+.3}, lost {:.3}",
+                                 format_move_from(perspective, &self.position, self.color, best),
+                                 equity, self.error);
+            }
+        }
+        if let Some(severity) = self.severity {
+            text += &format!(" ({})", severity);
+        }
+        return text;
+    }
+}
+
+// Each player's moves are numbered from its own side.
+impl fmt::Display for MoveAnalysis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.describe(self.color))
+    }
+}
+
+impl fmt::Display for CubeAnalysis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let action = match self.taken {
+            Some(true) => "double, take",
+            Some(false) => "double, pass",
+            None => "no double",
+        };
+        write!(f, "turn {} {} cube {}: {}; {}", self.turn + 1, self.color, self.cube.value, action,
+               self.decision)?;
+        if 0.0 < self.doubler_error {
+            write!(f, ", doubler lost {:.3}", self.doubler_error)?;
+        }
+        if 0.0 < self.taker_error {
+            write!(f, ", taker lost {:.3}", self.taker_error)?;
+        }
+        return Ok(());
+    }
+}
+
+impl fmt::Display for PlayerSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} decisions, {:.3} lost, {} doubtful, {} errors, {} blunders, \
+                   rating {:.1} ({})",
+               self.decisions(), self.total_error(), self.doubtful, self.errors, self.blunders,
+               self.performance_rating(), self.rating_name())
+    }
+}
@@ -0,0 +1,578 @@
+/*
+ * Rustgammon
+ *
+ * Bearoff databases
+ * The one-sided database holds, for every position of up to 15 checkers on the six home
+ * points, the distribution of the number of rolls needed to bear off all checkers and
+ * to bear off the first checker, playing to minimise the expected number of rolls.
+ * Treating the two sides as independent, the distributions give the win and gammon
+ * probabilities of the race.
+ * The two-sided database holds the exact probability that the player on roll wins for
+ * every pair of positions of a few checkers, playing to maximise it, found by
+ * retrograde analysis from the positions with the fewest pips.
+ *
+ * The one-sided file is a header line followed by two distributions of MAX_ROLLS
+ * little-endian u16 probabilities in units of 1/65535 for every position, in the order
+ * of the index. The two-sided file is a header line followed by a little-endian f32 for
+ * every pair, indexed by the position on roll and then the other position.
+ *
+ * @author ryutaroikeda94@gmail.com
+ *
+ */
+
+use std::fs::File;
+use std::io;
+use std::io::BufWriter;
+use std::io::Read;
+use std::io::Write;
+use std::sync::Arc;
+use std::sync::OnceLock;
+
+use evaluator::Evaluator;
+use evaluator::Probabilities;
+use evaluator::ProbabilityEvaluator;
+use packed::MoveBuffer;
+use packed::PackedPosition;
+use perft::distinct_rolls;
+use rustgammon::Backgammon;
+use rustgammon::BAR_POS;
+use rustgammon::BEARING_OFF_POS;
+use rustgammon::Color;
+
+pub type BearoffError = String;
+
+pub const HOME_POINTS: usize = 6;
+pub const MAX_CHECKERS: usize = 15;
+// Enough rolls to bear off 15 checkers from the six point with the worst rolls.
+pub const MAX_ROLLS: usize = 32;
+pub const DEFAULT_PATH: &str = "data/bearoff-one-sided.bin";
+
+pub const TWO_SIDED_MAX_CHECKERS: usize = 6;
+pub const TWO_SIDED_DEFAULT_PATH: &str = "data/bearoff-two-sided.bin";
+
+const HEADER: &str = "rustgammon-bearoff-one-sided 1";
+const TWO_SIDED_HEADER: &str = "rustgammon-bearoff-two-sided 1";
+
+// The checkers on each home point, where index 0 is the one point.
+pub type HomeBoard = [u8; HOME_POINTS];
+
+pub type Distribution = [f64; MAX_ROLLS];
+
+pub struct OneSidedBearoff {
+    pub max_checkers: usize,
+    // The distribution of the rolls to bear off all checkers of each position.
+    off: Vec<[u16; MAX_ROLLS]>,
+    // The distribution of the rolls to bear off the first checker.
+    first_off: Vec<[u16; MAX_ROLLS]>,
+}
+
+pub struct TwoSidedBearoff {
+    pub max_checkers: usize,
+    positions: usize,
+    // The probability that the player on roll wins, for each pair of positions.
+    win: Vec<f32>,
+}
+
+pub fn binomial(n: usize, k: usize) -> usize {
+    if n < k {
+        return 0;
+    }
+    let mut result = 1;
+    for i in 0..k {
+        result = result * (n - i) / (i + 1);
+    }
+    return result;
+}
+
+// The number of positions of up to the number of checkers on the points.
+pub fn count_positions(checkers: usize, points: usize) -> usize {
+    return binomial(checkers + points, points);
+}
+
+// The index of the position in the lexicographic order of the positions of up to
+// max_checkers checkers.
+pub fn position_index(board: &HomeBoard, max_checkers: usize) -> usize {
+    let mut index = 0;
+    let mut remaining = max_checkers;
+    for (point, &checkers) in board.iter().enumerate() {
+        let later_points = HOME_POINTS - point - 1;
+        for used in 0..checkers as usize {
+            index += count_positions(remaining - used, later_points);
+        }
+        remaining -= checkers as usize;
+    }
+    return index;
+}
+
+fn pips(board: &HomeBoard) -> usize {
+    return board.iter().enumerate().map(|(point, &checkers)| (point + 1) * checkers as usize).sum();
+}
+
+fn all_positions(max_checkers: usize) -> Vec<HomeBoard> {
+    let mut positions = Vec::with_capacity(count_positions(max_checkers, HOME_POINTS));
+    let mut board = [0; HOME_POINTS];
+    add_positions(&mut board, 0, max_checkers, &mut positions);
+    return positions;
+}
+
+fn add_positions(board: &mut HomeBoard, point: usize, remaining: usize,
+                 positions: &mut Vec<HomeBoard>) {
+    if point == HOME_POINTS {
+        positions.push(*board);
+        return;
+    }
+    for checkers in 0..(remaining + 1) {
+        board[point] = checkers as u8;
+        add_positions(board, point + 1, remaining - checkers, positions);
+    }
+    board[point] = 0;
+}
+
+// The home board of the player, or None if a checker is outside it.
+pub fn home_board(game: &Backgammon, color: Color) -> Option<HomeBoard> {
+    for pos in BAR_POS..(BEARING_OFF_POS - HOME_POINTS) {
+        if game.get_board(color, pos) != 0 {
+            return None;
+        }
+    }
+    let mut board = [0; HOME_POINTS];
+    for (point, checkers) in board.iter_mut().enumerate() {
+        *checkers = game.get_board(color, BEARING_OFF_POS - point - 1) as u8;
+    }
+    return Some(board);
+}
+
+fn packed_home_board(position: &PackedPosition) -> HomeBoard {
+    let mut board = [0; HOME_POINTS];
+    for (point, checkers) in board.iter_mut().enumerate() {
+        *checkers = position.get(Color::Red, BEARING_OFF_POS - point - 1) as u8;
+    }
+    return board;
+}
+
+fn to_packed(board: &HomeBoard) -> PackedPosition {
+    let mut game: Backgammon = Default::default();
+    for (point, &checkers) in board.iter().enumerate() {
+        game.set_board(Color::Red, BEARING_OFF_POS - point - 1, checkers as i8);
+    }
+    return PackedPosition::new(&game);
+}
+
+fn checkers_index(board: &HomeBoard, max_checkers: usize) -> Option<usize> {
+    let checkers: usize = board.iter().map(|&checkers| checkers as usize).sum();
+    if max_checkers < checkers {
+        return None;
+    }
+    return Some(position_index(board, max_checkers));
+}
+
+// Read a file of a header line naming the database and the number of checkers.
+fn read_header<R: Read>(input: &mut R, name: &str) -> Result<(usize, Vec<u8>), BearoffError> {
+    let mut data = Vec::new();
+    input.read_to_end(&mut data).map_err(|e| e.to_string())?;
+    let header_end = match data.iter().position(|&byte| byte == b'\n') {
+        Some(v) => v,
+        None => return Err("missing header".to_string()),
+    };
+    let header = String::from_utf8_lossy(&data[..header_end]).to_string();
+    let max_checkers = match header.strip_prefix(name).map(|v| v.trim().parse::<usize>()) {
+        Some(Ok(v)) if v <= MAX_CHECKERS => v,
+        _ => return Err(format!("not a {} database: {}", name, header)),
+    };
+    return Ok((max_checkers, data.split_off(header_end + 1)));
+}
+
+fn create<F: Fn(&mut BufWriter<File>) -> io::Result<()>>(path: &str, write: F)
+                                                         -> Result<(), BearoffError> {
+    let result = File::create(path).and_then(|file| {
+        let mut out = BufWriter::new(file);
+        write(&mut out)?;
+        return out.flush();
+    });
+    return result.map_err(|e| format!("{}: {}", path, e));
+}
+
+fn expected(distribution: &Distribution) -> f64 {
+    return distribution.iter().enumerate().map(|(rolls, p)| rolls as f64 * p).sum();
+}
+
+fn quantize(distribution: &Distribution) -> [u16; MAX_ROLLS] {
+    let mut quantized = [0; MAX_ROLLS];
+    for (q, p) in quantized.iter_mut().zip(distribution.iter()) {
+        *q = (p * 65535.0).round() as u16;
+    }
+    return quantized;
+}
+
+// Scale by the total so the rounding errors do not leave the probabilities short of one.
+fn dequantize(quantized: &[u16; MAX_ROLLS]) -> Distribution {
+    let total: f64 = quantized.iter().map(|&q| q as f64).sum();
+    let mut distribution = [0.0; MAX_ROLLS];
+    for (p, q) in distribution.iter_mut().zip(quantized.iter()) {
+        *p = *q as f64 / total;
+    }
+    return distribution;
+}
+
+// The probability of needing at least the number of rolls.
+fn at_least(distribution: &Distribution, rolls: usize) -> f64 {
+    return distribution.iter().skip(rolls).sum();
+}
+
+impl OneSidedBearoff {
+    // Solve every position, from the fewest pips up, so a move always leads to a solved
+    // position.
+    pub fn generate(max_checkers: usize) -> OneSidedBearoff {
+        assert!(max_checkers <= MAX_CHECKERS, "at most {} checkers", MAX_CHECKERS);
+        let positions = all_positions(max_checkers);
+        let mut order: Vec<usize> = (0..positions.len()).collect();
+        order.sort_by_key(|&index| pips(&positions[index]));
+
+        let mut off = vec![[0.0; MAX_ROLLS]; positions.len()];
+        let mut first_off = vec![[0.0; MAX_ROLLS]; positions.len()];
+        let mut expected_off = vec![0.0; positions.len()];
+        let mut expected_first_off = vec![0.0; positions.len()];
+        let mut buffer = MoveBuffer::new();
+        for index in order {
+            let board = positions[index];
+            let checkers: u8 = board.iter().sum();
+            if checkers == 0 {
+                off[index][0] = 1.0;
+                first_off[index][0] = 1.0;
+                continue;
+            }
+            let position = to_packed(&board);
+            for (roll, ways) in distinct_rolls() {
+                let probability = ways as f64 / 36.0;
+                position.generate_moves(Color::Red, roll, &mut buffer);
+                let mut best_off: Option<usize> = None;
+                let mut best_first_off: Option<usize> = None;
+                let mut can_bear_off = false;
+                for mov in buffer.moves() {
+                    let next_board = packed_home_board(&mov.position);
+                    let next = position_index(&next_board, max_checkers);
+                    match best_off {
+                        Some(best) if expected_off[best] <= expected_off[next] => (),
+                        _ => best_off = Some(next),
+                    }
+                    if next_board.iter().sum::<u8>() < checkers {
+                        can_bear_off = true;
+                    }
+                    match best_first_off {
+                        Some(best) if expected_first_off[best] <= expected_first_off[next] => (),
+                        _ => best_first_off = Some(next),
+                    }
+                }
+                // A player with checkers in the home board can always move.
+                let best_off = best_off.expect("no legal move in a bearoff");
+                for rolls in 1..MAX_ROLLS {
+                    off[index][rolls] += probability * off[best_off][rolls - 1];
+                }
+                match best_first_off {
+                    Some(best) if !can_bear_off => for rolls in 1..MAX_ROLLS {
+                        first_off[index][rolls] += probability * first_off[best][rolls - 1];
+                    },
+                    _ => first_off[index][1] += probability,
+                }
+            }
+            expected_off[index] = expected(&off[index]);
+            expected_first_off[index] = expected(&first_off[index]);
+        }
+        return OneSidedBearoff {
+            max_checkers: max_checkers,
+            off: off.iter().map(quantize).collect(),
+            first_off: first_off.iter().map(quantize).collect(),
+        };
+    }
+
+    pub fn len(&self) -> usize {
+        return self.off.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.off.is_empty();
+    }
+
+    fn index(&self, board: &HomeBoard) -> Option<usize> {
+        return checkers_index(board, self.max_checkers);
+    }
+
+    // The distribution of the rolls to bear off all checkers.
+    pub fn distribution(&self, board: &HomeBoard) -> Option<Distribution> {
+        return self.index(board).map(|index| dequantize(&self.off[index]));
+    }
+
+    // The distribution of the rolls to bear off the first checker.
+    pub fn first_off_distribution(&self, board: &HomeBoard) -> Option<Distribution> {
+        return self.index(board).map(|index| dequantize(&self.first_off[index]));
+    }
+
+    pub fn expected_rolls(&self, board: &HomeBoard) -> Option<f64> {
+        return self.distribution(board).map(|distribution| expected(&distribution));
+    }
+
+    // The probabilities of a pure bearoff for the player who has just moved, or None if
+    // the position is not in the database.
+    pub fn probabilities(&self, game: &Backgammon, color: Color) -> Option<Probabilities> {
+        let opponent = color.opposite();
+        let our_board = home_board(game, color)?;
+        let their_board = home_board(game, opponent)?;
+        let ours = self.distribution(&our_board)?;
+        let theirs = self.distribution(&their_board)?;
+        // The opponent is on roll, so they win if they need no more rolls than we do.
+        let mut lose = 0.0;
+        for (rolls, p) in theirs.iter().enumerate() {
+            lose += p * at_least(&ours, rolls);
+        }
+        let mut probabilities = Probabilities { win: 1.0 - lose, ..Default::default() };
+        // A gammon is possible while the loser has borne off no checkers.
+        if game.get_board(color, BEARING_OFF_POS) == 0 {
+            let first = self.first_off_distribution(&our_board)?;
+            for (rolls, p) in theirs.iter().enumerate() {
+                probabilities.lose_gammon += p * at_least(&first, rolls);
+            }
+        }
+        if game.get_board(opponent, BEARING_OFF_POS) == 0 {
+            let first = self.first_off_distribution(&their_board)?;
+            for (rolls, p) in ours.iter().enumerate() {
+                probabilities.win_gammon += p * at_least(&first, rolls + 1);
+            }
+        }
+        return Some(probabilities);
+    }
+
+    pub fn write<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "{} {}", HEADER, self.max_checkers)?;
+        for (off, first_off) in self.off.iter().zip(self.first_off.iter()) {
+            for value in off.iter().chain(first_off.iter()) {
+                out.write_all(&[*value as u8, (*value >> 8) as u8])?;
+            }
+        }
+        return Ok(());
+    }
+
+    pub fn read<R: Read>(input: &mut R) -> Result<OneSidedBearoff, BearoffError> {
+        let (max_checkers, body) = read_header(input, HEADER)?;
+        let positions = count_positions(max_checkers, HOME_POINTS);
+        let entry_size = 2 * 2 * MAX_ROLLS;
+        if body.len() != positions * entry_size {
+            return Err(format!("expected {} bytes but found {}", positions * entry_size, body.len()));
+        }
+        let mut off = Vec::with_capacity(positions);
+        let mut first_off = Vec::with_capacity(positions);
+        for entry in body.chunks(entry_size) {
+            let mut values = [[0u16; MAX_ROLLS]; 2];
+            for (i, bytes) in entry.chunks(2).enumerate() {
+                values[i / MAX_ROLLS][i % MAX_ROLLS] = bytes[0] as u16 | (bytes[1] as u16) << 8;
+            }
+            off.push(values[0]);
+            first_off.push(values[1]);
+        }
+        return Ok(OneSidedBearoff { max_checkers: max_checkers, off: off, first_off: first_off });
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), BearoffError> {
+        return create(path, |out| self.write(out));
+    }
+
+    pub fn load(path: &str) -> Result<OneSidedBearoff, BearoffError> {
+        let mut file = File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+        return OneSidedBearoff::read(&mut file).map_err(|e| format!("{}: {}", path, e));
+    }
+}
+
+impl TwoSidedBearoff {
+    // Solve the pairs of positions in order of their total pips, so every move leads to
+    // a solved pair with the other player on roll.
+    pub fn generate(max_checkers: usize) -> TwoSidedBearoff {
+        assert!(max_checkers <= MAX_CHECKERS, "at most {} checkers", MAX_CHECKERS);
+        let positions = all_positions(max_checkers);
+        let num_positions = positions.len();
+        let rolls = distinct_rolls();
+        // The distinct positions each position can move to with each roll.
+        let mut buffer = MoveBuffer::new();
+        let mut successors: Vec<Vec<usize>> = Vec::with_capacity(num_positions * rolls.len());
+        for board in &positions {
+            let position = to_packed(board);
+            for &(roll, _) in &rolls {
+                position.generate_moves(Color::Red, roll, &mut buffer);
+                successors.push(buffer.moves().iter()
+                    .map(|mov| position_index(&packed_home_board(&mov.position), max_checkers))
+                    .collect());
+            }
+        }
+        let position_pips: Vec<usize> = positions.iter().map(pips).collect();
+        let mut order: Vec<usize> = (0..num_positions * num_positions).collect();
+        order.sort_by_key(|&pair| {
+            position_pips[pair / num_positions] + position_pips[pair % num_positions]
+        });
+
+        let mut win = vec![0.0f64; num_positions * num_positions];
+        for pair in order {
+            let on_roll = pair / num_positions;
+            let other = pair % num_positions;
+            // The empty position is the first and a player without checkers has won.
+            if on_roll == 0 {
+                win[pair] = 1.0;
+                continue;
+            }
+            if other == 0 {
+                continue;
+            }
+            let mut total = 0.0;
+            for (roll_idx, &(_, ways)) in rolls.iter().enumerate() {
+                let mut best = 0.0f64;
+                for &next in &successors[on_roll * rolls.len() + roll_idx] {
+                    let p = if next == 0 { 1.0 } else { 1.0 - win[other * num_positions + next] };
+                    best = best.max(p);
+                }
+                total += ways as f64 * best;
+            }
+            win[pair] = total / 36.0;
+        }
+        return TwoSidedBearoff {
+            max_checkers: max_checkers,
+            positions: num_positions,
+            win: win.iter().map(|&p| p as f32).collect(),
+        };
+    }
+
+    pub fn len(&self) -> usize {
+        return self.win.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.win.is_empty();
+    }
+
+    // The probability that the player on roll wins.
+    pub fn win_probability(&self, on_roll: &HomeBoard, other: &HomeBoard) -> Option<f64> {
+        let on_roll = checkers_index(on_roll, self.max_checkers)?;
+        let other = checkers_index(other, self.max_checkers)?;
+        return Some(self.win[on_roll * self.positions + other] as f64);
+    }
+
+    // The probabilities of a bearoff for the player who has just moved, or None if the
+    // position is not in the database. Gammons are still possible while a player has
+    // borne off no checkers, which the database does not cover.
+    pub fn probabilities(&self, game: &Backgammon, color: Color) -> Option<Probabilities> {
+        let opponent = color.opposite();
+        if game.get_board(color, BEARING_OFF_POS) == 0 || game.get_board(opponent, BEARING_OFF_POS) == 0 {
+            return None;
+        }
+        let ours = home_board(game, color)?;
+        let theirs = home_board(game, opponent)?;
+        let lose = self.win_probability(&theirs, &ours)?;
+        return Some(Probabilities { win: 1.0 - lose, ..Default::default() });
+    }
+
+    pub fn write<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "{} {}", TWO_SIDED_HEADER, self.max_checkers)?;
+        for p in &self.win {
+            let bits = p.to_bits();
+            out.write_all(&[bits as u8, (bits >> 8) as u8, (bits >> 16) as u8, (bits >> 24) as u8])?;
+        }
+        return Ok(());
+    }
+
+    pub fn read<R: Read>(input: &mut R) -> Result<TwoSidedBearoff, BearoffError> {
+        let (max_checkers, body) = read_header(input, TWO_SIDED_HEADER)?;
+        let positions = count_positions(max_checkers, HOME_POINTS);
+        if body.len() != 4 * positions * positions {
+            return Err(format!("expected {} bytes but found {}", 4 * positions * positions, body.len()));
+        }
+        let win = body.chunks(4).map(|bytes| {
+            f32::from_bits(bytes[0] as u32 | (bytes[1] as u32) << 8 |
+                           (bytes[2] as u32) << 16 | (bytes[3] as u32) << 24)
+        }).collect();
+        return Ok(TwoSidedBearoff { max_checkers: max_checkers, positions: positions, win: win });
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), BearoffError> {
+        return create(path, |out| self.write(out));
+    }
+
+    pub fn load(path: &str) -> Result<TwoSidedBearoff, BearoffError> {
+        let mut file = File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+        return TwoSidedBearoff::read(&mut file).map_err(|e| format!("{}: {}", path, e));
+    }
+}
+
+// The bearoff databases that have been generated. The two-sided database is preferred as
+// it is exact.
+#[derive(Default)]
+pub struct BearoffDatabases {
+    pub one_sided: Option<OneSidedBearoff>,
+    pub two_sided: Option<TwoSidedBearoff>,
+}
+
+impl BearoffDatabases {
+    // Load the databases at the default paths if they have been generated.
+    pub fn load() -> BearoffDatabases {
+        let one_sided = match OneSidedBearoff::load(DEFAULT_PATH) {
+            Ok(database) => Some(database),
+            Err(e) => {
+                debug!("no one-sided bearoff database: {}", e);
+                None
+            },
+        };
+        let two_sided = match TwoSidedBearoff::load(TWO_SIDED_DEFAULT_PATH) {
+            Ok(database) => Some(database),
+            Err(e) => {
+                debug!("no two-sided bearoff database: {}", e);
+                None
+            },
+        };
+        return BearoffDatabases { one_sided: one_sided, two_sided: two_sided };
+    }
+
+    // The databases at the default paths, loaded when first asked for and then shared.
+    pub fn shared() -> Arc<BearoffDatabases> {
+        static SHARED: OnceLock<Arc<BearoffDatabases>> = OnceLock::new();
+        return SHARED.get_or_init(|| Arc::new(BearoffDatabases::load())).clone();
+    }
+
+    pub fn probabilities(&self, game: &Backgammon, color: Color) -> Option<Probabilities> {
+        let two_sided = self.two_sided.as_ref().and_then(|database| database.probabilities(game, color));
+        if two_sided.is_some() {
+            return two_sided;
+        }
+        return self.one_sided.as_ref().and_then(|database| database.probabilities(game, color));
+    }
+}
+
+// Use the exact bearoff probabilities when both players are bearing off and the
+// evaluator otherwise.
+pub struct BearoffEvaluator<E> {
+    pub databases: Arc<BearoffDatabases>,
+    pub evaluator: E,
+}
+
+impl<E> BearoffEvaluator<E> {
+    // Use the shared databases at the default paths.
+    pub fn new(evaluator: E) -> BearoffEvaluator<E> {
+        return BearoffEvaluator { databases: BearoffDatabases::shared(), evaluator: evaluator };
+    }
+}
+
+impl<E: Evaluator> Evaluator for BearoffEvaluator<E> {
+    fn evaluate(&self, game: &Backgammon, color: Color) -> f64 {
+        return match self.databases.probabilities(game, color) {
+            Some(probabilities) => probabilities.equity(),
+            None => self.evaluator.evaluate(game, color),
+        }
+    }
+
+    fn evaluate_finished(&self, game: &Backgammon, winner: Color, color: Color) -> f64 {
+        return self.evaluator.evaluate_finished(game, winner, color);
+    }
+}
+
+impl<E: ProbabilityEvaluator> ProbabilityEvaluator for BearoffEvaluator<E> {
+    fn probabilities(&self, game: &Backgammon, color: Color) -> Probabilities {
+        return match self.databases.probabilities(game, color) {
+            Some(probabilities) => probabilities,
+            None => self.evaluator.probabilities(game, color),
+        }
+    }
+}
@@ -7,13 +7,27 @@
  *
  */
 
+use bearoff::BearoffEvaluator;
+use cube;
+use cube::Cube;
+use evaluator::Evaluator;
+use evaluator::rank_moves;
+use heuristicplayer::Heuristic;
+use neuralnet;
+use neuralnet::NeuralNet;
+use notation::Orientation;
+use notation::format_move_from;
+use notation::point_position;
+use race;
 use rustgammon::Backgammon;
 use rustgammon::Color;
 use rustgammon::DiceRoll;
 use rustgammon::Move;
 use rustgammon::Player;
 use rustgammon::Submove;
+use textboard::BoardStyle;
 
+use std::cell::OnceCell;
 use std::cmp;
 use std::io;
 use std::io::Write;
@@ -21,8 +35,17 @@ use std::error::Error;
 
 pub type CommandLineError = String;
 
+// The number of moves the hint command shows unless told otherwise.
+pub const DEFAULT_HINTS: usize = 5;
+
+// A human at the terminal, who reads and writes the point numbers of a perspective and
+// sees the board from it.
 pub struct CommandLinePlayer {
     pub color: Color,
+    pub perspective: Color,
+    pub orientation: Orientation,
+    // The neural network with the bearoff databases, loaded when first asked for a hint.
+    pub network: OnceCell<Result<BearoffEvaluator<NeuralNet>, CommandLineError>>,
 }
 
 impl Player for CommandLinePlayer {
@@ -30,6 +53,10 @@ impl Player for CommandLinePlayer {
         return self.color;
     }
 
+    fn view(&self) -> (Color, Orientation) {
+        return (self.perspective, self.orientation);
+    }
+
     fn make_move(&self, game: &Backgammon, roll: DiceRoll) -> Move {
         loop {
             print!("enter move: ");
@@ -53,56 +80,56 @@ impl Player for CommandLinePlayer {
 }
 
 impl CommandLinePlayer {
+    // A player numbering the points from its own home board.
+    pub fn new(color: Color) -> CommandLinePlayer {
+        return CommandLinePlayer {
+            color: color,
+            perspective: color,
+            orientation: Orientation::HomeRight,
+            network: OnceCell::new(),
+        };
+    }
+
     fn read_command(&self, game: &Backgammon, roll: DiceRoll) -> Result<Move, CommandLineError> {
         let mut command = String::new();
         match io::stdin().read_line(&mut command) {
             Ok(_) => (),
             Err(e) => return Err(e.description().to_string()),
         }
-        return self.parse_command(game, roll, &command.trim());
+        return self.parse_command(game, roll, command.trim());
     }
 
     fn is_valid_pos(&self, pos: usize) -> bool {
         return pos <= 25;
     }
 
+    // The position of a point number, or of the bar or off.
+    fn parse_pos(&self, text: &str, special: &str, special_pos: usize)
+        -> Result<usize, CommandLineError> {
+        if text == special {
+            return Ok(special_pos);
+        }
+        let point = match text.parse::<usize>() {
+            Ok(point) => point,
+            Err(e) => return Err(e.description().to_string()),
+        };
+        if !self.is_valid_pos(point) {
+            return Err("position out of range".to_string());
+        }
+        return Ok(point_position(self.perspective, self.color, point));
+    }
+
     // A submove is written from/to where
     // `from` is a position or "bar"
     // `to` is a position or "off"
-    fn parse_submove(&self, game: &Backgammon, submove: &str)
+    fn parse_submove(&self, submove: &str)
         -> Result<Submove, CommandLineError> {
         let mut poss = submove.split("/");
         let from_str = try!(poss.next().ok_or("Invalid backgammon notation"));
-        let from = match from_str {
-            "bar" => 0,
-            _ =>
-                match from_str.parse::<usize>() {
-                    Ok(pos) => 
-                        match self.color {
-                            Color::Red => game.get_opposite_pos(pos),
-                            Color::White => pos,
-                        },
-                    Err(e) => return Err(e.description().to_string()),
-                }
-        };
+        let from = self.parse_pos(from_str, "bar", ::rustgammon::BAR_POS)?;
 
         let to_str = try!(poss.next().ok_or("Invalid backgammon notation"));
-        let to = match to_str {
-            "off" =>
-                match self.color {
-                    Color::Red => ::rustgammon::BEARING_OFF_POS,
-                    Color::White => ::rustgammon::BEARING_OFF_POS,
-                },
-            _ =>
-                match to_str.parse::<usize>() {
-                    Ok(pos) => 
-                        match self.color {
-                            Color::Red => game.get_opposite_pos(pos),
-                            Color::White => pos,
-                        },
-                    Err(e) => return Err(e.description().to_string()),
-                }
-        };
+        let to = self.parse_pos(to_str, "off", ::rustgammon::BEARING_OFF_POS)?;
 
         let die = if from < to {
             // The case for bearing off is handled in parse_move().
@@ -119,12 +146,12 @@ impl CommandLinePlayer {
         return Ok(Submove { from: from, die: die });
     }
 
-    fn parse_move(&self, game: &Backgammon, roll: DiceRoll, command: &str)
+    fn parse_move(&self, roll: DiceRoll, command: &str)
     -> Result<Move, CommandLineError> {
         let submove_iter = command.split_whitespace();
         let mut submoves = vec!();
         for submove in submove_iter {
-            submoves.push(try!(self.parse_submove(game, submove)));
+            submoves.push(try!(self.parse_submove(submove)));
         }
         // Handle die for bearing off.
         if roll.0 == roll.1 {
@@ -173,16 +200,112 @@ impl CommandLinePlayer {
             "list" => {
                 let moves = game.list_moves(self.get_color(), roll);
                 for mov in &moves {
-                    mov.print();
+                    println!("{}", mov);
                 }
                 Err("".to_string())
             },
-            "show" => {
-                game.print();
+            _ if command.split_whitespace().next() == Some("show") => {
+                let style = match command.split_whitespace().nth(1) {
+                    Some(text) => text.parse::<BoardStyle>()?,
+                    None => BoardStyle::Compact,
+                };
+                print!("{}", game.view_from(style, self.perspective, self.orientation));
+                Err("".to_string())
+            },
+            "race" => {
+                let assessments = race::assess_all(game, self.get_color(), true);
+                if assessments.is_empty() {
+                    println!("not a race");
+                }
+                for assessment in &assessments {
+                    println!("{}", assessment);
+                }
                 Err("".to_string())
             },
-            _ => self.parse_move(game, roll, command),
+            "cube hint" => {
+                for line in self.cube_hint(game)? {
+                    println!("{}", line);
+                }
+                Err("".to_string())
+            },
+            _ if command.split_whitespace().next() == Some("hint") => {
+                let count = match command.split_whitespace().nth(1) {
+                    Some(text) => match text.parse::<usize>() {
+                        Ok(count) => count,
+                        Err(e) => return Err(format!("hint: {}", e)),
+                    },
+                    None => DEFAULT_HINTS,
+                };
+                for line in self.hint(game, roll, count) {
+                    println!("{}", line);
+                }
+                Err("".to_string())
+            },
+            _ => self.parse_move(roll, command),
+        }
+    }
+
+    // Load the neural network once, so repeated hints don't read the weights again.
+    fn network(&self) -> Result<&BearoffEvaluator<NeuralNet>, CommandLineError> {
+        let network = self.network.get_or_init(|| {
+            return NeuralNet::load(neuralnet::DEFAULT_WEIGHTS_PATH).map(BearoffEvaluator::new);
+        });
+        return network.as_ref().map_err(|e| e.clone());
+    }
+
+    // Rank the moves with the neural network if its weights are available, or else with
+    // the heuristic under a line saying that its scores are not equities.
+    pub fn hint(&self, game: &Backgammon, roll: DiceRoll, count: usize) -> Vec<String> {
+        return match self.network() {
+            Ok(evaluator) => self.hint_lines(game, roll, evaluator, count),
+            Err(e) => {
+                debug!("hint with the heuristic: {}", e);
+                let heuristic: Heuristic = Default::default();
+                let mut lines = vec!("heuristic scores, not equities:".to_string());
+                lines.extend(self.hint_lines(game, roll, &heuristic, count));
+                lines
+            },
+        };
+    }
+
+    // The money cube decision with a centered cube, from the neural network or else from
+    // the race formulas.
+    fn cube_hint(&self, game: &Backgammon) -> Result<Vec<String>, CommandLineError> {
+        let evaluator = match self.network() {
+            Ok(evaluator) => evaluator,
+            Err(e) if game.is_race() => {
+                debug!("cube hint with the race formulas: {}", e);
+                let assessments = race::assess_all(game, self.color, true);
+                return Ok(assessments.iter().map(|assessment| assessment.to_string()).collect());
+            },
+            Err(e) => return Err(format!("cube hint: {}", e)),
+        };
+        let probabilities = cube::on_roll_probabilities(game, self.color, evaluator);
+        let mut lines = vec!(format!("win {:.1}%, gammon {:.1}%, lose gammon {:.1}%",
+                                     100.0 * probabilities.win, 100.0 * probabilities.win_gammon,
+                                     100.0 * probabilities.lose_gammon));
+        let cube: Cube = Default::default();
+        if let Some(decision) = cube::money_decision(&probabilities, &cube, self.color,
+                                                     cube::DEFAULT_CUBE_EFFICIENCY) {
+            lines.push(decision.to_string());
         }
+        return Ok(lines);
+    }
+
+    // The best moves in standard notation with their scores and their differences from the
+    // best score.
+    pub fn hint_lines<E: Evaluator>(&self, game: &Backgammon, roll: DiceRoll, evaluator: &E,
+                                    count: usize) -> Vec<String> {
+        let ranked = rank_moves(game, self.color, roll, evaluator);
+        let best = match ranked.first() {
+            Some(&(_, score)) => score,
+            None => return vec!(format_move_from(self.perspective, game, self.color,
+                                                 &Move { submoves: Vec::new() })),
+        };
+        return ranked.iter().take(count).enumerate().map(|(idx, &(ref mov, score))| {
+            format!("{:>2}. {:<24} {:+.3} ({:+.3})", idx + 1,
+                    format_move_from(self.perspective, game, self.color, mov), score, score - best)
+        }).collect();
     }
 }
 
@@ -0,0 +1,212 @@
+/*
+ * Rustgammon
+ *
+ * Cube decisions
+ * Turn the cubeless probabilities of the player on roll into the equities of not
+ * doubling, doubling with the opponent taking and doubling with the opponent passing.
+ * For money the cubeful equity is Janowski's interpolation between the dead cube, which
+ * is the cubeless equity, and the fully live cube, where a player doubles or cashes
+ * exactly at the opponent's take point. The cube efficiency is the weight of the live cube.
+ * For a match the equities come from the match equity table with the cube treated as
+ * dead, and are given as equivalent money equities, where -1 is losing and 1 winning the
+ * value of the cube.
+ * Equities are in units of the value of the cube before doubling.
+ *
+ * @author ryutaroikeda94@gmail.com
+ *
+ */
+
+use std::fmt;
+
+use evaluator::Probabilities;
+use evaluator::ProbabilityEvaluator;
+use met::MatchEquityTable;
+use met::MatchScore;
+use race::CubeAction;
+use rustgammon::Backgammon;
+use rustgammon::Color;
+
+// The efficiency of the cube in a typical contact position.
+pub const DEFAULT_CUBE_EFFICIENCY: f64 = 0.68;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Cube {
+    pub value: u32,
+    // The player who may double next, or None when the cube is centered.
+    pub owner: Option<Color>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CubeDecision {
+    pub no_double: f64,
+    pub double_take: f64,
+    pub double_pass: f64,
+    pub action: CubeAction,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Owner {
+    Centered,
+    Player,
+    Opponent,
+}
+
+impl Default for Cube {
+    fn default() -> Cube {
+        return Cube { value: 1, owner: None };
+    }
+}
+
+impl Cube {
+    pub fn can_double(&self, color: Color) -> bool {
+        return self.owner.is_none() || self.owner == Some(color);
+    }
+
+    fn owner_for(&self, color: Color) -> Owner {
+        return match self.owner {
+            None => Owner::Centered,
+            Some(owner) if owner == color => Owner::Player,
+            Some(_) => Owner::Opponent,
+        };
+    }
+}
+
+// The probabilities for the player on roll, from the evaluator's probabilities for the
+// opponent who has just moved.
+pub fn on_roll_probabilities<E: ProbabilityEvaluator>(game: &Backgammon, color: Color,
+                                                      evaluator: &E) -> Probabilities {
+    return evaluator.probabilities(game, color.opposite()).flip();
+}
+
+// The average points won when winning and lost when losing.
+fn average_results(probabilities: &Probabilities) -> (f64, f64) {
+    let win = probabilities.win;
+    let won = if 0.0 < win {
+        (win + probabilities.win_gammon + probabilities.win_backgammon) / win
+    } else {
+        1.0
+    };
+    let lose = 1.0 - win;
+    let lost = if 0.0 < lose {
+        (lose + probabilities.lose_gammon + probabilities.lose_backgammon) / lose
+    } else {
+        1.0
+    };
+    return (won, lost);
+}
+
+// The cubeful money equity for the owner of the cube.
+fn money_equity(probabilities: &Probabilities, owner: Owner, cube_efficiency: f64) -> f64 {
+    let win = probabilities.win;
+    let (won, lost) = average_results(probabilities);
+    let dead = probabilities.equity();
+    // With a live cube the opponent passes from our cash point and we pass below our take
+    // point, and the equity is linear in between.
+    let slope = won + lost + 0.5;
+    let take_point = (lost - 0.5) / slope;
+    let cash_point = (lost + 1.0) / slope;
+    let live = match owner {
+        Owner::Player if cash_point <= win => 1.0,
+        Owner::Player => win * slope - lost,
+        Owner::Opponent if win <= take_point => -1.0,
+        Owner::Opponent => win * slope - lost - 0.5,
+        Owner::Centered if cash_point <= win => 1.0,
+        Owner::Centered if win <= take_point => -1.0,
+        Owner::Centered => -1.0 + 2.0 * (win - take_point) / (cash_point - take_point),
+    };
+    return cube_efficiency * live + (1.0 - cube_efficiency) * dead;
+}
+
+fn decide(no_double: f64, double_take: f64, double_pass: f64) -> CubeDecision {
+    let take = double_take <= double_pass;
+    let double = if take { double_take } else { double_pass };
+    let action = if double <= no_double {
+        CubeAction::NoDouble
+    } else if take {
+        CubeAction::DoubleTake
+    } else {
+        CubeAction::DoublePass
+    };
+    return CubeDecision {
+        no_double: no_double,
+        double_take: double_take,
+        double_pass: double_pass,
+        action: action,
+    };
+}
+
+// The money cube decision for the player on roll, or None if it cannot double.
+pub fn money_decision(probabilities: &Probabilities, cube: &Cube, color: Color,
+                      cube_efficiency: f64) -> Option<CubeDecision> {
+    if !cube.can_double(color) {
+        return None;
+    }
+    let no_double = money_equity(probabilities, cube.owner_for(color), cube_efficiency);
+    let double_take = 2.0 * money_equity(probabilities, Owner::Opponent, cube_efficiency);
+    return Some(decide(no_double, double_take, 1.0));
+}
+
+// The probability of winning the match if the game is played to the end with the cube.
+fn dead_cube_match_equity(probabilities: &Probabilities, table: &MatchEquityTable,
+                          score: &MatchScore, value: u32) -> f64 {
+    let value = value as i32;
+    let p = probabilities;
+    return (p.win - p.win_gammon) * table.equity_after(score, value) +
+        (p.win_gammon - p.win_backgammon) * table.equity_after(score, 2 * value) +
+        p.win_backgammon * table.equity_after(score, 3 * value) +
+        (1.0 - p.win - p.lose_gammon) * table.equity_after(score, -value) +
+        (p.lose_gammon - p.lose_backgammon) * table.equity_after(score, -2 * value) +
+        p.lose_backgammon * table.equity_after(score, -3 * value);
+}
+
+// The match cube decision for the player on roll, or None if it cannot double.
+pub fn match_decision(probabilities: &Probabilities, cube: &Cube, color: Color,
+                      score: &MatchScore, table: &MatchEquityTable) -> Option<CubeDecision> {
+    if score.crawford || !cube.can_double(color) {
+        return None;
+    }
+    let win = table.equity_after(score, cube.value as i32);
+    let lose = table.equity_after(score, -(cube.value as i32));
+    // Scale the match equities so that winning and losing the cube are 1 and -1.
+    let normalize = |equity: f64| {
+        if win <= lose { 0.0 } else { 2.0 * (equity - lose) / (win - lose) - 1.0 }
+    };
+    let no_double = dead_cube_match_equity(probabilities, table, score, cube.value);
+    let double_take = dead_cube_match_equity(probabilities, table, score, 2 * cube.value);
+    return Some(decide(normalize(no_double), normalize(double_take), 1.0));
+}
+
+impl CubeDecision {
+    // Whether the opponent should take a double.
+    pub fn is_take(&self) -> bool {
+        return self.double_take <= self.double_pass;
+    }
+
+    // Whether the player should play on for a gammon instead of cashing.
+    pub fn is_too_good(&self) -> bool {
+        return self.action == CubeAction::NoDouble && !self.is_take() &&
+            self.double_pass <= self.no_double;
+    }
+
+    // The equity of the best choice of both players.
+    pub fn equity(&self) -> f64 {
+        return self.no_double.max(self.double_take.min(self.double_pass));
+    }
+}
+
+impl fmt::Display for CubeDecision {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let recommendation = if self.is_too_good() {
+            "too good, pass"
+        } else {
+            match (self.action, self.is_take()) {
+                (CubeAction::NoDouble, true) => "no double, take",
+                (CubeAction::NoDouble, false) => "no double, pass",
+                (CubeAction::DoubleTake, _) => "double, take",
+                (CubeAction::DoublePass, _) => "double, pass",
+            }
+        };
+        write!(f, "no double {:+.3}, double/take {:+.3}, double/pass {:+.3}: {}",
+               self.no_double, self.double_take, self.double_pass, recommendation)
+    }
+}
@@ -0,0 +1,312 @@
+/*
+ * Rustgammon
+ *
+ * Dice statistics
+ * Audit a sequence of recorded rolls for fairness.
+ *
+ * @author ryutaroikeda94@gmail.com
+ *
+ */
+
+use gamerecord::GameRecord;
+use gamerecord::parse_roll;
+use perft::roll_index;
+use rustgammon::Backgammon;
+use rustgammon::BAR_POS;
+use rustgammon::Color;
+use rustgammon::DiceRoll;
+use rustgammon::Move;
+
+pub type DiceStatsError = String;
+
+// A roll is a joker if it hits or enters from the bar when at most this many of the 36
+// rolls would have.
+pub const JOKER_MAX_ROLLS: usize = 11;
+
+pub struct DiceStats {
+    pub rolls: usize,
+    // How often each face came up, counting both dice.
+    pub faces: [usize; 6],
+    pub doubles: usize,
+    // Chi-squared test of the faces against a fair die.
+    pub face_chi_squared: f64,
+    pub face_p_value: f64,
+    // Chi-squared test of the 21 distinct rolls against fair dice.
+    pub roll_chi_squared: f64,
+    pub roll_p_value: f64,
+    pub longest_doubles_run: usize,
+    pub longest_non_doubles_run: usize,
+    pub longest_repeated_roll_run: usize,
+}
+
+#[derive(Default)]
+pub struct JokerCounts {
+    pub red_turns: usize,
+    pub red_jokers: usize,
+    pub white_turns: usize,
+    pub white_jokers: usize,
+}
+
+impl DiceStats {
+    pub fn new(rolls: &[DiceRoll]) -> DiceStats {
+        let mut faces = [0; 6];
+        let mut roll_counts = [0usize; 21];
+        let mut doubles = 0;
+        let mut longest_doubles_run = 0;
+        let mut longest_non_doubles_run = 0;
+        let mut longest_repeated_roll_run = 0;
+        let mut doubles_run = 0;
+        let mut non_doubles_run = 0;
+        let mut repeated_roll_run = 0;
+        let mut previous: Option<DiceRoll> = None;
+        for roll in rolls {
+            faces[roll.0 - 1] += 1;
+            faces[roll.1 - 1] += 1;
+            roll_counts[roll_index(*roll)] += 1;
+            if roll.0 == roll.1 {
+                doubles += 1;
+                doubles_run += 1;
+                non_doubles_run = 0;
+            } else {
+                non_doubles_run += 1;
+                doubles_run = 0;
+            }
+            let is_repeat = match previous {
+                Some(prev) => roll_index(prev) == roll_index(*roll),
+                None => false,
+            };
+            repeated_roll_run = if is_repeat { repeated_roll_run + 1 } else { 1 };
+            previous = Some(*roll);
+            longest_doubles_run = std::cmp::max(longest_doubles_run, doubles_run);
+            longest_non_doubles_run = std::cmp::max(longest_non_doubles_run, non_doubles_run);
+            longest_repeated_roll_run = std::cmp::max(longest_repeated_roll_run, repeated_roll_run);
+        }
+
+        let face_expected = [rolls.len() as f64 * 2.0 / 6.0; 6];
+        let mut roll_expected = [0.0; 21];
+        for low in 1..7 {
+            for high in low..7 {
+                let ways = if low == high { 1.0 } else { 2.0 };
+                roll_expected[roll_index((low, high))] = rolls.len() as f64 * ways / 36.0;
+            }
+        }
+        let face_chi_squared = chi_squared(&faces, &face_expected);
+        let roll_chi_squared = chi_squared(&roll_counts, &roll_expected);
+        return DiceStats {
+            rolls: rolls.len(),
+            faces: faces,
+            doubles: doubles,
+            face_chi_squared: face_chi_squared,
+            face_p_value: chi_squared_p_value(face_chi_squared, 5),
+            roll_chi_squared: roll_chi_squared,
+            roll_p_value: chi_squared_p_value(roll_chi_squared, 20),
+            longest_doubles_run: longest_doubles_run,
+            longest_non_doubles_run: longest_non_doubles_run,
+            longest_repeated_roll_run: longest_repeated_roll_run,
+        };
+    }
+
+    pub fn print(&self) {
+        println!("rolls: {}", self.rolls);
+        let dice = 2 * self.rolls;
+        for (face, count) in self.faces.iter().enumerate() {
+            println!("  {}: {:>6} {:>6.2}% (expected 16.67%)",
+                     face + 1, count, percent(*count, dice));
+        }
+        println!("doubles: {} {:.2}% (expected 16.67%)",
+                 self.doubles, percent(self.doubles, self.rolls));
+        println!("faces chi-squared: {:.3} (5 degrees of freedom), p = {:.4}",
+                 self.face_chi_squared, self.face_p_value);
+        println!("rolls chi-squared: {:.3} (20 degrees of freedom), p = {:.4}",
+                 self.roll_chi_squared, self.roll_p_value);
+        println!("longest run of doubles: {}", self.longest_doubles_run);
+        println!("longest run without doubles: {}", self.longest_non_doubles_run);
+        println!("longest run of the same roll: {}", self.longest_repeated_roll_run);
+    }
+}
+
+impl JokerCounts {
+    pub fn add(&mut self, other: &JokerCounts) {
+        self.red_turns += other.red_turns;
+        self.red_jokers += other.red_jokers;
+        self.white_turns += other.white_turns;
+        self.white_jokers += other.white_jokers;
+    }
+
+    pub fn print(&self) {
+        println!("jokers (hits or entries that at most {} of 36 rolls make):",
+                 JOKER_MAX_ROLLS);
+        println!("  red: {} in {} turns", self.red_jokers, self.red_turns);
+        println!("  white: {} in {} turns", self.white_jokers, self.white_turns);
+    }
+}
+
+fn percent(count: usize, total: usize) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    return 100.0 * count as f64 / total as f64;
+}
+
+fn chi_squared(observed: &[usize], expected: &[f64]) -> f64 {
+    let mut sum = 0.0;
+    for (o, e) in observed.iter().zip(expected.iter()) {
+        if 0.0 < *e {
+            let diff = *o as f64 - e;
+            sum += diff * diff / e;
+        }
+    }
+    return sum;
+}
+
+fn ln_gamma(x: f64) -> f64 {
+    // Lanczos approximation.
+    let coefficients = [76.18009172947146, -86.50532032941677, 24.01409824083091,
+                        -1.231739572450155, 0.1208650973866179e-2, -0.5395239384953e-5];
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+    let mut series = 1.000000000190015;
+    let mut y = x;
+    for c in &coefficients {
+        y += 1.0;
+        series += c / y;
+    }
+    return -tmp + (2.5066282746310005 * series / x).ln();
+}
+
+// The regularized upper incomplete gamma function Q(a, x).
+fn upper_incomplete_gamma(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    let iterations = 500;
+    let epsilon = 1e-12;
+    if x < a + 1.0 {
+        // Series for the lower function.
+        let mut term = 1.0 / a;
+        let mut sum = term;
+        let mut n = a;
+        for _ in 0..iterations {
+            n += 1.0;
+            term *= x / n;
+            sum += term;
+            if term.abs() < sum.abs() * epsilon {
+                break;
+            }
+        }
+        return 1.0 - sum * (-x + a * x.ln() - ln_gamma(a)).exp();
+    }
+    // Continued fraction for the upper function.
+    let tiny = 1e-300;
+    let mut b = x + 1.0 - a;
+    let mut c = 1.0 / tiny;
+    let mut d = 1.0 / b;
+    let mut h = d;
+    for i in 1..iterations {
+        let an = -(i as f64) * (i as f64 - a);
+        b += 2.0;
+        d = an * d + b;
+        if d.abs() < tiny {
+            d = tiny;
+        }
+        c = b + an / c;
+        if c.abs() < tiny {
+            c = tiny;
+        }
+        d = 1.0 / d;
+        let delta = d * c;
+        h *= delta;
+        if (delta - 1.0).abs() < epsilon {
+            break;
+        }
+    }
+    return (-x + a * x.ln() - ln_gamma(a)).exp() * h;
+}
+
+// The probability of a chi-squared statistic at least this large from fair dice.
+pub fn chi_squared_p_value(chi_squared: f64, degrees_of_freedom: usize) -> f64 {
+    return upper_incomplete_gamma(degrees_of_freedom as f64 / 2.0, chi_squared / 2.0);
+}
+
+// Parse rolls written as 3-1 or 31, separated by whitespace or commas.
+pub fn parse_rolls(text: &str) -> Result<Vec<DiceRoll>, DiceStatsError> {
+    let mut rolls = Vec::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+        for word in line.split(|c: char| c.is_whitespace() || c == ',') {
+            if word.is_empty() {
+                continue;
+            }
+            if word.len() == 2 && word.is_ascii() {
+                rolls.push(parse_roll(&format!("{}-{}", &word[0..1], &word[1..2]))?);
+            } else {
+                rolls.push(parse_roll(word)?);
+            }
+        }
+    }
+    return Ok(rolls);
+}
+
+fn bar_checkers(game: &Backgammon, color: Color) -> i8 {
+    return game.get_board(color, BAR_POS);
+}
+
+fn hits(game: &Backgammon, color: Color, mov: &Move) -> bool {
+    let mut next = *game;
+    next.do_move(color, mov);
+    return bar_checkers(game, color.opposite()) < bar_checkers(&next, color.opposite());
+}
+
+fn enters(game: &Backgammon, color: Color, mov: &Move) -> bool {
+    let mut next = *game;
+    next.do_move(color, mov);
+    return bar_checkers(&next, color) == 0;
+}
+
+// Count how many of the 36 rolls allow a move with the property.
+fn count_rolls<F>(game: &Backgammon, color: Color, property: F) -> usize
+    where F: Fn(&Backgammon, Color, &Move) -> bool {
+    let mut count = 0;
+    for a in 1..7 {
+        for b in a..7 {
+            let moves = game.list_moves(color, (a, b));
+            if moves.iter().any(|mov| property(game, color, mov)) {
+                count += if a == b { 1 } else { 2 };
+            }
+        }
+    }
+    return count;
+}
+
+// Replay a game and count the rolls that hit or entered against the odds.
+pub fn count_jokers(record: &GameRecord) -> JokerCounts {
+    let mut counts: JokerCounts = Default::default();
+    let mut game = record.start;
+    for turn in &record.turns {
+        let color = turn.color;
+        let mut is_joker = false;
+        if hits(&game, color, &turn.mov) &&
+            count_rolls(&game, color, hits) <= JOKER_MAX_ROLLS {
+            is_joker = true;
+        }
+        if 0 < bar_checkers(&game, color) && enters(&game, color, &turn.mov) &&
+            count_rolls(&game, color, enters) <= JOKER_MAX_ROLLS {
+            is_joker = true;
+        }
+        match color {
+            Color::Red => {
+                counts.red_turns += 1;
+                counts.red_jokers += is_joker as usize;
+            },
+            Color::White => {
+                counts.white_turns += 1;
+                counts.white_jokers += is_joker as usize;
+            },
+        }
+        game.do_move(color, &turn.mov);
+    }
+    return counts;
+}
@@ -0,0 +1,116 @@
+/*
+ * Rustgammon
+ *
+ * Evaluator
+ * Static evaluation of positions for computer players.
+ *
+ * @author ryutaroikeda94@gmail.com
+ *
+ */
+
+use std::collections::HashSet;
+
+use heuristicplayer::Heuristic;
+use packed::PackedPosition;
+use rustgammon::Backgammon;
+use rustgammon::Color;
+use rustgammon::DiceRoll;
+use rustgammon::Move;
+
+pub trait Evaluator {
+    // Score the position for the player who has just moved. Higher is better.
+    fn evaluate(&self, game: &Backgammon, color: Color) -> f64;
+
+    // Score a finished game for the player. Evaluators scoring in points per game give the
+    // points won or lost; the others score the final position.
+    fn evaluate_finished(&self, game: &Backgammon, winner: Color, color: Color) -> f64 {
+        let _ = winner;
+        return self.evaluate(game, color);
+    }
+}
+
+impl Evaluator for Heuristic {
+    fn evaluate(&self, game: &Backgammon, color: Color) -> f64 {
+        return Heuristic::evaluate(self, game, color);
+    }
+}
+
+impl<E: Evaluator + ?Sized> Evaluator for &E {
+    fn evaluate(&self, game: &Backgammon, color: Color) -> f64 {
+        return (**self).evaluate(game, color);
+    }
+
+    fn evaluate_finished(&self, game: &Backgammon, winner: Color, color: Color) -> f64 {
+        return (**self).evaluate_finished(game, winner, color);
+    }
+}
+
+impl<E: Evaluator + ?Sized> Evaluator for Box<E> {
+    fn evaluate(&self, game: &Backgammon, color: Color) -> f64 {
+        return (**self).evaluate(game, color);
+    }
+
+    fn evaluate_finished(&self, game: &Backgammon, winner: Color, color: Color) -> f64 {
+        return (**self).evaluate_finished(game, winner, color);
+    }
+}
+
+// Cubeless probabilities of the results of the game for a player. The gammon
+// probabilities include backgammons and the win probability includes gammons.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Probabilities {
+    pub win: f64,
+    pub win_gammon: f64,
+    pub win_backgammon: f64,
+    pub lose_gammon: f64,
+    pub lose_backgammon: f64,
+}
+
+impl Probabilities {
+    // The cubeless equity in points per game.
+    pub fn equity(&self) -> f64 {
+        return 2.0 * self.win - 1.0 + self.win_gammon - self.lose_gammon +
+            self.win_backgammon - self.lose_backgammon;
+    }
+
+    // The probabilities for the opponent.
+    pub fn flip(&self) -> Probabilities {
+        return Probabilities {
+            win: 1.0 - self.win,
+            win_gammon: self.lose_gammon,
+            win_backgammon: self.lose_backgammon,
+            lose_gammon: self.win_gammon,
+            lose_backgammon: self.win_backgammon,
+        };
+    }
+}
+
+pub trait ProbabilityEvaluator: Evaluator {
+    // The probabilities for the player who has just moved.
+    fn probabilities(&self, game: &Backgammon, color: Color) -> Probabilities;
+}
+
+// The legal moves with their resulting positions. Moves leading to the same position are
+// listed once.
+pub fn distinct_moves(game: &Backgammon, color: Color, roll: DiceRoll) -> Vec<(Move, Backgammon)> {
+    let mut seen = HashSet::new();
+    let mut moves = Vec::new();
+    for mov in game.list_moves(color, roll) {
+        let mut next = *game;
+        next.do_move(color, &mov);
+        if seen.insert(PackedPosition::new(&next)) {
+            moves.push((mov, next));
+        }
+    }
+    return moves;
+}
+
+// The distinct moves with the scores of their resulting positions, best first.
+pub fn rank_moves<E: Evaluator>(game: &Backgammon, color: Color, roll: DiceRoll, evaluator: &E)
+    -> Vec<(Move, f64)> {
+    let mut ranked: Vec<(Move, f64)> = distinct_moves(game, color, roll).into_iter()
+        .map(|(mov, next)| (mov, evaluator.evaluate(&next, color)))
+        .collect();
+    ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(::std::cmp::Ordering::Equal));
+    return ranked;
+}
@@ -0,0 +1,145 @@
+/*
+ * Rustgammon
+ *
+ * Provably fair dice
+ * Each player commits to the SHA-256 hash of a secret. Once both commitments are
+ * exchanged the secrets are revealed and the rolls are derived from the combined seed,
+ * so neither player can bias the dice without the other noticing.
+ *
+ * @author ryutaroikeda94@gmail.com
+ *
+ */
+
+extern crate rand;
+
+use rand::OsRng;
+use rand::Rng;
+
+use gamerecord::GameRecord;
+use rustgammon::Dice;
+use rustgammon::DiceRoll;
+use rustgammon::Die;
+use sha256::Digest;
+use sha256::sha256;
+
+pub const SECRET_SIZE: usize = 32;
+
+pub type Secret = [u8; SECRET_SIZE];
+
+pub type FairDiceError = String;
+
+pub fn generate_secret() -> Secret {
+    let mut secret = [0u8; SECRET_SIZE];
+    let mut rng = OsRng::new().expect("no operating system random number generator");
+    rng.fill_bytes(&mut secret);
+    return secret;
+}
+
+pub fn commit(secret: &[u8]) -> Digest {
+    return sha256(secret);
+}
+
+// The length prefix stops a player from moving bytes between the two secrets.
+pub fn combine_secrets(first: &[u8], second: &[u8]) -> Digest {
+    let mut data = Vec::with_capacity(8 + first.len() + second.len());
+    let first_len = first.len() as u64;
+    for i in 0..8 {
+        data.push((first_len >> (56 - 8 * i)) as u8);
+    }
+    data.extend_from_slice(first);
+    data.extend_from_slice(second);
+    return sha256(&data);
+}
+
+// Dice derived from a seed by hashing the seed with a block counter.
+pub struct FairDice {
+    seed: Digest,
+    counter: u64,
+    block: Digest,
+    index: usize,
+}
+
+impl FairDice {
+    pub fn new(seed: Digest) -> FairDice {
+        return FairDice { seed: seed, counter: 0, block: [0; 32], index: 32 };
+    }
+
+    pub fn from_secrets(first: &[u8], second: &[u8]) -> FairDice {
+        return FairDice::new(combine_secrets(first, second));
+    }
+
+    fn next_byte(&mut self) -> u8 {
+        if self.index == self.block.len() {
+            let mut data = [0u8; 40];
+            data[..32].copy_from_slice(&self.seed);
+            for i in 0..8 {
+                data[32 + i] = (self.counter >> (56 - 8 * i)) as u8;
+            }
+            self.block = sha256(&data);
+            self.counter += 1;
+            self.index = 0;
+        }
+        let byte = self.block[self.index];
+        self.index += 1;
+        return byte;
+    }
+
+    fn roll_die(&mut self) -> Die {
+        // Reject the bytes that would make the faces uneven. 252 is the largest multiple of 6.
+        loop {
+            let byte = self.next_byte();
+            if byte < 252 {
+                return (byte % 6) as Die + 1;
+            }
+        }
+    }
+}
+
+impl Dice for FairDice {
+    fn roll(&mut self) -> DiceRoll {
+        let a = self.roll_die();
+        let b = self.roll_die();
+        return (a, b);
+    }
+}
+
+// Check the revealed secrets against the commitments and replay a finished game: each roll
+// must come from the seed, each move must be legal and the winner must be the one recorded.
+pub fn verify(first_commitment: &Digest, first_secret: &[u8],
+              second_commitment: &Digest, second_secret: &[u8],
+              record: &GameRecord) -> Result<(), FairDiceError> {
+    if commit(first_secret) != *first_commitment {
+        return Err("first secret does not match its commitment".to_string());
+    }
+    if commit(second_secret) != *second_commitment {
+        return Err("second secret does not match its commitment".to_string());
+    }
+    let mut dice = FairDice::from_secrets(first_secret, second_secret);
+    let mut game = record.start;
+    for (turn_idx, turn) in record.turns.iter().enumerate() {
+        if game.winner().is_some() {
+            return Err(format!("turn {}: the game was already over", turn_idx + 1));
+        }
+        let roll = dice.roll();
+        if roll != turn.roll {
+            return Err(format!("turn {}: recorded roll {}-{} but the seed gives {}-{}",
+                               turn_idx + 1, turn.roll.0, turn.roll.1, roll.0, roll.1));
+        }
+        if !game.can_do_move(turn.color, turn.roll, &turn.mov) {
+            return Err(format!("turn {}: illegal move for {}", turn_idx + 1, turn.color));
+        }
+        game.do_move(turn.color, &turn.mov);
+    }
+    // A passed double ends the game before it is played out.
+    let passed = record.doubles.iter().find(|double| !double.taken).map(|double| double.color);
+    let winner = match passed.or_else(|| game.winner()) {
+        Some(winner) => winner,
+        None => return Err("the game is not finished".to_string()),
+    };
+    if record.winner != Some(winner) {
+        return Err(format!("the record gives the winner as {} but {} won",
+                           record.winner.map_or("nobody".to_string(), |color| color.to_string()),
+                           winner));
+    }
+    return Ok(());
+}
@@ -0,0 +1,188 @@
+/*
+ * Rustgammon
+ *
+ * Game record
+ * The starting position and every turn played in a game.
+ *
+ * Records are saved as text, one entry per line:
+ *   board red 0 2 0 0 ...      checkers on each of the 26 positions of a player
+ *   turn red 3-1 12:3 17:1     the roll and each submove as from:die in the order played
+ *   double red take            a double before the next turn and the opponent's response
+ *   winner red
+ * Lines starting with # are comments.
+ *
+ * @author ryutaroikeda94@gmail.com
+ *
+ */
+
+use std::io;
+use std::io::BufRead;
+
+use rustgammon::Backgammon;
+use rustgammon::BOARD_SIZE;
+use rustgammon::Checker;
+use rustgammon::Color;
+use rustgammon::DiceRoll;
+use rustgammon::Die;
+use rustgammon::Move;
+use rustgammon::Submove;
+
+pub type GameRecordError = String;
+
+#[derive(Clone, Debug)]
+pub struct Turn {
+    pub color: Color,
+    pub roll: DiceRoll,
+    // The move is empty if there were no legal moves.
+    pub mov: Move,
+}
+
+// A double by the player before the turn with the index, which may be one past the last
+// turn if the double was passed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Double {
+    pub turn: usize,
+    pub color: Color,
+    pub taken: bool,
+}
+
+#[derive(Clone)]
+pub struct GameRecord {
+    pub start: Backgammon,
+    pub turns: Vec<Turn>,
+    pub doubles: Vec<Double>,
+    pub winner: Option<Color>,
+}
+
+impl GameRecord {
+    pub fn new(start: &Backgammon) -> GameRecord {
+        return GameRecord { start: *start, turns: Vec::new(), doubles: Vec::new(), winner: None };
+    }
+
+    pub fn rolls(&self) -> Vec<DiceRoll> {
+        return self.turns.iter().map(|turn| turn.roll).collect();
+    }
+
+    pub fn write<W: io::Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "# rustgammon game record")?;
+        for color in &[Color::Red, Color::White] {
+            write!(out, "board {}", color)?;
+            for pos in 0..BOARD_SIZE {
+                write!(out, " {}", self.start.get_board(*color, pos))?;
+            }
+            writeln!(out)?;
+        }
+        for (turn_idx, turn) in self.turns.iter().enumerate() {
+            self.write_doubles(out, turn_idx)?;
+            write!(out, "turn {} {}-{}", turn.color, turn.roll.0, turn.roll.1)?;
+            // Submoves are stored as a stack, so write them in reverse.
+            for submove in turn.mov.submoves.iter().rev() {
+                write!(out, " {}:{}", submove.from, submove.die)?;
+            }
+            writeln!(out)?;
+        }
+        self.write_doubles(out, self.turns.len())?;
+        if let Some(winner) = self.winner {
+            writeln!(out, "winner {}", winner)?;
+        }
+        return Ok(());
+    }
+
+    fn write_doubles<W: io::Write>(&self, out: &mut W, turn_idx: usize) -> io::Result<()> {
+        for double in self.doubles.iter().filter(|double| double.turn == turn_idx) {
+            writeln!(out, "double {} {}", double.color, if double.taken { "take" } else { "pass" })?;
+        }
+        return Ok(());
+    }
+
+    pub fn read<R: BufRead>(input: R) -> Result<GameRecord, GameRecordError> {
+        let mut record = GameRecord::new(&Default::default());
+        // The position after the turns read so far, to check that each move is legal.
+        let mut game = record.start;
+        for (line_idx, line) in input.lines().enumerate() {
+            let line = match line {
+                Ok(v) => v,
+                Err(e) => return Err(e.to_string()),
+            };
+            match parse_line(&mut record, &mut game, &line) {
+                Ok(_) => (),
+                Err(e) => return Err(format!("line {}: {}", line_idx + 1, e)),
+            }
+        }
+        return Ok(record);
+    }
+}
+
+fn parse_line(record: &mut GameRecord, game: &mut Backgammon, line: &str) -> Result<(), GameRecordError> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(());
+    }
+    let mut words = line.split_whitespace();
+    let kind = words.next().unwrap_or("");
+    let color = words.next().ok_or("missing color")?.parse::<Color>()?;
+    match kind {
+        "board" => {
+            for pos in 0..BOARD_SIZE {
+                let word = words.next().ok_or("too few positions")?;
+                let checkers = word.parse::<Checker>().map_err(|e| e.to_string())?;
+                record.start.set_board(color, pos, checkers);
+                game.set_board(color, pos, checkers);
+            }
+        },
+        "turn" => {
+            let roll = parse_roll(words.next().ok_or("missing roll")?)?;
+            let words: Vec<&str> = words.collect();
+            let mut submoves = Vec::new();
+            for word in &words {
+                submoves.push(parse_submove(word)?);
+            }
+            submoves.reverse();
+            let mov = Move { submoves: submoves };
+            if !game.can_do_move(color, roll, &mov) {
+                return Err(format!("illegal move {} for {} {}-{}", words.join(" "), color, roll.0, roll.1));
+            }
+            game.do_move(color, &mov);
+            record.turns.push(Turn { color: color, roll: roll, mov: mov });
+        },
+        "double" => {
+            let taken = match words.next() {
+                Some("take") => true,
+                Some("pass") => false,
+                _ => return Err("expected take or pass".to_string()),
+            };
+            record.doubles.push(Double { turn: record.turns.len(), color: color, taken: taken });
+        },
+        "winner" => record.winner = Some(color),
+        _ => return Err(format!("unknown entry {}", kind)),
+    }
+    return Ok(());
+}
+
+fn parse_die(word: &str) -> Result<Die, GameRecordError> {
+    return match word.parse::<Die>() {
+        Ok(die) if (1..7).contains(&die) => Ok(die),
+        _ => Err(format!("invalid die {}", word)),
+    }
+}
+
+// A roll is written as 3-1.
+pub fn parse_roll(word: &str) -> Result<DiceRoll, GameRecordError> {
+    let mut dice = word.split('-');
+    let a = parse_die(dice.next().unwrap_or(""))?;
+    let b = parse_die(dice.next().ok_or(format!("invalid roll {}", word))?)?;
+    if dice.next().is_some() {
+        return Err(format!("invalid roll {}", word));
+    }
+    return Ok((a, b));
+}
+
+fn parse_submove(word: &str) -> Result<Submove, GameRecordError> {
+    let mut parts = word.split(':');
+    let from = match parts.next().unwrap_or("").parse::<usize>() {
+        Ok(from) if from < BOARD_SIZE => from,
+        _ => return Err(format!("invalid submove {}", word)),
+    };
+    let die = parse_die(parts.next().ok_or(format!("invalid submove {}", word))?)?;
+    return Ok(Submove { from: from, die: die });
+}
@@ -0,0 +1,219 @@
+/*
+ * Rustgammon
+ *
+ * Heuristic player
+ * Play the legal move leading to the position with the best weighted evaluation.
+ *
+ * @author ryutaroikeda94@gmail.com
+ *
+ */
+
+use std::fs::File;
+use std::io::Read;
+use std::sync::Arc;
+
+use bearoff::BearoffDatabases;
+use positionstats::PositionStats;
+use rustgammon::Backgammon;
+use rustgammon::BAR_POS;
+use rustgammon::BEARING_OFF_POS;
+use rustgammon::Color;
+use rustgammon::DiceRoll;
+use rustgammon::Move;
+use rustgammon::Player;
+
+pub type HeuristicError = String;
+
+pub const DEFAULT_CONFIG_PATH: &str = "config/heuristic.yaml";
+
+// Weights of the features of a position, all from the point of view of the player.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Heuristic {
+    // Per pip of lead in the race.
+    pub pip_count: f64,
+    // Per checker borne off more than the opponent.
+    pub off: f64,
+    // Per opponent checker on the bar.
+    pub opponent_bar: f64,
+    // Per opponent checker that is within 6 pips of one of our blots.
+    pub direct_shots: f64,
+    // Per opponent checker that is 7 to 12 pips from one of our blots.
+    pub indirect_shots: f64,
+    pub made_points: f64,
+    // Per point in the longest prime.
+    pub prime_length: f64,
+    // Per made point in the opponent's home board.
+    pub anchors: f64,
+    pub home_board_points: f64,
+}
+
+pub struct HeuristicPlayer {
+    pub color: Color,
+    pub heuristic: Heuristic,
+    // The bearoff databases, to score the moves of a pure bearoff. None are given by default.
+    pub bearoff: Arc<BearoffDatabases>,
+}
+
+impl Default for Heuristic {
+    fn default() -> Heuristic {
+        return Heuristic {
+            pip_count: 1.0,
+            off: 2.0,
+            opponent_bar: 8.0,
+            direct_shots: 4.0,
+            indirect_shots: 1.5,
+            made_points: 2.0,
+            prime_length: 3.0,
+            anchors: 3.0,
+            home_board_points: 3.0,
+        };
+    }
+}
+
+// Count the opponent checkers that are a direct and an indirect shot away from our blots.
+fn count_shots(game: &Backgammon, color: Color) -> (u32, u32) {
+    let opponent = color.opposite();
+    let mut direct = 0;
+    let mut indirect = 0;
+    for pos in (BAR_POS + 1)..BEARING_OFF_POS {
+        if game.get_board(color, pos) != 1 {
+            continue;
+        }
+        // The opponent hits the blot by landing on the opposite position.
+        let target = game.get_opposite_pos(pos);
+        for opponent_pos in BAR_POS..target {
+            let checkers = game.get_board(opponent, opponent_pos) as u32;
+            let distance = target - opponent_pos;
+            if distance <= 6 {
+                direct += checkers;
+            } else if distance <= 12 {
+                indirect += checkers;
+            }
+        }
+    }
+    return (direct, indirect);
+}
+
+// Count our made points in the opponent's home board.
+fn count_anchors(game: &Backgammon, color: Color) -> u32 {
+    let mut anchors = 0;
+    for pos in 1..7 {
+        if 2 <= game.get_board(color, pos) {
+            anchors += 1;
+        }
+    }
+    return anchors;
+}
+
+impl Heuristic {
+    pub fn parse(text: &str) -> Result<Heuristic, HeuristicError> {
+        let mut heuristic: Heuristic = Default::default();
+        for (line_idx, line) in text.lines().enumerate() {
+            let line = match line.find('#') {
+                Some(idx) => &line[..idx],
+                None => line,
+            }.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut parts = line.splitn(2, ':');
+            let name = parts.next().unwrap_or("").trim();
+            let value = match parts.next().map(|v| v.trim().parse::<f64>()) {
+                Some(Ok(v)) => v,
+                _ => return Err(format!("line {}: expected name: number", line_idx + 1)),
+            };
+            let weight = match name {
+                "pip_count" => &mut heuristic.pip_count,
+                "off" => &mut heuristic.off,
+                "opponent_bar" => &mut heuristic.opponent_bar,
+                "direct_shots" => &mut heuristic.direct_shots,
+                "indirect_shots" => &mut heuristic.indirect_shots,
+                "made_points" => &mut heuristic.made_points,
+                "prime_length" => &mut heuristic.prime_length,
+                "anchors" => &mut heuristic.anchors,
+                "home_board_points" => &mut heuristic.home_board_points,
+                _ => return Err(format!("line {}: unknown weight {}", line_idx + 1, name)),
+            };
+            *weight = value;
+        }
+        return Ok(heuristic);
+    }
+
+    pub fn load(path: &str) -> Result<Heuristic, HeuristicError> {
+        let mut text = String::new();
+        match File::open(path).and_then(|mut file| file.read_to_string(&mut text)) {
+            Ok(_) => (),
+            Err(e) => return Err(format!("{}: {}", path, e)),
+        }
+        return Heuristic::parse(&text);
+    }
+
+    // Score the position for the player. Higher is better.
+    pub fn evaluate(&self, game: &Backgammon, color: Color) -> f64 {
+        let opponent = color.opposite();
+        let stats = PositionStats::new(game, color);
+        let opponent_stats = PositionStats::new(game, opponent);
+        let (direct_shots, indirect_shots) = count_shots(game, color);
+        let mut score = self.pip_count *
+            (opponent_stats.pip_count as f64 - stats.pip_count as f64);
+        score += self.off * (stats.off as f64 - opponent_stats.off as f64);
+        score += self.opponent_bar * opponent_stats.bar as f64;
+        score -= self.direct_shots * direct_shots as f64;
+        score -= self.indirect_shots * indirect_shots as f64;
+        score += self.made_points * stats.made_points as f64;
+        score += self.prime_length * stats.prime_length as f64;
+        score += self.anchors * count_anchors(game, color) as f64;
+        score += self.home_board_points * stats.home_board_points as f64;
+        return score;
+    }
+}
+
+impl HeuristicPlayer {
+    pub fn new(color: Color) -> HeuristicPlayer {
+        return HeuristicPlayer {
+            color: color,
+            heuristic: Default::default(),
+            bearoff: Default::default(),
+        };
+    }
+
+    // Load the weights from the config file.
+    pub fn from_config(color: Color, path: &str) -> Result<HeuristicPlayer, HeuristicError> {
+        let heuristic = Heuristic::load(path)?;
+        return Ok(HeuristicPlayer { heuristic: heuristic, ..HeuristicPlayer::new(color) });
+    }
+}
+
+impl Player for HeuristicPlayer {
+    fn get_color(&self) -> Color {
+        return self.color;
+    }
+
+    fn make_move(&self, game: &Backgammon, roll: DiceRoll) -> Move {
+        let results: Vec<(Move, Backgammon)> = game.list_moves(self.color, roll).into_iter()
+            .map(|mov| {
+                let mut next = *game;
+                next.do_move(self.color, &mov);
+                (mov, next)
+            }).collect();
+        // Score by the bearoff equity if every move stays in the databases, since the
+        // heuristic is on a different scale.
+        let equities: Option<Vec<f64>> = results.iter()
+            .map(|(_, next)| self.bearoff.probabilities(next, self.color)
+                 .map(|probabilities| probabilities.equity()))
+            .collect();
+        let mut best_move = Move { submoves: Vec::new() };
+        let mut best_score = f64::NEG_INFINITY;
+        for (move_idx, (mov, next)) in results.into_iter().enumerate() {
+            let score = match equities {
+                Some(ref equities) => equities[move_idx],
+                None => self.heuristic.evaluate(&next, self.color),
+            };
+            if best_score < score {
+                best_score = score;
+                best_move = mov;
+            }
+        }
+        return best_move;
+    }
+}
@@ -1,7 +1,34 @@
 #[macro_use]
 extern crate log;
 extern crate rand;
+extern crate termion;
 
 pub mod rustgammon;
 pub mod commandlineplayer;
 pub mod randomplayer;
+pub mod gamerecord;
+pub mod sha256;
+pub mod fairdice;
+pub mod dicestats;
+pub mod positionstats;
+pub mod packed;
+pub mod perft;
+pub mod heuristicplayer;
+pub mod evaluator;
+pub mod searchplayer;
+pub mod neuralnet;
+pub mod training;
+pub mod notation;
+pub mod rollout;
+pub mod mctsplayer;
+pub mod bearoff;
+pub mod race;
+pub mod met;
+pub mod cube;
+pub mod analysis;
+pub mod luck;
+pub mod report;
+pub mod svg;
+pub mod textboard;
+pub mod tui;
+pub mod web;
@@ -0,0 +1,179 @@
+/*
+ * Rustgammon
+ *
+ * Luck analysis
+ * The luck of a roll is the equity of the best move with the roll less the average equity
+ * of the best moves over all 36 rolls, both for the player on roll and scaled by the
+ * value of the cube. A roll that wins the game is worth the points won.
+ * Summing the luck of each player and subtracting the difference from the result of the
+ * game gives the luck-adjusted result, which is what the players earned by their play.
+ *
+ * @author ryutaroikeda94@gmail.com
+ *
+ */
+
+use std::collections::HashSet;
+use std::fmt;
+
+use evaluator::Evaluator;
+use gamerecord::GameRecord;
+use packed::PackedPosition;
+use perft::distinct_rolls;
+use rustgammon::Backgammon;
+use rustgammon::Color;
+use rustgammon::DiceRoll;
+
+// The luck in points per game from which a roll is lucky and very lucky.
+pub const LUCKY_THRESHOLD: f64 = 0.3;
+pub const VERY_LUCKY_THRESHOLD: f64 = 0.6;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RollLuck {
+    pub turn: usize,
+    pub color: Color,
+    pub roll: DiceRoll,
+    pub cube_value: u32,
+    // The cubeless equity of the best move with the roll and the average over all rolls.
+    pub equity: f64,
+    pub average: f64,
+    // The difference in points, scaled by the cube.
+    pub luck: f64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LuckAnalysis {
+    pub rolls: Vec<RollLuck>,
+    pub red: f64,
+    pub white: f64,
+    // The points won by Red, negative if White won, or None if the game did not finish.
+    pub result: Option<f64>,
+}
+
+// The equity of the position for the player who has just moved, or the points won if the
+// player has won.
+fn settled_equity<E: Evaluator>(game: &Backgammon, color: Color, evaluator: &E) -> f64 {
+    if game.winner() == Some(color) {
+        return game.points_won(color) as f64;
+    }
+    return evaluator.evaluate(game, color);
+}
+
+// The equity of the best move with the roll for the player on roll.
+pub fn roll_equity<E: Evaluator>(game: &Backgammon, color: Color, roll: DiceRoll,
+                                 evaluator: &E) -> f64 {
+    let mut seen = HashSet::new();
+    let mut best = f64::NEG_INFINITY;
+    for mov in game.list_moves(color, roll) {
+        let mut next = *game;
+        next.do_move(color, &mov);
+        if !seen.insert(PackedPosition::new(&next)) {
+            continue;
+        }
+        best = best.max(settled_equity(&next, color, evaluator));
+    }
+    if seen.is_empty() {
+        // A player who cannot move leaves the position as it is.
+        return settled_equity(game, color, evaluator);
+    }
+    return best;
+}
+
+// The equity of the player on roll before rolling, as the average over all 36 rolls.
+pub fn average_equity<E: Evaluator>(game: &Backgammon, color: Color, evaluator: &E) -> f64 {
+    let mut total = 0.0;
+    for (roll, ways) in distinct_rolls() {
+        total += ways as f64 * roll_equity(game, color, roll, evaluator);
+    }
+    return total / 36.0;
+}
+
+// The luck of every roll in the record.
+pub fn analyze_luck<E: Evaluator>(record: &GameRecord, evaluator: &E) -> LuckAnalysis {
+    let mut analysis: LuckAnalysis = Default::default();
+    let mut game = record.start;
+    let mut cube_value = 1;
+    for (turn_idx, turn) in record.turns.iter().enumerate() {
+        if game.winner().is_some() {
+            break;
+        }
+        let taken = record.doubles.iter().filter(|double| double.turn == turn_idx && double.taken);
+        cube_value <<= taken.count();
+        let equity = roll_equity(&game, turn.color, turn.roll, evaluator);
+        let average = average_equity(&game, turn.color, evaluator);
+        let roll_luck = RollLuck {
+            turn: turn_idx,
+            color: turn.color,
+            roll: turn.roll,
+            cube_value: cube_value,
+            equity: equity,
+            average: average,
+            luck: cube_value as f64 * (equity - average),
+        };
+        match turn.color {
+            Color::Red => analysis.red += roll_luck.luck,
+            Color::White => analysis.white += roll_luck.luck,
+        }
+        analysis.rolls.push(roll_luck);
+        game.do_move(turn.color, &turn.mov);
+    }
+    analysis.result = record.winner.map(|winner| {
+        // A game ended by a pass is worth the cube, and a game played out its result.
+        let points = if game.winner() == Some(winner) {
+            cube_value * game.points_won(winner)
+        } else {
+            cube_value
+        };
+        if winner == Color::Red { points as f64 } else { -(points as f64) }
+    });
+    return analysis;
+}
+
+impl RollLuck {
+    pub fn description(&self) -> Option<&'static str> {
+        return if VERY_LUCKY_THRESHOLD <= self.luck {
+            Some("very lucky")
+        } else if LUCKY_THRESHOLD <= self.luck {
+            Some("lucky")
+        } else if self.luck <= -VERY_LUCKY_THRESHOLD {
+            Some("very unlucky")
+        } else if self.luck <= -LUCKY_THRESHOLD {
+            Some("unlucky")
+        } else {
+            None
+        };
+    }
+}
+
+impl LuckAnalysis {
+    pub fn luck(&self, color: Color) -> f64 {
+        return match color {
+            Color::Red => self.red,
+            Color::White => self.white,
+        };
+    }
+
+    // The luck of the player less the luck of the opponent.
+    pub fn net_luck(&self, color: Color) -> f64 {
+        return self.luck(color) - self.luck(color.opposite());
+    }
+
+    // The points won by the player, or None if the game did not finish.
+    pub fn result(&self, color: Color) -> Option<f64> {
+        return self.result.map(|points| if color == Color::Red { points } else { -points });
+    }
+
+    pub fn luck_adjusted_result(&self, color: Color) -> Option<f64> {
+        return self.result(color).map(|points| points - self.net_luck(color));
+    }
+}
+
+impl fmt::Display for RollLuck {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "turn {} {} {}-{}: luck {:+.3}", self.turn + 1, self.color, self.roll.0,
+               self.roll.1, self.luck)?;
+        if let Some(description) = self.description() {
+            write!(f, " ({})", description)?;
+        }
+        return Ok(());
+    }
+}
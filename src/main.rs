@@ -4,21 +4,676 @@ extern crate log4rs;
 
 extern crate rustgammon;
 
+use std::env;
+use std::fs;
+use std::fs::File;
+use std::path::Path;
+use std::time::Duration;
+use std::time::Instant;
+use std::io;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Read;
+use std::net::TcpListener;
+
+use rustgammon::analysis::Analyzer;
+use rustgammon::bearoff;
+use rustgammon::bearoff::BearoffDatabases;
+use rustgammon::bearoff::BearoffEvaluator;
+use rustgammon::bearoff::OneSidedBearoff;
+use rustgammon::bearoff::TwoSidedBearoff;
+use rustgammon::cube::Cube;
+use rustgammon::dicestats;
+use rustgammon::dicestats::DiceStats;
+use rustgammon::dicestats::JokerCounts;
+use rustgammon::fairdice;
+use rustgammon::fairdice::FairDice;
+use rustgammon::evaluator::ProbabilityEvaluator;
+use rustgammon::gamerecord::GameRecord;
+use rustgammon::heuristicplayer;
+use rustgammon::heuristicplayer::Heuristic;
+use rustgammon::heuristicplayer::HeuristicPlayer;
+use rustgammon::luck;
+use rustgammon::mctsplayer::Budget;
+use rustgammon::mctsplayer::MctsPlayer;
+use rustgammon::neuralnet;
+use rustgammon::neuralnet::NeuralNet;
+use rustgammon::gamerecord;
+use rustgammon::notation;
+use rustgammon::packed::MoveBuffer;
+use rustgammon::packed::PackedPosition;
+use rustgammon::report;
+use rustgammon::rollout;
+use rustgammon::rollout::RolloutConfig;
+use rustgammon::sha256;
+use rustgammon::notation::Orientation;
+use rustgammon::perft;
+use rustgammon::race::RaceEvaluator;
 use rustgammon::rustgammon::Backgammon;
 use rustgammon::rustgammon::Color;
+use rustgammon::rustgammon::Dice;
+use rustgammon::rustgammon::Player;
+use rustgammon::rustgammon::RandomDice;
+use rustgammon::searchplayer::SearchPlayer;
+use rustgammon::svg::Diagram;
+use rustgammon::svg::SvgRenderer;
+use rustgammon::svg::Theme;
+use rustgammon::tui;
+use rustgammon::web;
+use rustgammon::training;
+use rustgammon::training::TdTrainer;
+use rustgammon::training::TrainingConfig;
 use rustgammon::commandlineplayer::CommandLinePlayer;
 use rustgammon::randomplayer::RandomPlayer;
 
-fn main() {
-    log4rs::init_file("config/log4rs.yaml", Default::default()).unwrap();
-    info!("rustgammon - Backgammon implementation in Rust");
+fn usage() {
+    println!("usage: rustgammon [play [--opponent random|heuristic|search|neural|mcts|human]");
+    println!("                        [--home-left] [--tui] [--fair-dice] [--record FILE]]");
+    println!("       rustgammon verify RECORD COMMITMENT SECRET COMMITMENT SECRET");
+    println!("       rustgammon serve [--port PORT] [--opponent random|heuristic|search|neural|mcts]");
+    println!("       rustgammon dicestats [--sample ROLLS] [FILE...]");
+    println!("       rustgammon perft [DEPTH]");
+    println!("       rustgammon bench [ITERATIONS]");
+    println!("       rustgammon rollout ROLL [--games N] [--truncate PLIES] [--threads N] [--seed HEX]");
+    println!("       rustgammon bearoff [--two-sided] [--checkers N] [FILE]");
+    println!("       rustgammon analyze [--cube] [--evaluator neural|race] [--perspective red|white]");
+    println!("                          [--html FILE] RECORD");
+    println!("       rustgammon svg [--turn N] [--theme classic|felt|print] [--perspective red|white]");
+    println!("                      [--home-left] RECORD [FILE]");
+    println!("       rustgammon train [--games N] [--hidden UNITS] [--alpha RATE] [--lambda DECAY]");
+    println!("                        [--weights FILE] [--checkpoint-every N]");
+    println!("                        [--benchmark-every N] [--benchmark-games N]");
+}
+
+fn play(args: &[String]) {
+    let mut record_path = None;
+    let mut opponent = "random";
+    let mut orientation = Orientation::HomeRight;
+    let mut full_screen = false;
+    let mut fair_dice = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--record" => record_path = args.next(),
+            "--home-left" => orientation = Orientation::HomeLeft,
+            "--tui" => full_screen = true,
+            "--fair-dice" => fair_dice = true,
+            "--opponent" => opponent = match args.next() {
+                Some(v) => v,
+                None => return usage(),
+            },
+            _ => return usage(),
+        }
+    }
+
+    let cmd_player = CommandLinePlayer { orientation: orientation,
+                                         ..CommandLinePlayer::new(Color::Red) };
+    // Two humans sharing the terminal, each seeing the board from its own side. The full
+    // screen board is drawn for one human against the computer.
+    if opponent == "human" {
+        if full_screen {
+            return println!("error: --tui plays against the computer, so --opponent human is not supported");
+        }
+        let second_player = CommandLinePlayer { orientation: orientation,
+                                                ..CommandLinePlayer::new(Color::White) };
+        return play_against(&cmd_player, &second_player, full_screen, fair_dice, record_path);
+    }
+    match computer_player(opponent, Color::White) {
+        Ok(Some(player)) => play_against(&cmd_player, &player, full_screen, fair_dice, record_path),
+        Ok(None) => usage(),
+        Err(e) => println!("error: {}", e),
+    }
+}
+
+// The computer player of a name, or none if there is no such player.
+fn computer_player(name: &str, color: Color) -> Result<Option<Box<dyn Player>>, String> {
+    let player: Box<dyn Player> = match name {
+        "random" => Box::new(RandomPlayer { color: color }),
+        "heuristic" => {
+            let player = HeuristicPlayer::from_config(color, heuristicplayer::DEFAULT_CONFIG_PATH)?;
+            Box::new(HeuristicPlayer { bearoff: BearoffDatabases::shared(), ..player })
+        },
+        "search" => {
+            let heuristic = Heuristic::load(heuristicplayer::DEFAULT_CONFIG_PATH)?;
+            Box::new(SearchPlayer::new(color, BearoffEvaluator::new(heuristic), 2))
+        },
+        "neural" => {
+            let net = NeuralNet::load(neuralnet::DEFAULT_WEIGHTS_PATH)?;
+            Box::new(SearchPlayer::new(color, BearoffEvaluator::new(net), 0))
+        },
+        "mcts" => Box::new(MctsPlayer { bearoff: BearoffDatabases::shared(),
+                                        ..MctsPlayer::new(color, Budget::Time(Duration::from_secs(1))) }),
+        _ => return Ok(None),
+    };
+    return Ok(Some(player));
+}
+
+// Play with the first player as the human, at the command line or on the full screen. Fair
+// dice come from a secret for each player, committed to before the game and revealed after.
+fn play_against<S: Player, T: Player>(first: &S, second: &T, full_screen: bool, fair_dice: bool,
+                                      record_path: Option<&String>) {
+    if !fair_dice {
+        return play_with_dice(first, second, full_screen, &mut RandomDice, record_path);
+    }
+    let first_secret = fairdice::generate_secret();
+    let second_secret = fairdice::generate_secret();
+    println!("dice commitments: {} {}", sha256::to_hex(&fairdice::commit(&first_secret)),
+             sha256::to_hex(&fairdice::commit(&second_secret)));
+    let mut dice = FairDice::from_secrets(&first_secret, &second_secret);
+    play_with_dice(first, second, full_screen, &mut dice, record_path);
+    println!("dice secrets: {} {}", sha256::to_hex(&first_secret), sha256::to_hex(&second_secret));
+}
+
+fn play_with_dice<S: Player, T: Player, D: Dice>(first: &S, second: &T, full_screen: bool,
+                                                 dice: &mut D, record_path: Option<&String>) {
+    let record = if full_screen {
+        match tui::play(first.get_color(), second, first.view().1, dice) {
+            Ok(record) => record,
+            Err(e) => return println!("error: {}", e),
+        }
+    } else {
+        let mut game: Backgammon = Default::default();
+        game.init();
+        game.run_with_dice(first, second, dice)
+    };
+    if let Some(path) = record_path {
+        let result = File::create(path).and_then(|mut file| record.write(&mut file));
+        match result {
+            Ok(_) => info!("saved game record to {}", path),
+            Err(e) => println!("error: {}", e),
+        }
+    }
+}
+
+// Check a game played with fair dice against the commitments and secrets printed for it.
+fn run_verify(args: &[String]) {
+    if args.len() != 5 {
+        return usage();
+    }
+    let mut hex = Vec::new();
+    for arg in &args[1..] {
+        match sha256::from_hex(arg) {
+            Some(bytes) => hex.push(bytes),
+            None => return println!("error: {} is not hexadecimal", arg),
+        }
+    }
+    let mut commitments = [[0u8; 32]; 2];
+    for (commitment, bytes) in commitments.iter_mut().zip(hex.iter().step_by(2)) {
+        if bytes.len() != commitment.len() {
+            return println!("error: a commitment has {} bytes, not {}", bytes.len(), commitment.len());
+        }
+        commitment.copy_from_slice(bytes);
+    }
+    let record = match File::open(&args[0]) {
+        Ok(file) => match GameRecord::read(BufReader::new(file)) {
+            Ok(record) => record,
+            Err(e) => return println!("error: {}", e),
+        },
+        Err(e) => return println!("error: {}", e),
+    };
+    match fairdice::verify(&commitments[0], &hex[1], &commitments[1], &hex[3], &record) {
+        Ok(()) => println!("the dice and moves are fair"),
+        Err(e) => println!("error: {}", e),
+    }
+}
+
+// Audit the rolls in roll lists or game records, or a sample from the random dice.
+fn dice_stats(args: &[String]) {
+    let mut rolls = Vec::new();
+    let mut jokers: JokerCounts = Default::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--sample" {
+            let count = match args.next().map(|v| v.parse::<usize>()) {
+                Some(Ok(v)) => v,
+                _ => return usage(),
+            };
+            let mut dice = RandomDice;
+            for _ in 0..count {
+                rolls.push(dice.roll());
+            }
+            continue;
+        }
+        let mut text = String::new();
+        match File::open(arg).and_then(|mut file| file.read_to_string(&mut text)) {
+            Ok(_) => (),
+            Err(e) => return println!("error: {}: {}", arg, e),
+        }
+        let is_record = text.lines().any(|line| line.trim_start().starts_with("turn "));
+        if is_record {
+            match GameRecord::read(BufReader::new(text.as_bytes())) {
+                Ok(record) => {
+                    rolls.extend(record.rolls());
+                    jokers.add(&dicestats::count_jokers(&record));
+                },
+                Err(e) => return println!("error: {}: {}", arg, e),
+            }
+        } else {
+            match dicestats::parse_rolls(&text) {
+                Ok(v) => rolls.extend(v),
+                Err(e) => return println!("error: {}: {}", arg, e),
+            }
+        }
+    }
+    if rolls.is_empty() {
+        return usage();
+    }
+    DiceStats::new(&rolls).print();
+    if 0 < jokers.red_turns + jokers.white_turns {
+        jokers.print();
+    }
+}
+
+fn parse_count_arg(args: &[String], default: u64) -> Option<u64> {
+    return match args.first() {
+        Some(arg) => arg.parse::<u64>().ok(),
+        None => Some(default),
+    }
+}
+
+fn run_perft(args: &[String]) {
+    let depth = match parse_count_arg(args, 2) {
+        Some(v) => v as usize,
+        None => return usage(),
+    };
+    for reference in &perft::reference_positions() {
+        print!("{}:", reference.name);
+        for (roll, _) in perft::distinct_rolls() {
+            print!(" {}-{}:{}", roll.0, roll.1,
+                   perft::count_positions(&reference.game, reference.color, roll));
+        }
+        println!();
+        let position = PackedPosition::new(&reference.game);
+        for d in 1..(depth + 1) {
+            println!("  depth {}: {}", d, perft::perft(&position, reference.color, d));
+        }
+    }
+}
+
+fn run_bench(args: &[String]) {
+    let iterations = match parse_count_arg(args, 100) {
+        Some(v) => v,
+        None => return usage(),
+    };
+    let positions = perft::reference_positions();
+    perft::bench_list_moves(&positions, iterations).print("list_moves");
+    perft::bench_generate_moves(&positions, iterations).print("generate_moves");
+    // A 2-ply decision with the default settings for each opening roll.
+    let search = SearchPlayer::new(Color::Red, Heuristic::default(), 2);
+    let start = Instant::now();
+    let mut result = perft::BenchResult { calls: 0, moves: 0, elapsed: Duration::new(0, 0) };
+    for (roll, _) in perft::distinct_rolls() {
+        result.moves += search.make_move(&positions[0].game, roll).submoves.len() as u64;
+        result.calls += 1;
+    }
+    result.elapsed = start.elapsed();
+    result.print("2-ply opening search");
+}
+
+// Train the network by self-play, continuing from the weights file if it exists.
+fn run_training(args: &[String]) {
+    let mut config = TrainingConfig {
+        games: 100000,
+        checkpoint_interval: 1000,
+        checkpoint_path: training::DEFAULT_CHECKPOINT_PATH.to_string(),
+        benchmark_interval: 5000,
+        benchmark_games: 500,
+    };
+    let mut hidden = None;
+    let mut alpha = 0.1;
+    let mut lambda = 0.7;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = match args.next() {
+            Some(v) => v,
+            None => return usage(),
+        };
+        let ok = match arg.as_str() {
+            "--games" => value.parse().map(|v| config.games = v).is_ok(),
+            "--hidden" => value.parse().map(|v| hidden = Some(v)).is_ok(),
+            "--alpha" => value.parse().map(|v| alpha = v).is_ok(),
+            "--lambda" => value.parse().map(|v| lambda = v).is_ok(),
+            "--weights" => {
+                config.checkpoint_path = value.clone();
+                true
+            },
+            "--checkpoint-every" => value.parse().map(|v| config.checkpoint_interval = v).is_ok(),
+            "--benchmark-every" => value.parse().map(|v| config.benchmark_interval = v).is_ok(),
+            "--benchmark-games" => value.parse().map(|v| config.benchmark_games = v).is_ok(),
+            _ => false,
+        };
+        if !ok {
+            return usage();
+        }
+    }
+
+    let net = if Path::new(&config.checkpoint_path).exists() {
+        match NeuralNet::load(&config.checkpoint_path) {
+            Ok(ref net) if hidden.is_some_and(|hidden| hidden != net.hidden) => {
+                return println!("error: {} has {} hidden units, so --hidden must match or use \
+                                 another --weights file", config.checkpoint_path, net.hidden);
+            },
+            Ok(net) => {
+                info!("resuming from {}", config.checkpoint_path);
+                net
+            },
+            Err(e) => return println!("error: {}", e),
+        }
+    } else {
+        if let Some(dir) = Path::new(&config.checkpoint_path).parent() {
+            if let Err(e) = fs::create_dir_all(dir) {
+                return println!("error: {}", e);
+            }
+        }
+        NeuralNet::new(hidden.unwrap_or(neuralnet::DEFAULT_HIDDEN))
+    };
+    let mut trainer = TdTrainer::new(net, alpha, lambda);
+    if let Err(e) = training::train(&mut trainer, &config, &mut RandomDice) {
+        println!("error: {}", e);
+    }
+}
+
+// Roll out every opening move for the roll with the network.
+fn run_rollout(args: &[String]) {
+    let roll = match args.first().map(|v| gamerecord::parse_roll(v)) {
+        Some(Ok(v)) => v,
+        _ => return usage(),
+    };
+    let mut config: RolloutConfig = Default::default();
+    let mut args = args[1..].iter();
+    while let Some(arg) = args.next() {
+        let value = match args.next() {
+            Some(v) => v,
+            None => return usage(),
+        };
+        let ok = match arg.as_str() {
+            "--games" => value.parse().map(|v| config.games = v).is_ok(),
+            "--truncate" => value.parse().map(|v| config.truncation = Some(v)).is_ok(),
+            "--threads" => value.parse().map(|v| config.threads = v).is_ok(),
+            "--seed" => match sha256::from_hex(value) {
+                Some(ref bytes) if bytes.len() == config.seed.len() => {
+                    config.seed.copy_from_slice(bytes);
+                    true
+                },
+                _ => false,
+            },
+            _ => false,
+        };
+        if !ok {
+            return usage();
+        }
+    }
+    let net = match NeuralNet::load(neuralnet::DEFAULT_WEIGHTS_PATH) {
+        Ok(v) => v,
+        Err(e) => return println!("error: {}", e),
+    };
 
     let mut game: Backgammon = Default::default();
     game.init();
+    let mut buffer = MoveBuffer::new();
+    PackedPosition::new(&game).generate_moves(Color::Red, roll, &mut buffer);
+    let moves: Vec<_> = buffer.moves().iter().map(|mov| mov.to_move()).collect();
+    let evaluator = BearoffEvaluator::new(net);
+    let results = rollout::rollout_moves(&game, Color::Red, &moves, &evaluator, &config);
+    let mut ranked: Vec<_> = moves.iter().zip(results.iter()).collect();
+    ranked.sort_by(|a, b| b.1.equity.partial_cmp(&a.1.equity).unwrap_or(::std::cmp::Ordering::Equal));
+    for (mov, result) in ranked {
+        let p = &result.probabilities;
+        println!("{:<20} {:+.3} +/- {:.3}  win {:.3} gammon {:.3} bg {:.3} lose gammon {:.3} bg {:.3}",
+                 notation::format_move(&game, Color::Red, mov), result.equity,
+                 result.standard_error, p.win, p.win_gammon, p.win_backgammon,
+                 p.lose_gammon, p.lose_backgammon);
+    }
+}
 
-    let cmd_player = CommandLinePlayer { color: Color::Red };
-    //let second_player = CommandLinePlayer { color: Color::White };
-    let second_player = RandomPlayer { color: Color::White};
+// Generate the one-sided or the two-sided bearoff database.
+fn run_bearoff(args: &[String]) {
+    let mut two_sided = false;
+    let mut checkers = None;
+    let mut path = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--two-sided" => two_sided = true,
+            "--checkers" => checkers = match args.next().map(|v| v.parse::<usize>()) {
+                Some(Ok(v)) if v <= bearoff::MAX_CHECKERS => Some(v),
+                _ => return usage(),
+            },
+            _ => path = Some(arg.as_str()),
+        }
+    }
+    let path = match (path, two_sided) {
+        (Some(v), _) => v,
+        (None, false) => bearoff::DEFAULT_PATH,
+        (None, true) => bearoff::TWO_SIDED_DEFAULT_PATH,
+    };
+    if let Some(dir) = Path::new(path).parent() {
+        if let Err(e) = fs::create_dir_all(dir) {
+            return println!("error: {}", e);
+        }
+    }
+    let result = if two_sided {
+        let database = TwoSidedBearoff::generate(checkers.unwrap_or(bearoff::TWO_SIDED_MAX_CHECKERS));
+        database.save(path).map(|_| database.len())
+    } else {
+        let database = OneSidedBearoff::generate(checkers.unwrap_or(bearoff::MAX_CHECKERS));
+        database.save(path).map(|_| database.len())
+    };
+    match result {
+        Ok(positions) => info!("saved {} positions to {}", positions, path),
+        Err(e) => println!("error: {}", e),
+    }
+}
+
+// Annotate the checker plays and cube decisions of a game record.
+fn run_analysis(args: &[String]) {
+    let mut analyze_cube = false;
+    let mut evaluator = None;
+    let mut perspective = None;
+    let mut html_path = None;
+    let mut path = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--cube" => analyze_cube = true,
+            "--evaluator" => evaluator = match args.next().map(|v| v.as_str()) {
+                Some(v) if v == "neural" || v == "race" => Some(v),
+                _ => return usage(),
+            },
+            "--perspective" => perspective = match args.next().map(|v| v.parse::<Color>()) {
+                Some(Ok(v)) => Some(v),
+                _ => return usage(),
+            },
+            "--html" => html_path = match args.next() {
+                Some(v) => Some(v),
+                None => return usage(),
+            },
+            _ => path = Some(arg.as_str()),
+        }
+    }
+    let path = match path {
+        Some(v) => v,
+        None => return usage(),
+    };
+    let record = match File::open(path).map_err(|e| e.to_string())
+        .and_then(|file| GameRecord::read(BufReader::new(file))) {
+        Ok(v) => v,
+        Err(e) => return println!("error: {}: {}", path, e),
+    };
+    // Without the network's weights, fall back to the race formulas unless asked for the
+    // network.
+    if evaluator != Some("race") {
+        match NeuralNet::load(neuralnet::DEFAULT_WEIGHTS_PATH) {
+            Ok(net) => {
+                let analyzer = Analyzer { analyze_cube: analyze_cube,
+                                          ..Analyzer::new(BearoffEvaluator::new(net)) };
+                return print_analysis(&analyzer, &record, path, perspective, html_path);
+            },
+            Err(e) if evaluator.is_some() => return println!("error: {}", e),
+            Err(e) => println!("{}: analysing with the race formulas instead", e),
+        }
+    }
+    let analyzer = Analyzer { analyze_cube: analyze_cube,
+                              ..Analyzer::new(BearoffEvaluator::new(RaceEvaluator)) };
+    print_analysis(&analyzer, &record, path, perspective, html_path);
+}
+
+// Print the analysis with each move from the perspective, or else from the side of the
+// player on roll.
+fn print_analysis<E: ProbabilityEvaluator>(analyzer: &Analyzer<E>, record: &GameRecord, path: &str,
+                                           perspective: Option<Color>, html_path: Option<&String>) {
+    let analysis = analyzer.analyze(record);
+    let luck_analysis = luck::analyze_luck(record, &analyzer.evaluator);
+    // Each cube decision is made before the turn with its index.
+    let mut cube_decisions = analysis.cube_decisions.iter().peekable();
+    for move_analysis in &analysis.moves {
+        while let Some(cube_analysis) = cube_decisions.next_if(|cube| cube.turn <= move_analysis.turn) {
+            println!("{}", cube_analysis);
+        }
+        println!("{}", move_analysis.describe(perspective.unwrap_or(move_analysis.color)));
+        let roll_luck = luck_analysis.rolls.iter().find(|roll| roll.turn == move_analysis.turn);
+        if let Some(roll_luck) = roll_luck.filter(|roll| roll.description().is_some()) {
+            println!("{}", roll_luck);
+        }
+    }
+    for cube_analysis in cube_decisions {
+        println!("{}", cube_analysis);
+    }
+    for &color in [Color::Red, Color::White].iter() {
+        println!("{}: {}", color, analysis.summary(color));
+        print!("{}: luck {:+.3}", color, luck_analysis.luck(color));
+        match (luck_analysis.result(color), luck_analysis.luck_adjusted_result(color)) {
+            (Some(result), Some(adjusted)) => println!(", result {:+}, luck-adjusted {:+.3}", result,
+                                                       adjusted),
+            _ => println!(),
+        }
+    }
+    if let Some(html_path) = html_path {
+        let title = format!("Analysis of {}", path);
+        let result = File::create(html_path).and_then(|file| {
+            let mut out = BufWriter::new(file);
+            report::write_report(&mut out, &title, &analysis, &luck_analysis, perspective)
+        });
+        match result {
+            Ok(_) => info!("saved the report to {}", html_path),
+            Err(e) => println!("error: {}", e),
+        }
+    }
+}
+
+// Draw the position of a game record before a turn, or at the end, as an SVG image.
+fn run_svg(args: &[String]) {
+    let mut renderer: SvgRenderer = Default::default();
+    let mut turn = None;
+    let mut paths = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--home-left" => renderer.orientation = Orientation::HomeLeft,
+            "--perspective" => renderer.perspective = match args.next().map(|v| v.parse::<Color>()) {
+                Some(Ok(v)) => v,
+                _ => return usage(),
+            },
+            "--theme" => renderer.theme = match args.next().and_then(|v| Theme::by_name(v)) {
+                Some(v) => v,
+                None => return usage(),
+            },
+            "--turn" => turn = match args.next().map(|v| v.parse::<usize>()) {
+                Some(Ok(v)) if 0 < v => Some(v - 1),
+                _ => return usage(),
+            },
+            _ => paths.push(arg.as_str()),
+        }
+    }
+    let (path, output) = match paths.as_slice() {
+        [path] => (*path, None),
+        [path, output] => (*path, Some(*output)),
+        _ => return usage(),
+    };
+    let record = match File::open(path).map_err(|e| e.to_string())
+        .and_then(|file| GameRecord::read(BufReader::new(file))) {
+        Ok(v) => v,
+        Err(e) => return println!("error: {}: {}", path, e),
+    };
+    let turn = turn.unwrap_or(record.turns.len()).min(record.turns.len());
+    let mut diagram = Diagram::new(&record.start);
+    let mut cube: Cube = Default::default();
+    for (turn_idx, played) in record.turns.iter().enumerate().take(turn) {
+        for double in record.doubles.iter().filter(|double| double.turn == turn_idx && double.taken) {
+            cube = Cube { value: 2 * cube.value, owner: Some(double.color.opposite()) };
+        }
+        diagram.position.do_move(played.color, &played.mov);
+    }
+    diagram.dice = record.turns.get(turn).map(|played| (played.color, played.roll));
+    diagram.cube = Some(cube);
+    let result = match output {
+        Some(output) => File::create(output).and_then(|file| renderer.write(&mut BufWriter::new(file), &diagram)),
+        None => renderer.write(&mut io::stdout(), &diagram),
+    };
+    if let Err(e) = result {
+        println!("error: {}", e);
+    }
+}
+
+// Serve the board to a browser on this machine for a human playing Red.
+fn run_server(args: &[String]) {
+    let mut port = web::DEFAULT_PORT;
+    let mut opponent = "random";
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--port" => port = match args.next().map(|v| v.parse::<u16>()) {
+                Some(Ok(v)) => v,
+                _ => return usage(),
+            },
+            "--opponent" => opponent = match args.next() {
+                Some(v) => v,
+                None => return usage(),
+            },
+            _ => return usage(),
+        }
+    }
+    let player = match computer_player(opponent, Color::White) {
+        Ok(Some(player)) => player,
+        Ok(None) => return usage(),
+        Err(e) => return println!("error: {}", e),
+    };
+    let mut listeners = match TcpListener::bind(("127.0.0.1", port)) {
+        Ok(listener) => vec!(listener),
+        Err(e) => return println!("error: port {}: {}", port, e),
+    };
+    // Browsers may try localhost over IPv6 first, which a machine need not have.
+    match TcpListener::bind(("::1", port)) {
+        Ok(listener) => listeners.push(listener),
+        Err(e) => debug!("not serving on IPv6: {}", e),
+    }
+    println!("serving the board at http://localhost:{}/", port);
+    if let Err(e) = web::serve(listeners, &player) {
+        println!("error: {}", e);
+    }
+}
+
+fn main() {
+    log4rs::init_file("config/log4rs.yaml", Default::default()).unwrap();
+    info!("rustgammon - Backgammon implementation in Rust");
 
-    game.run(&cmd_player, &second_player);
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (command, command_args) = match args.split_first() {
+        Some((head, tail)) => (head.as_str(), tail),
+        None => ("play", &args[..]),
+    };
+    match command {
+        "play" => play(command_args),
+        "dicestats" => dice_stats(command_args),
+        "perft" => run_perft(command_args),
+        "bench" => run_bench(command_args),
+        "rollout" => run_rollout(command_args),
+        "train" => run_training(command_args),
+        "bearoff" => run_bearoff(command_args),
+        "analyze" => run_analysis(command_args),
+        "svg" => run_svg(command_args),
+        "serve" => run_server(command_args),
+        "verify" => run_verify(command_args),
+        _ => usage(),
+    }
 }
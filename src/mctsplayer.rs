@@ -0,0 +1,342 @@
+/*
+ * Rustgammon
+ *
+ * Monte Carlo tree search player
+ * Grow a tree of decision nodes, where the player to move chooses a move for a roll,
+ * and chance nodes, where the player to move rolls the dice. Moves are chosen with UCT,
+ * rolls are sampled, and new nodes are scored by playing out the game with a light
+ * policy that favours safe and attacking moves.
+ * No weights are needed and the play improves with the budget.
+ *
+ * @author ryutaroikeda94@gmail.com
+ *
+ */
+
+extern crate rand;
+
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use rand::Rng;
+
+use bearoff::BearoffDatabases;
+use packed::MoveBuffer;
+use packed::PackedPosition;
+use perft::roll_index;
+use rustgammon::BAR_POS;
+use rustgammon::BEARING_OFF_POS;
+use rustgammon::Backgammon;
+use rustgammon::Color;
+use rustgammon::DiceRoll;
+use rustgammon::Move;
+use rustgammon::Player;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Budget {
+    Iterations(usize),
+    Time(Duration),
+}
+
+pub struct MctsPlayer {
+    pub color: Color,
+    pub budget: Budget,
+    // The weight of the exploration term of UCT.
+    pub exploration: f64,
+    // The bearoff databases, to score leaves instead of playing out. None are given by default.
+    pub bearoff: Arc<BearoffDatabases>,
+}
+
+enum NodeKind {
+    // The player to move has rolled and chooses a move.
+    Decision(DiceRoll),
+    // The player to move is about to roll.
+    Chance,
+}
+
+struct Node {
+    position: PackedPosition,
+    to_move: Color,
+    kind: NodeKind,
+    // A decision node has a child for each move. A chance node has a slot for each of
+    // the 21 distinct rolls, filled when the roll is first sampled.
+    children: Vec<Option<usize>>,
+    expanded: bool,
+    visits: u32,
+    // The sum of the points won by the searching player.
+    total: f64,
+}
+
+fn roll_dice<R: Rng>(rng: &mut R) -> DiceRoll {
+    return (rng.gen_range(1, 7), rng.gen_range(1, 7));
+}
+
+// The points won by the player if the game is over.
+fn result(position: &PackedPosition, color: Color) -> Option<f64> {
+    let game = position.to_backgammon();
+    return game.winner().map(|winner| {
+        let points = game.points_won(winner) as f64;
+        if winner == color { points } else { -points }
+    });
+}
+
+// A cheap score of the position for the player who moved: hit, make points and avoid
+// leaving blots.
+fn light_score(position: &PackedPosition, color: Color) -> f64 {
+    let mut score = 3.0 * position.get(color.opposite(), BAR_POS) as f64 +
+        0.5 * position.get(color, BEARING_OFF_POS) as f64;
+    for pos in (BAR_POS + 1)..BEARING_OFF_POS {
+        match position.get(color, pos) {
+            0 => (),
+            1 => score -= 1.0,
+            _ => score += 1.0,
+        }
+    }
+    return score;
+}
+
+impl Node {
+    fn new(position: PackedPosition, to_move: Color, kind: NodeKind) -> Node {
+        return Node {
+            position: position,
+            to_move: to_move,
+            kind: kind,
+            children: Vec::new(),
+            expanded: false,
+            visits: 0,
+            total: 0.0,
+        };
+    }
+}
+
+struct Search<'a, R: Rng + 'a> {
+    color: Color,
+    exploration: f64,
+    nodes: Vec<Node>,
+    // The moves of the root, in the order of its children.
+    root_moves: Vec<Move>,
+    buffer: MoveBuffer,
+    bearoff: &'a BearoffDatabases,
+    rng: &'a mut R,
+}
+
+impl<'a, R: Rng> Search<'a, R> {
+    // Pick the playout move for the roll, breaking ties at random.
+    fn playout_move(&mut self, position: &PackedPosition, to_move: Color,
+                    roll: DiceRoll) -> PackedPosition {
+        position.generate_moves(to_move, roll, &mut self.buffer);
+        let mut best = *position;
+        let mut best_score = f64::NEG_INFINITY;
+        for mov in self.buffer.moves() {
+            let score = light_score(&mov.position, to_move) + self.rng.gen_range(0.0, 0.5);
+            if best_score < score {
+                best_score = score;
+                best = mov.position;
+            }
+        }
+        return best;
+    }
+
+    // The equity of the node for the searching player from the bearoff databases, if both
+    // sides are in them. A player who has rolled plays the move with the best equity.
+    fn bearoff_equity(&mut self, node_idx: usize) -> Option<f64> {
+        let position = self.nodes[node_idx].position;
+        let to_move = self.nodes[node_idx].to_move;
+        let sign = if to_move == self.color { 1.0 } else { -1.0 };
+        match self.nodes[node_idx].kind {
+            NodeKind::Chance => {
+                let game = position.to_backgammon();
+                let probabilities = self.bearoff.probabilities(&game, to_move.opposite())?;
+                return Some(-sign * probabilities.equity());
+            },
+            NodeKind::Decision(roll) => {
+                position.generate_moves(to_move, roll, &mut self.buffer);
+                let mut best: Option<f64> = None;
+                for mov in self.buffer.moves() {
+                    let game = mov.position.to_backgammon();
+                    let equity = self.bearoff.probabilities(&game, to_move)?.equity();
+                    best = Some(best.map_or(equity, |best| best.max(equity)));
+                }
+                return best.map(|equity| sign * equity);
+            },
+        }
+    }
+
+    // Play to the end of the game from the node, or look it up in a pure bearoff.
+    fn playout(&mut self, node_idx: usize) -> f64 {
+        if let Some(points) = result(&self.nodes[node_idx].position, self.color) {
+            return points;
+        }
+        if let Some(equity) = self.bearoff_equity(node_idx) {
+            return equity;
+        }
+        let mut position = self.nodes[node_idx].position;
+        let mut to_move = self.nodes[node_idx].to_move;
+        let mut roll = match self.nodes[node_idx].kind {
+            NodeKind::Decision(roll) => Some(roll),
+            NodeKind::Chance => None,
+        };
+        loop {
+            if let Some(points) = result(&position, self.color) {
+                return points;
+            }
+            let dice = match roll.take() {
+                Some(v) => v,
+                None => roll_dice(self.rng),
+            };
+            position = self.playout_move(&position, to_move, dice);
+            to_move = to_move.opposite();
+        }
+    }
+
+    fn expand(&mut self, node_idx: usize) {
+        self.nodes[node_idx].expanded = true;
+        let position = self.nodes[node_idx].position;
+        let to_move = self.nodes[node_idx].to_move;
+        let roll = match self.nodes[node_idx].kind {
+            NodeKind::Decision(roll) => roll,
+            NodeKind::Chance => {
+                self.nodes[node_idx].children = vec![None; 21];
+                return;
+            },
+        };
+        position.generate_moves(to_move, roll, &mut self.buffer);
+        let mut children: Vec<PackedPosition> =
+            self.buffer.moves().iter().map(|mov| mov.position).collect();
+        if node_idx == 0 {
+            self.root_moves = self.buffer.moves().iter().map(|mov| mov.to_move()).collect();
+        }
+        if children.is_empty() {
+            // The player cannot move and passes the dice.
+            children.push(position);
+        }
+        for child in children {
+            let child_idx = self.nodes.len();
+            self.nodes.push(Node::new(child, to_move.opposite(), NodeKind::Chance));
+            self.nodes[node_idx].children.push(Some(child_idx));
+        }
+    }
+
+    // The child with the highest upper confidence bound for the player to move.
+    fn select(&self, node_idx: usize) -> usize {
+        let node = &self.nodes[node_idx];
+        let sign = if node.to_move == self.color { 1.0 } else { -1.0 };
+        let log_visits = (node.visits.max(1) as f64).ln();
+        let mut best = 0;
+        let mut best_bound = f64::NEG_INFINITY;
+        for child_idx in node.children.iter().filter_map(|child| *child) {
+            let child = &self.nodes[child_idx];
+            if child.visits == 0 {
+                return child_idx;
+            }
+            let mean = sign * child.total / child.visits as f64;
+            let bound = mean + self.exploration * (log_visits / child.visits as f64).sqrt();
+            if best_bound < bound {
+                best_bound = bound;
+                best = child_idx;
+            }
+        }
+        return best;
+    }
+
+    fn iterate(&mut self) {
+        let mut path = vec!(0);
+        let mut node_idx = 0;
+        let reward = loop {
+            if self.nodes[node_idx].visits == 0 && node_idx != 0 {
+                break self.playout(node_idx);
+            }
+            if let Some(points) = result(&self.nodes[node_idx].position, self.color) {
+                break points;
+            }
+            if !self.nodes[node_idx].expanded {
+                self.expand(node_idx);
+            }
+            node_idx = match self.nodes[node_idx].kind {
+                NodeKind::Decision(_) => self.select(node_idx),
+                NodeKind::Chance => {
+                    let roll = roll_dice(self.rng);
+                    let slot = roll_index(roll);
+                    match self.nodes[node_idx].children[slot] {
+                        Some(child_idx) => child_idx,
+                        None => {
+                            let child_idx = self.nodes.len();
+                            let node = &self.nodes[node_idx];
+                            let child = Node::new(node.position, node.to_move, NodeKind::Decision(roll));
+                            self.nodes.push(child);
+                            self.nodes[node_idx].children[slot] = Some(child_idx);
+                            child_idx
+                        },
+                    }
+                },
+            };
+            path.push(node_idx);
+        };
+        for idx in path {
+            self.nodes[idx].visits += 1;
+            self.nodes[idx].total += reward;
+        }
+    }
+}
+
+impl MctsPlayer {
+    pub fn new(color: Color, budget: Budget) -> MctsPlayer {
+        return MctsPlayer {
+            color: color,
+            budget: budget,
+            exploration: 1.5,
+            bearoff: Default::default(),
+        };
+    }
+
+    // Search with the random number generator and return the moves with their visits.
+    pub fn search<R: Rng>(&self, game: &Backgammon, roll: DiceRoll,
+                          rng: &mut R) -> Vec<(Move, u32)> {
+        let root = Node::new(PackedPosition::new(game), self.color, NodeKind::Decision(roll));
+        let mut search = Search {
+            color: self.color,
+            exploration: self.exploration,
+            nodes: vec!(root),
+            root_moves: Vec::new(),
+            buffer: MoveBuffer::new(),
+            bearoff: &self.bearoff,
+            rng: rng,
+        };
+        search.expand(0);
+        if search.root_moves.len() <= 1 {
+            return search.root_moves.into_iter().map(|mov| (mov, 0)).collect();
+        }
+        let start = Instant::now();
+        let mut iterations = 0;
+        loop {
+            let done = match self.budget {
+                Budget::Iterations(max) => max <= iterations,
+                Budget::Time(duration) => duration <= start.elapsed(),
+            };
+            if done {
+                break;
+            }
+            search.iterate();
+            iterations += 1;
+        }
+        debug!("mcts: {} iterations, {} nodes", iterations, search.nodes.len());
+        let visits: Vec<u32> = search.nodes[0].children.iter()
+            .map(|child| child.map_or(0, |idx| search.nodes[idx].visits)).collect();
+        return search.root_moves.into_iter().zip(visits).collect();
+    }
+}
+
+impl Player for MctsPlayer {
+    fn get_color(&self) -> Color {
+        return self.color;
+    }
+
+    // Play the most visited move.
+    fn make_move(&self, game: &Backgammon, roll: DiceRoll) -> Move {
+        let candidates = self.search(game, roll, &mut rand::thread_rng());
+        return match candidates.into_iter().max_by_key(|&(_, visits)| visits) {
+            Some((mov, _)) => mov,
+            None => Move { submoves: Vec::new() },
+        }
+    }
+}
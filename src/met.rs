@@ -0,0 +1,489 @@
+/*
+ * Rustgammon
+ *
+ * Match equity tables
+ * The probability of winning the match from each score, with the scores given as the
+ * points each player still needs.
+ * The built-in tables are generated from models where every game is won by either player
+ * with equal chances and a fixed fraction of the wins are gammons. After the Crawford
+ * game the trailer doubles at once and the leader passes when that is no worse. Before
+ * it, the cubeless table plays without the cube, and the live cube table treats the
+ * probability of winning as moving continuously through the game, so that a player
+ * doubles exactly at the opponent's take point.
+ * The published tables, Kazaross XG2 and Rockwell-Kazaross, are read from config/met in
+ * the GNUbg XML format, where the explicit pre-Crawford table has a row for each score of
+ * the player and the post-Crawford table has a row for the trailer. The files are not
+ * part of this repository; they come with GNU Backgammon. Kazaross XG2 is the default,
+ * with the live cube model standing in, under a warning, when its file is missing.
+ *
+ * @author ryutaroikeda94@gmail.com
+ *
+ */
+
+use std::fs::File;
+use std::io;
+use std::io::BufWriter;
+use std::io::Read;
+use std::io::Write;
+
+pub type MetError = String;
+
+// The fraction of wins that are gammons in the generated tables.
+pub const DEFAULT_GAMMON_RATE: f64 = 0.2;
+
+pub const DEFAULT_MAX_AWAY: usize = 25;
+
+pub const BUILTIN_TABLES: [&str; 2] = ["live-cube", "cubeless"];
+
+// The published tables with the paths of their GNUbg XML files.
+pub const PUBLISHED_TABLES: [(&str, &str); 2] = [
+    ("kazaross-xg2", "config/met/Kazaross-XG2.xml"),
+    ("rockwell-kazaross", "config/met/Rockwell-Kazaross.xml"),
+];
+
+pub const DEFAULT_TABLE: &str = "kazaross-xg2";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MatchEquityTable {
+    pub name: String,
+    // The equity of the player needing i + 1 points against j + 1 points. When one player
+    // needs 1 point, this is the equity at the start of the Crawford game.
+    pub pre_crawford: Vec<Vec<f64>>,
+    // The equity of the trailer needing i + 1 points after the Crawford game, against a
+    // leader needing 1 point.
+    pub post_crawford: Vec<f64>,
+}
+
+// The score of a game in a match for a player.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct MatchScore {
+    pub away: u32,
+    pub opponent_away: u32,
+    // Whether this is the Crawford game, in which the cube is not used.
+    pub crawford: bool,
+}
+
+// A piecewise linear function of the probability of winning the game, as its points from
+// 0 to 1.
+type Piecewise = Vec<(f64, f64)>;
+
+fn evaluate(function: &[(f64, f64)], p: f64) -> f64 {
+    for pair in function.windows(2) {
+        let (x0, y0) = pair[0];
+        let (x1, y1) = pair[1];
+        if p <= x1 {
+            if x1 <= x0 {
+                return y1;
+            }
+            return y0 + (y1 - y0) * (p - x0) / (x1 - x0);
+        }
+    }
+    return function[function.len() - 1].1;
+}
+
+// The smallest probability at which the increasing function reaches the value.
+fn first_at_least(function: &[(f64, f64)], value: f64) -> f64 {
+    if value <= function[0].1 {
+        return function[0].0;
+    }
+    for pair in function.windows(2) {
+        let (x0, y0) = pair[0];
+        let (x1, y1) = pair[1];
+        if value <= y1 {
+            return x0 + (x1 - x0) * (value - y0) / (y1 - y0);
+        }
+    }
+    return 1.0;
+}
+
+// The largest probability at which the increasing function is at most the value.
+fn last_at_most(function: &[(f64, f64)], value: f64) -> f64 {
+    if function[function.len() - 1].1 <= value {
+        return function[function.len() - 1].0;
+    }
+    for pair in function.windows(2).rev() {
+        let (x0, y0) = pair[0];
+        let (x1, y1) = pair[1];
+        if y0 <= value {
+            return x0 + (x1 - x0) * (value - y0) / (y1 - y0);
+        }
+    }
+    return 0.0;
+}
+
+// The function limited from above or below by the value, with the end points replaced.
+fn clip(function: &[(f64, f64)], value: f64, upper: bool, start: f64, end: f64) -> Piecewise {
+    let mut points = Vec::with_capacity(2 * function.len());
+    for (i, &(x, y)) in function.iter().enumerate() {
+        if 0 < i {
+            let (x0, y0) = function[i - 1];
+            if (y0 - value) * (y - value) < 0.0 {
+                points.push((x0 + (x - x0) * (value - y0) / (y - y0), value));
+            }
+        }
+        points.push((x, if upper { y.min(value) } else { y.max(value) }));
+    }
+    points[0].1 = start;
+    let last = points.len() - 1;
+    points[last].1 = end;
+    return points;
+}
+
+// The least concave function above the points, or the greatest convex function below them.
+fn hull(points: &[(f64, f64)], upper: bool) -> Piecewise {
+    let mut hull: Piecewise = Vec::with_capacity(points.len());
+    for &(x, y) in points {
+        while 2 <= hull.len() {
+            let (x0, y0) = hull[hull.len() - 2];
+            let (x1, y1) = hull[hull.len() - 1];
+            let turn = (x1 - x0) * (y - y0) - (y1 - y0) * (x - x0);
+            if (upper && 0.0 <= turn) || (!upper && turn <= 0.0) {
+                hull.pop();
+            } else {
+                break;
+            }
+        }
+        hull.push((x, y));
+    }
+    return hull;
+}
+
+impl MatchEquityTable {
+    // The table without the cube before the Crawford game.
+    pub fn generate(max_away: usize, gammon_rate: f64) -> MatchEquityTable {
+        return MatchEquityTable::generate_with(max_away, gammon_rate, false);
+    }
+
+    // The table with a live cube before the Crawford game.
+    pub fn generate_live_cube(max_away: usize, gammon_rate: f64) -> MatchEquityTable {
+        return MatchEquityTable::generate_with(max_away, gammon_rate, true);
+    }
+
+    fn generate_with(max_away: usize, gammon_rate: f64, live_cube: bool) -> MatchEquityTable {
+        let mut table = MatchEquityTable {
+            name: if live_cube { BUILTIN_TABLES[0] } else { BUILTIN_TABLES[1] }.to_string(),
+            pre_crawford: vec![vec![0.0; max_away]; max_away],
+            post_crawford: vec![0.0; max_away],
+        };
+        let single_rate = 1.0 - gammon_rate;
+        for away in 1..(max_away + 1) {
+            table.post_crawford[away - 1] = if away == 1 {
+                0.5
+            } else {
+                let post = |away: usize, points: usize| {
+                    if away <= points { 1.0 } else { table.post_crawford[away - points - 1] }
+                };
+                let take = 0.5 * (single_rate * post(away, 2) + gammon_rate * post(away, 4));
+                take.min(post(away, 1))
+            };
+        }
+        for sum in 2..(2 * max_away + 1) {
+            for away in 1..(max_away + 1) {
+                if sum <= away || max_away < sum - away {
+                    continue;
+                }
+                let opponent_away = sum - away;
+                let equity = if away == 1 && opponent_away == 1 {
+                    0.5
+                } else if away == 1 || opponent_away == 1 {
+                    // The Crawford game is played without the cube.
+                    let trailer_away = ::std::cmp::max(away, opponent_away) as u32;
+                    let post = |points: u32| table.equity(trailer_away.saturating_sub(points), 1, true);
+                    let trailer = 0.5 * (single_rate * post(1) + gammon_rate * post(2));
+                    if away == 1 { 1.0 - trailer } else { trailer }
+                } else {
+                    table.game_equity(away as u32, opponent_away as u32, gammon_rate, live_cube)
+                };
+                table.pre_crawford[away - 1][opponent_away - 1] = equity;
+            }
+        }
+        return table;
+    }
+
+    // The equity at the start of a game before the Crawford game, from the equities of the
+    // scores it can lead to.
+    fn game_equity(&self, away: u32, opponent_away: u32, gammon_rate: f64, live_cube: bool) -> f64 {
+        let score = MatchScore { away: away, opponent_away: opponent_away, crawford: false };
+        let results = |cube: u32| {
+            let cube = cube as i32;
+            let win = (1.0 - gammon_rate) * self.equity_after(&score, cube) +
+                gammon_rate * self.equity_after(&score, 2 * cube);
+            let lose = (1.0 - gammon_rate) * self.equity_after(&score, -cube) +
+                gammon_rate * self.equity_after(&score, -2 * cube);
+            (win, lose)
+        };
+        let (win, lose) = results(1);
+        if !live_cube {
+            return 0.5 * (win + lose);
+        }
+        // Once the cube is at least the points either player needs, it is dead.
+        let mut cube = 1;
+        while cube < away || cube < opponent_away {
+            cube *= 2;
+        }
+        let (top_win, top_lose) = results(cube);
+        let mut owned: Piecewise = vec![(0.0, top_lose), (1.0, top_win)];
+        let mut opponent_owned = owned.clone();
+        while 2 < cube {
+            cube /= 2;
+            let (win, lose) = results(cube);
+            let cash = self.equity_after(&score, cube as i32);
+            let drop = self.equity_after(&score, -(cube as i32));
+            // We double when the opponent's taking is no better for it than passing, and
+            // the opponent doubles when our taking is no better for us than passing.
+            let next_owned = hull(&clip(&opponent_owned, cash, true, lose, win), true);
+            opponent_owned = hull(&clip(&owned, drop, false, lose, win), false);
+            owned = next_owned;
+        }
+        let cash = self.equity_after(&score, 1);
+        let drop = self.equity_after(&score, -1);
+        let our_double = evaluate(&opponent_owned, 0.5).min(cash);
+        let their_double = evaluate(&owned, 0.5).max(drop);
+        let take_point = last_at_most(&owned, drop);
+        let opponent_take_point = first_at_least(&opponent_owned, cash);
+        let we_double = opponent_take_point <= 0.5;
+        let they_double = 0.5 <= take_point;
+        if we_double && they_double {
+            return 0.5 * (our_double + their_double);
+        }
+        if we_double {
+            return our_double;
+        }
+        if they_double {
+            return their_double;
+        }
+        // Between the take points the equity is linear.
+        return drop + (cash - drop) * (0.5 - take_point) / (opponent_take_point - take_point);
+    }
+
+    // A generated table by name.
+    pub fn builtin(name: &str) -> Option<MatchEquityTable> {
+        return match name {
+            "live-cube" => Some(MatchEquityTable::generate_live_cube(DEFAULT_MAX_AWAY, DEFAULT_GAMMON_RATE)),
+            "cubeless" => Some(MatchEquityTable::generate(DEFAULT_MAX_AWAY, DEFAULT_GAMMON_RATE)),
+            _ => None,
+        };
+    }
+
+    // A published or generated table by name.
+    pub fn named(name: &str) -> Result<MatchEquityTable, MetError> {
+        if let Some(&(_, path)) = PUBLISHED_TABLES.iter().find(|&&(published, _)| published == name) {
+            let mut table = MatchEquityTable::load(path).map_err(|e| {
+                return format!("{} (copy it from the met directory of GNU Backgammon)", e);
+            })?;
+            table.name = name.to_string();
+            return Ok(table);
+        }
+        return MatchEquityTable::builtin(name).ok_or(format!("unknown match equity table {}", name));
+    }
+
+    // The most points needed by either player in the table.
+    pub fn max_away(&self) -> usize {
+        return self.pre_crawford.len();
+    }
+
+    // The equity at the start of a game. After the Crawford game, the player needing 1
+    // point is the leader.
+    pub fn equity(&self, away: u32, opponent_away: u32, post_crawford: bool) -> f64 {
+        if away == 0 {
+            return 1.0;
+        }
+        if opponent_away == 0 {
+            return 0.0;
+        }
+        assert!(away as usize <= self.max_away() && opponent_away as usize <= self.max_away(),
+                "score {}-away {}-away is outside the table", away, opponent_away);
+        if post_crawford && away == 1 && opponent_away != 1 {
+            return 1.0 - self.post_crawford_equity(opponent_away);
+        }
+        if post_crawford && opponent_away == 1 && away != 1 {
+            return self.post_crawford_equity(away);
+        }
+        return self.pre_crawford[away as usize - 1][opponent_away as usize - 1];
+    }
+
+    // The equity of the trailer at the start of a game after the Crawford game.
+    pub fn post_crawford_equity(&self, away: u32) -> f64 {
+        return self.post_crawford[away as usize - 1];
+    }
+
+    // The equity after the game of the score, where the player wins or loses the points.
+    pub fn equity_after(&self, score: &MatchScore, points: i32) -> f64 {
+        let post_crawford = score.away == 1 || score.opponent_away == 1;
+        if 0 <= points {
+            let away = score.away.saturating_sub(points as u32);
+            return self.equity(away, score.opponent_away, post_crawford);
+        }
+        let opponent_away = score.opponent_away.saturating_sub((-points) as u32);
+        return self.equity(score.away, opponent_away, post_crawford);
+    }
+
+    // The probability of winning the game the player needs to take a double of the cube,
+    // ignoring gammons and later doubles.
+    pub fn take_point(&self, score: &MatchScore, cube: u32) -> f64 {
+        let pass = self.equity_after(score, -(cube as i32));
+        let win = self.equity_after(score, 2 * cube as i32);
+        let lose = self.equity_after(score, -2 * (cube as i32));
+        if win <= lose {
+            return 0.0;
+        }
+        return (pass - lose) / (win - lose);
+    }
+
+    // The values of winning and of losing a gammon rather than a single game with the cube,
+    // in units of half the difference between winning and losing a single game, so that
+    // both are 1 for money.
+    pub fn gammon_values(&self, score: &MatchScore, cube: u32) -> (f64, f64) {
+        let cube = cube as i32;
+        let win = self.equity_after(score, cube);
+        let lose = self.equity_after(score, -cube);
+        if win <= lose {
+            return (0.0, 0.0);
+        }
+        let unit = 0.5 * (win - lose);
+        return ((self.equity_after(score, 2 * cube) - win) / unit,
+                (lose - self.equity_after(score, -2 * cube)) / unit);
+    }
+
+    pub fn write_xml<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let write_row = |out: &mut W, row: &[f64]| -> io::Result<()> {
+            write!(out, "    <row>")?;
+            for equity in row {
+                write!(out, " <me>{:.6}</me>", equity)?;
+            }
+            return writeln!(out, " </row>");
+        };
+        writeln!(out, "<?xml version=\"1.0\" encoding=\"ISO-8859-1\" ?>")?;
+        writeln!(out, "<!DOCTYPE match-equity-table SYSTEM \"met.dtd\">")?;
+        writeln!(out, "<match-equity-table>")?;
+        writeln!(out, "  <info>")?;
+        writeln!(out, "    <name>{}</name>", self.name)?;
+        writeln!(out, "    <length>{}</length>", self.max_away())?;
+        writeln!(out, "  </info>")?;
+        writeln!(out, "  <pre-crawford-table type=\"explicit\">")?;
+        for row in &self.pre_crawford {
+            write_row(out, row)?;
+        }
+        writeln!(out, "  </pre-crawford-table>")?;
+        writeln!(out, "  <post-crawford-table player=\"both\" type=\"explicit\">")?;
+        write_row(out, &self.post_crawford)?;
+        writeln!(out, "  </post-crawford-table>")?;
+        return writeln!(out, "</match-equity-table>");
+    }
+
+    pub fn parse_xml(text: &str) -> Result<MatchEquityTable, MetError> {
+        let name = match elements(text, "name").first() {
+            Some(&(_, content)) => content.trim().to_string(),
+            None => String::new(),
+        };
+        let pre_tables = elements(text, "pre-crawford-table");
+        let &(attributes, content) = match pre_tables.first() {
+            Some(table) => table,
+            None => return Err("no pre-crawford-table".to_string()),
+        };
+        if !is_explicit(attributes) {
+            return Err("only explicit pre-crawford tables are supported".to_string());
+        }
+        let pre_crawford = elements(content, "row").iter()
+            .map(|&(_, row)| parse_row(row))
+            .collect::<Result<Vec<Vec<f64>>, MetError>>()?;
+        let length = pre_crawford.len();
+        if length == 0 || pre_crawford.iter().any(|row| row.len() != length) {
+            return Err("the pre-crawford table is not square".to_string());
+        }
+        // Both players share the post-Crawford table, or else take the first player's.
+        let post_tables = elements(text, "post-crawford-table");
+        let post_table = post_tables.iter()
+            .find(|&&(attributes, _)| attributes.contains("\"both\""))
+            .or_else(|| post_tables.first());
+        let &(attributes, content) = match post_table {
+            Some(table) => table,
+            None => return Err("no post-crawford-table".to_string()),
+        };
+        if !is_explicit(attributes) {
+            return Err("only explicit post-crawford tables are supported".to_string());
+        }
+        let post_crawford = match elements(content, "row").first() {
+            Some(&(_, row)) => parse_row(row)?,
+            None => return Err("the post-crawford table has no row".to_string()),
+        };
+        if post_crawford.len() < length {
+            return Err(format!("the post-crawford table has {} entries for length {}",
+                               post_crawford.len(), length));
+        }
+        return Ok(MatchEquityTable {
+            name: name,
+            pre_crawford: pre_crawford,
+            post_crawford: post_crawford[..length].to_vec(),
+        });
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), MetError> {
+        let result = File::create(path).and_then(|file| {
+            let mut out = BufWriter::new(file);
+            self.write_xml(&mut out)?;
+            return out.flush();
+        });
+        return result.map_err(|e| format!("{}: {}", path, e));
+    }
+
+    pub fn load(path: &str) -> Result<MatchEquityTable, MetError> {
+        let mut text = String::new();
+        match File::open(path).and_then(|mut file| file.read_to_string(&mut text)) {
+            Ok(_) => (),
+            Err(e) => return Err(format!("{}: {}", path, e)),
+        }
+        return MatchEquityTable::parse_xml(&text).map_err(|e| format!("{}: {}", path, e));
+    }
+}
+
+// The attributes and the contents of the elements with the tag, which must not nest.
+fn elements<'a>(text: &'a str, tag: &str) -> Vec<(&'a str, &'a str)> {
+    let open = format!("<{}", tag);
+    let close = format!("</{}>", tag);
+    let mut found = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(&open) {
+        let after = &rest[start + open.len()..];
+        // Skip longer tags starting with the same name.
+        if !after.starts_with(|c: char| c == '>' || c.is_whitespace()) {
+            rest = after;
+            continue;
+        }
+        let end_of_start = match after.find('>') {
+            Some(idx) => idx,
+            None => break,
+        };
+        let attributes = &after[..end_of_start];
+        let body = &after[end_of_start + 1..];
+        let end = match body.find(&close) {
+            Some(idx) => idx,
+            None => break,
+        };
+        found.push((attributes, &body[..end]));
+        rest = &body[end + close.len()..];
+    }
+    return found;
+}
+
+fn is_explicit(attributes: &str) -> bool {
+    return !attributes.contains("type") || attributes.contains("\"explicit\"");
+}
+
+fn parse_row(row: &str) -> Result<Vec<f64>, MetError> {
+    return elements(row, "me").iter().map(|&(_, value)| {
+        value.trim().parse::<f64>().map_err(|e| format!("invalid equity {}: {}", value.trim(), e))
+    }).collect();
+}
+
+impl Default for MatchEquityTable {
+    fn default() -> MatchEquityTable {
+        return match MatchEquityTable::named(DEFAULT_TABLE) {
+            Ok(table) => table,
+            Err(e) => {
+                warn!("using the generated live cube table instead of {}: {}", DEFAULT_TABLE, e);
+                MatchEquityTable::generate_live_cube(DEFAULT_MAX_AWAY, DEFAULT_GAMMON_RATE)
+            },
+        };
+    }
+}
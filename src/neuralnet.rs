@@ -0,0 +1,232 @@
+/*
+ * Rustgammon
+ *
+ * Neural network evaluator
+ * A feed-forward network with one hidden layer and the TD-Gammon input encoding.
+ * The outputs are the probabilities of winning, winning a gammon, winning a backgammon,
+ * losing a gammon and losing a backgammon.
+ *
+ * Weights are saved as text: a header line, the layer sizes, then the hidden layer
+ * weights and the output layer weights with the bias last for each unit.
+ *
+ * @author ryutaroikeda94@gmail.com
+ *
+ */
+
+extern crate rand;
+
+use std::fs::File;
+use std::io;
+use std::io::BufWriter;
+use std::io::Read;
+use std::io::Write;
+
+use rand::Rng;
+
+use evaluator::Evaluator;
+use evaluator::Probabilities;
+use evaluator::ProbabilityEvaluator;
+use rollout::game_result;
+use rustgammon::Backgammon;
+use rustgammon::BAR_POS;
+use rustgammon::BEARING_OFF_POS;
+use rustgammon::Color;
+
+pub type NeuralNetError = String;
+
+// Four units for each of the 24 points and one each for the bar and borne off checkers
+// for both players, and two units for the side on roll.
+pub const NUM_INPUTS: usize = 2 * (24 * 4 + 2) + 2;
+pub const NUM_OUTPUTS: usize = 5;
+pub const DEFAULT_HIDDEN: usize = 40;
+pub const DEFAULT_WEIGHTS_PATH: &str = "config/neuralnet.txt";
+
+const HEADER: &str = "rustgammon-neuralnet 1";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct NeuralNet {
+    pub hidden: usize,
+    // hidden * (NUM_INPUTS + 1) weights.
+    pub hidden_weights: Vec<f64>,
+    // NUM_OUTPUTS * (hidden + 1) weights.
+    pub output_weights: Vec<f64>,
+}
+
+pub fn sigmoid(x: f64) -> f64 {
+    return 1.0 / (1.0 + (-x).exp());
+}
+
+fn encode_player(game: &Backgammon, color: Color, inputs: &mut Vec<f64>) {
+    for pos in (BAR_POS + 1)..BEARING_OFF_POS {
+        let checkers = game.get_board(color, pos) as f64;
+        inputs.push(if 1.0 <= checkers { 1.0 } else { 0.0 });
+        inputs.push(if 2.0 <= checkers { 1.0 } else { 0.0 });
+        inputs.push(if 3.0 <= checkers { 1.0 } else { 0.0 });
+        inputs.push(if 3.0 < checkers { (checkers - 3.0) / 2.0 } else { 0.0 });
+    }
+    inputs.push(game.get_board(color, BAR_POS) as f64 / 2.0);
+    inputs.push(game.get_board(color, BEARING_OFF_POS) as f64 / 15.0);
+}
+
+// Encode the position from the player's side, with the player's checkers first.
+pub fn encode(game: &Backgammon, color: Color, on_roll: bool) -> Vec<f64> {
+    let mut inputs = Vec::with_capacity(NUM_INPUTS);
+    encode_player(game, color, &mut inputs);
+    encode_player(game, color.opposite(), &mut inputs);
+    inputs.push(if on_roll { 1.0 } else { 0.0 });
+    inputs.push(if on_roll { 0.0 } else { 1.0 });
+    return inputs;
+}
+
+impl NeuralNet {
+    // A network with small random weights.
+    pub fn random<R: Rng>(hidden: usize, rng: &mut R) -> NeuralNet {
+        let hidden_weights = (0..hidden * (NUM_INPUTS + 1))
+            .map(|_| rng.gen_range(-0.1, 0.1)).collect();
+        let output_weights = (0..NUM_OUTPUTS * (hidden + 1))
+            .map(|_| rng.gen_range(-0.1, 0.1)).collect();
+        return NeuralNet {
+            hidden: hidden,
+            hidden_weights: hidden_weights,
+            output_weights: output_weights,
+        };
+    }
+
+    pub fn new(hidden: usize) -> NeuralNet {
+        return NeuralNet::random(hidden, &mut rand::thread_rng());
+    }
+
+    // Compute the hidden and output activations.
+    pub fn forward(&self, inputs: &[f64]) -> (Vec<f64>, [f64; NUM_OUTPUTS]) {
+        debug_assert!(inputs.len() == NUM_INPUTS);
+        // Most inputs are zero.
+        let active: Vec<(usize, f64)> = inputs.iter().cloned().enumerate()
+            .filter(|&(_, input)| input != 0.0).collect();
+        let mut hidden = Vec::with_capacity(self.hidden);
+        for unit in 0..self.hidden {
+            let weights = &self.hidden_weights[unit * (NUM_INPUTS + 1)..(unit + 1) * (NUM_INPUTS + 1)];
+            let mut sum = weights[NUM_INPUTS];
+            for &(i, input) in &active {
+                sum += weights[i] * input;
+            }
+            hidden.push(sigmoid(sum));
+        }
+        let mut outputs = [0.0; NUM_OUTPUTS];
+        for (unit, output) in outputs.iter_mut().enumerate() {
+            let weights = &self.output_weights[unit * (self.hidden + 1)..(unit + 1) * (self.hidden + 1)];
+            let mut sum = weights[self.hidden];
+            for (weight, activation) in weights.iter().zip(hidden.iter()) {
+                sum += weight * activation;
+            }
+            *output = sigmoid(sum);
+        }
+        return (hidden, outputs);
+    }
+
+    // The probabilities for the player, who may or may not be on roll.
+    pub fn evaluate_for(&self, game: &Backgammon, color: Color, on_roll: bool) -> Probabilities {
+        let (_, outputs) = self.forward(&encode(game, color, on_roll));
+        return to_probabilities(&outputs);
+    }
+
+    pub fn write<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "{}", HEADER)?;
+        writeln!(out, "{} {} {}", NUM_INPUTS, self.hidden, NUM_OUTPUTS)?;
+        for weights in &[&self.hidden_weights, &self.output_weights] {
+            for (i, weight) in weights.iter().enumerate() {
+                let separator = if (i + 1) % 8 == 0 { "\n" } else { " " };
+                write!(out, "{:e}{}", weight, separator)?;
+            }
+            writeln!(out)?;
+        }
+        return Ok(());
+    }
+
+    pub fn parse(text: &str) -> Result<NeuralNet, NeuralNetError> {
+        let mut lines = text.lines();
+        if lines.next().map(|line| line.trim()) != Some(HEADER) {
+            return Err("not a rustgammon neural network".to_string());
+        }
+        let mut words = lines.flat_map(|line| line.split_whitespace());
+        let mut sizes = [0usize; 3];
+        for size in sizes.iter_mut() {
+            *size = match words.next().map(|v| v.parse::<usize>()) {
+                Some(Ok(v)) => v,
+                _ => return Err("invalid layer sizes".to_string()),
+            };
+        }
+        if sizes[0] != NUM_INPUTS || sizes[2] != NUM_OUTPUTS {
+            return Err(format!("expected {} inputs and {} outputs", NUM_INPUTS, NUM_OUTPUTS));
+        }
+        let hidden = sizes[1];
+        let mut weights = Vec::new();
+        for word in words {
+            match word.parse::<f64>() {
+                Ok(v) => weights.push(v),
+                Err(e) => return Err(format!("invalid weight {}: {}", word, e)),
+            }
+        }
+        let num_hidden_weights = hidden * (NUM_INPUTS + 1);
+        if weights.len() != num_hidden_weights + NUM_OUTPUTS * (hidden + 1) {
+            return Err(format!("expected {} weights but found {}",
+                               num_hidden_weights + NUM_OUTPUTS * (hidden + 1), weights.len()));
+        }
+        let output_weights = weights.split_off(num_hidden_weights);
+        return Ok(NeuralNet { hidden: hidden, hidden_weights: weights, output_weights: output_weights });
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), NeuralNetError> {
+        let result = File::create(path).and_then(|file| {
+            let mut out = BufWriter::new(file);
+            self.write(&mut out)?;
+            return out.flush();
+        });
+        return result.map_err(|e| format!("{}: {}", path, e));
+    }
+
+    pub fn load(path: &str) -> Result<NeuralNet, NeuralNetError> {
+        let mut text = String::new();
+        match File::open(path).and_then(|mut file| file.read_to_string(&mut text)) {
+            Ok(_) => (),
+            Err(e) => return Err(format!("{}: {}", path, e)),
+        }
+        return NeuralNet::parse(&text);
+    }
+}
+
+// Keep the outputs consistent: a gammon is also a win and a backgammon is also a gammon.
+pub fn to_probabilities(outputs: &[f64; NUM_OUTPUTS]) -> Probabilities {
+    let win = outputs[0];
+    let win_gammon = outputs[1].min(win);
+    let lose_gammon = outputs[3].min(1.0 - win);
+    return Probabilities {
+        win: win,
+        win_gammon: win_gammon,
+        win_backgammon: outputs[2].min(win_gammon),
+        lose_gammon: lose_gammon,
+        lose_backgammon: outputs[4].min(lose_gammon),
+    };
+}
+
+// The outputs giving the probabilities.
+pub fn to_outputs(probabilities: &Probabilities) -> [f64; NUM_OUTPUTS] {
+    return [probabilities.win, probabilities.win_gammon, probabilities.win_backgammon,
+            probabilities.lose_gammon, probabilities.lose_backgammon];
+}
+
+impl Evaluator for NeuralNet {
+    fn evaluate(&self, game: &Backgammon, color: Color) -> f64 {
+        return self.probabilities(game, color).equity();
+    }
+
+    fn evaluate_finished(&self, game: &Backgammon, winner: Color, color: Color) -> f64 {
+        return game_result(game, winner, color).equity();
+    }
+}
+
+impl ProbabilityEvaluator for NeuralNet {
+    fn probabilities(&self, game: &Backgammon, color: Color) -> Probabilities {
+        // The player has just moved, so the opponent is on roll.
+        return self.evaluate_for(game, color, false);
+    }
+}
@@ -0,0 +1,78 @@
+/*
+ * Rustgammon
+ *
+ * Notation
+ * Write moves in standard notation with the point numbers the command line player
+ * reads, for example "8/5 6/5*" where the star marks a hit.
+ * Each player numbers the points from its own home board, so the numbers depend on the
+ * perspective the board is seen from. Unless told otherwise the points are numbered for
+ * Red.
+ *
+ * @author ryutaroikeda94@gmail.com
+ *
+ */
+
+use rustgammon::Backgammon;
+use rustgammon::BAR_POS;
+use rustgammon::BEARING_OFF_POS;
+use rustgammon::Color;
+use rustgammon::Move;
+use rustgammon::Position;
+
+// The side of the board the home board of the player seen from is drawn on.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Orientation {
+    HomeRight,
+    HomeLeft,
+}
+
+// The number of a point from the perspective of a player. Its own positions count down
+// to its home board and the positions of the other player are its points.
+pub fn point_number(perspective: Color, color: Color, pos: Position) -> usize {
+    if color == perspective {
+        return BEARING_OFF_POS - pos;
+    }
+    return pos;
+}
+
+// The position of a player on the point of the number from the perspective of a player.
+pub fn point_position(perspective: Color, color: Color, point: usize) -> Position {
+    return point_number(perspective, color, point);
+}
+
+// The point number of a position as the command line player reads it.
+pub fn point_name(color: Color, pos: Position) -> String {
+    return point_name_from(Color::Red, color, pos);
+}
+
+pub fn point_name_from(perspective: Color, color: Color, pos: Position) -> String {
+    if pos == BAR_POS {
+        return "bar".to_string();
+    }
+    if BEARING_OFF_POS <= pos {
+        return "off".to_string();
+    }
+    return point_number(perspective, color, pos).to_string();
+}
+
+// Write the submoves in the order they are played.
+pub fn format_move(game: &Backgammon, color: Color, mov: &Move) -> String {
+    return format_move_from(Color::Red, game, color, mov);
+}
+
+pub fn format_move_from(perspective: Color, game: &Backgammon, color: Color, mov: &Move) -> String {
+    if mov.submoves.is_empty() {
+        return "cannot move".to_string();
+    }
+    let mut game = *game;
+    let mut parts = Vec::with_capacity(mov.submoves.len());
+    for submove in mov.submoves.iter().rev() {
+        let to = ::std::cmp::min(submove.from + submove.die, BEARING_OFF_POS);
+        let is_hit = to != BEARING_OFF_POS &&
+            game.get_board(color.opposite(), game.get_opposite_pos(to)) == 1;
+        parts.push(format!("{}/{}{}", point_name_from(perspective, color, submove.from),
+                           point_name_from(perspective, color, to), if is_hit { "*" } else { "" }));
+        game.do_submove(color, submove);
+    }
+    return parts.join(" ");
+}
@@ -0,0 +1,346 @@
+/*
+ * Rustgammon
+ *
+ * Packed position
+ * A compact position for fast move generation in self-play and rollouts.
+ * Each player's board is a u128 holding four bits of checkers for each of the 26
+ * positions, so moving a checker is an addition and a subtraction.
+ * Moves are generated into a caller-provided buffer without allocating and without
+ * duplicate resulting positions.
+ *
+ * @author ryutaroikeda94@gmail.com
+ *
+ */
+
+use rustgammon::Backgammon;
+use rustgammon::BAR_POS;
+use rustgammon::BEARING_OFF_POS;
+use rustgammon::BOARD_SIZE;
+use rustgammon::Checker;
+use rustgammon::Color;
+use rustgammon::DiceRoll;
+use rustgammon::Move;
+use rustgammon::Position;
+use rustgammon::Submove;
+
+pub type PackedBoard = u128;
+
+// Enough for the distinct positions reachable with any roll. The position after a move is
+// fixed by the dice each checker plays, since a blot can be hit only once and a checker
+// playing a double passes the same points in any order. With at most four dice among 15
+// checkers that is at most 18 choose 4 = 3060 positions for a double. Otherwise it is 15 *
+// 15 choices of checkers plus 15 for one checker playing the dice in the other order.
+pub const MAX_MOVES: usize = 8192;
+
+// The bound above on the distinct positions reachable with a double.
+pub const MAX_DOUBLE_MOVES: usize = 3060;
+
+const _: () = assert!(MAX_DOUBLE_MOVES <= MAX_MOVES && 15 * 15 + 15 <= MAX_MOVES);
+
+const HASH_TABLE_SIZE: usize = 2 * MAX_MOVES;
+
+const START_OF_HOME_BOARD: Position = 19;
+
+// Every position outside the home board, including the bar.
+const OUTSIDE_HOME_MASK: PackedBoard = (1 << (4 * START_OF_HOME_BOARD)) - 1;
+
+// The lowest bit of the nibble of every position on which a checker can stand before
+// bearing off.
+const NIBBLE_LOW_BITS: PackedBoard = 0x0001_1111_1111_1111_1111_1111_1111;
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Debug, Default)]
+pub struct PackedPosition {
+    pub red: PackedBoard,
+    pub white: PackedBoard,
+}
+
+// A move with its submoves in the order played and the position it leads to.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct PackedMove {
+    // Pairs of from and die.
+    pub submoves: [(u8, u8); 4],
+    pub len: u8,
+    pub position: PackedPosition,
+}
+
+pub struct MoveBuffer {
+    moves: Box<[PackedMove]>,
+    len: usize,
+    // The most dice played by any move so far.
+    max_dice: usize,
+    // Open addressing table of move indices for removing duplicates. An entry is valid
+    // if its upper bits equal the current generation.
+    table: Box<[u32]>,
+    generation: u32,
+}
+
+#[inline]
+fn get(board: PackedBoard, pos: Position) -> u32 {
+    return ((board >> (4 * pos)) & 0xf) as u32;
+}
+
+#[inline]
+fn unit(pos: Position) -> PackedBoard {
+    return 1 << (4 * pos);
+}
+
+impl PackedPosition {
+    pub fn new(game: &Backgammon) -> PackedPosition {
+        let mut position: PackedPosition = Default::default();
+        for pos in 0..BOARD_SIZE {
+            position.red |= (game.get_board(Color::Red, pos) as PackedBoard) << (4 * pos);
+            position.white |= (game.get_board(Color::White, pos) as PackedBoard) << (4 * pos);
+        }
+        return position;
+    }
+
+    pub fn to_backgammon(&self) -> Backgammon {
+        let mut game: Backgammon = Default::default();
+        for pos in 0..BOARD_SIZE {
+            game.set_board(Color::Red, pos, get(self.red, pos) as Checker);
+            game.set_board(Color::White, pos, get(self.white, pos) as Checker);
+        }
+        return game;
+    }
+
+    pub fn get(&self, color: Color, pos: Position) -> Checker {
+        return get(self.board(color), pos) as Checker;
+    }
+
+    pub fn board(&self, color: Color) -> PackedBoard {
+        return match color {
+            Color::Red => self.red,
+            Color::White => self.white,
+        }
+    }
+
+    fn from_boards(color: Color, own: PackedBoard, opponent: PackedBoard) -> PackedPosition {
+        return match color {
+            Color::Red => PackedPosition { red: own, white: opponent },
+            Color::White => PackedPosition { red: opponent, white: own },
+        }
+    }
+
+    // List the moves for the roll into the buffer, one for each distinct resulting position.
+    pub fn generate_moves(&self, color: Color, roll: DiceRoll, buffer: &mut MoveBuffer) {
+        buffer.clear();
+        let own = self.board(color);
+        let opponent = self.board(color.opposite());
+        let mut generator = Generator {
+            color: color,
+            buffer: buffer,
+            dice: [roll.0 as u8; 4],
+            num_dice: 4,
+            is_double: roll.0 == roll.1,
+            submoves: [(0, 0); 4],
+        };
+        if generator.is_double {
+            generator.search(own, opponent, 0, BAR_POS);
+            return;
+        }
+        let high = ::std::cmp::max(roll.0, roll.1) as u8;
+        let low = ::std::cmp::min(roll.0, roll.1) as u8;
+        generator.num_dice = 2;
+        generator.dice = [high, low, 0, 0];
+        generator.search(own, opponent, 0, BAR_POS);
+        generator.dice = [low, high, 0, 0];
+        generator.search(own, opponent, 0, BAR_POS);
+        // If only one die can be played, the higher one must be played if possible.
+        if generator.buffer.max_dice == 1 {
+            generator.buffer.retain_first_die(high);
+        }
+    }
+}
+
+struct Generator<'a> {
+    color: Color,
+    buffer: &'a mut MoveBuffer,
+    dice: [u8; 4],
+    num_dice: usize,
+    is_double: bool,
+    submoves: [(u8, u8); 4],
+}
+
+impl<'a> Generator<'a> {
+    #[inline]
+    fn can_do_submove(&self, own: PackedBoard, opponent: PackedBoard, from: Position,
+                      die: Position) -> bool {
+        let destination = from + die;
+        if BEARING_OFF_POS <= destination {
+            if own & OUTSIDE_HOME_MASK != 0 {
+                return false;
+            }
+            // Bearing off with a higher die needs no checkers on higher points.
+            if BEARING_OFF_POS < destination {
+                let higher_mask = (unit(from) - 1) & !OUTSIDE_HOME_MASK;
+                if own & higher_mask != 0 {
+                    return false;
+                }
+            }
+            return true;
+        }
+        return get(opponent, BEARING_OFF_POS - destination) < 2;
+    }
+
+    // Extend the current move by one submove at every depth, or record it if no
+    // submove is possible.
+    fn search(&mut self, own: PackedBoard, opponent: PackedBoard, depth: usize,
+              min_from: Position) {
+        if depth == self.num_dice {
+            self.record(own, opponent, depth);
+            return;
+        }
+        let die = self.dice[depth] as Position;
+        // Checkers on the bar must enter first.
+        let occupied = if get(own, BAR_POS) != 0 {
+            1
+        } else {
+            (own | own >> 1 | own >> 2 | own >> 3) & NIBBLE_LOW_BITS & !(unit(min_from) - 1)
+        };
+        let mut found = false;
+        let mut remaining = occupied;
+        while remaining != 0 {
+            let from = (remaining.trailing_zeros() / 4) as Position;
+            remaining &= remaining - 1;
+            if from < min_from || !self.can_do_submove(own, opponent, from, die) {
+                continue;
+            }
+            found = true;
+            let destination = ::std::cmp::min(from + die, BEARING_OFF_POS);
+            let next_own = own - unit(from) + unit(destination);
+            let mut next_opponent = opponent;
+            let opposite_pos = BEARING_OFF_POS - destination;
+            if destination != BEARING_OFF_POS && get(opponent, opposite_pos) == 1 {
+                next_opponent = opponent - unit(opposite_pos) + unit(BAR_POS);
+            }
+            self.submoves[depth] = (from as u8, die as u8);
+            // With doubles, playing the submoves in order of position finds every move.
+            let next_min_from = if self.is_double { from } else { BAR_POS };
+            self.search(next_own, next_opponent, depth + 1, next_min_from);
+        }
+        if !found && 0 < depth {
+            self.record(own, opponent, depth);
+        }
+    }
+
+    fn record(&mut self, own: PackedBoard, opponent: PackedBoard, depth: usize) {
+        let mov = PackedMove {
+            submoves: self.submoves,
+            len: depth as u8,
+            position: PackedPosition::from_boards(self.color, own, opponent),
+        };
+        self.buffer.insert(mov);
+    }
+}
+
+impl MoveBuffer {
+    pub fn new() -> MoveBuffer {
+        return MoveBuffer {
+            moves: vec![Default::default(); MAX_MOVES].into_boxed_slice(),
+            len: 0,
+            max_dice: 0,
+            table: vec![0; HASH_TABLE_SIZE].into_boxed_slice(),
+            generation: 1,
+        };
+    }
+
+    pub fn len(&self) -> usize {
+        return self.len;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.len == 0;
+    }
+
+    pub fn moves(&self) -> &[PackedMove] {
+        return &self.moves[..self.len];
+    }
+
+    fn clear(&mut self) {
+        self.len = 0;
+        self.max_dice = 0;
+        self.next_generation();
+    }
+
+    fn next_generation(&mut self) {
+        self.generation += 1;
+        // The generation lives in the upper 16 bits of a table entry.
+        if self.generation == 1 << 16 {
+            for entry in self.table.iter_mut() {
+                *entry = 0;
+            }
+            self.generation = 1;
+        }
+    }
+
+    fn hash(position: &PackedPosition) -> usize {
+        let mixed = position.red.wrapping_mul(0x9e37_79b9_7f4a_7c15_f39c_c060_5ced_c835)
+            ^ position.white.wrapping_mul(0xc2b2_ae3d_27d4_eb4f_1656_67b1_9e37_79f9);
+        return ((mixed >> 64) as u64 ^ mixed as u64) as usize;
+    }
+
+    fn insert(&mut self, mov: PackedMove) {
+        let dice = mov.len as usize;
+        if dice < self.max_dice {
+            return;
+        }
+        if self.max_dice < dice {
+            // Moves must play as many dice as possible, so drop the shorter ones.
+            self.len = 0;
+            self.max_dice = dice;
+            self.next_generation();
+        }
+        let mask = HASH_TABLE_SIZE - 1;
+        let mut slot = MoveBuffer::hash(&mov.position) & mask;
+        loop {
+            let entry = self.table[slot];
+            if entry >> 16 != self.generation {
+                break;
+            }
+            let index = (entry & 0xffff) as usize;
+            if self.moves[index].position == mov.position {
+                return;
+            }
+            slot = (slot + 1) & mask;
+        }
+        // Cannot fail, because MAX_MOVES bounds the distinct positions reachable.
+        assert!(self.len < MAX_MOVES, "too many moves");
+        self.table[slot] = self.generation << 16 | self.len as u32;
+        self.moves[self.len] = mov;
+        self.len += 1;
+    }
+
+    // Keep only the moves that play the die first.
+    fn retain_first_die(&mut self, die: u8) {
+        let has_die = self.moves().iter().any(|mov| mov.submoves[0].1 == die);
+        if !has_die {
+            return;
+        }
+        let mut kept = 0;
+        for i in 0..self.len {
+            if self.moves[i].submoves[0].1 == die {
+                self.moves[kept] = self.moves[i];
+                kept += 1;
+            }
+        }
+        self.len = kept;
+    }
+}
+
+impl Default for MoveBuffer {
+    fn default() -> MoveBuffer {
+        return MoveBuffer::new();
+    }
+}
+
+impl PackedMove {
+    // Convert to a move with submoves as a stack.
+    pub fn to_move(&self) -> Move {
+        let mut submoves = Vec::with_capacity(self.len as usize);
+        for i in (0..self.len as usize).rev() {
+            let (from, die) = self.submoves[i];
+            submoves.push(Submove { from: from as Position, die: die as usize });
+        }
+        return Move { submoves: submoves };
+    }
+}
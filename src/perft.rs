@@ -0,0 +1,171 @@
+/*
+ * Rustgammon
+ *
+ * Perft
+ * Count the distinct positions reachable from reference positions to catch rule changes
+ * that alter move generation, and time the move generators.
+ *
+ * @author ryutaroikeda94@gmail.com
+ *
+ */
+
+use std::collections::HashSet;
+use std::time::Duration;
+use std::time::Instant;
+
+use packed::MoveBuffer;
+use packed::PackedPosition;
+use rustgammon::Backgammon;
+use rustgammon::Checker;
+use rustgammon::Color;
+use rustgammon::DiceRoll;
+use rustgammon::Position;
+
+pub struct ReferencePosition {
+    pub name: &'static str,
+    pub game: Backgammon,
+    pub color: Color,
+}
+
+pub struct BenchResult {
+    pub calls: u64,
+    pub moves: u64,
+    pub elapsed: Duration,
+}
+
+// The 21 distinct rolls with the number of ways each can be rolled.
+pub fn distinct_rolls() -> Vec<(DiceRoll, u32)> {
+    let mut rolls = Vec::with_capacity(21);
+    for a in 1..7 {
+        for b in a..7 {
+            rolls.push(((a, b), if a == b { 1 } else { 2 }));
+        }
+    }
+    return rolls;
+}
+
+// The index of a roll in distinct_rolls, in either order of the dice.
+pub fn roll_index(roll: DiceRoll) -> usize {
+    let low = ::std::cmp::min(roll.0, roll.1) - 1;
+    let high = ::std::cmp::max(roll.0, roll.1) - 1;
+    // The rolls are ordered by the lower die, and there are 6 - low rolls for each.
+    return low * (13 - low) / 2 + high - low;
+}
+
+fn position(red: &[(Position, Checker)], white: &[(Position, Checker)]) -> Backgammon {
+    let mut game: Backgammon = Default::default();
+    for &(pos, checkers) in red {
+        game.set_board(Color::Red, pos, checkers);
+    }
+    for &(pos, checkers) in white {
+        game.set_board(Color::White, pos, checkers);
+    }
+    return game;
+}
+
+pub fn reference_positions() -> Vec<ReferencePosition> {
+    let mut opening: Backgammon = Default::default();
+    opening.init();
+    return vec!(
+        ReferencePosition { name: "opening", game: opening, color: Color::Red },
+        ReferencePosition {
+            name: "middle game",
+            game: position(&[(1, 2), (10, 1), (12, 4), (14, 1), (17, 2), (19, 3), (20, 2)],
+                           &[(1, 1), (2, 2), (12, 3), (16, 2), (18, 2), (19, 3), (21, 2)]),
+            color: Color::Red,
+        },
+        ReferencePosition {
+            name: "on the bar",
+            game: position(&[(0, 2), (7, 2), (12, 4), (17, 3), (19, 2), (21, 2)],
+                           &[(1, 1), (12, 2), (19, 3), (20, 2), (21, 3), (22, 2), (24, 2)]),
+            color: Color::Red,
+        },
+        ReferencePosition {
+            name: "bearing off",
+            game: position(&[(19, 2), (20, 3), (21, 2), (22, 3), (23, 3), (24, 1), (25, 1)],
+                           &[(7, 2), (19, 5), (20, 4), (21, 4)]),
+            color: Color::Red,
+        },
+    );
+}
+
+// The number of distinct positions reachable with the roll, from list_moves.
+pub fn count_positions(game: &Backgammon, color: Color, roll: DiceRoll) -> usize {
+    let mut positions = HashSet::new();
+    for mov in &game.list_moves(color, roll) {
+        let mut next = *game;
+        next.do_move(color, mov);
+        positions.insert(PackedPosition::new(&next));
+    }
+    return positions.len();
+}
+
+// Count the leaves of the tree of all rolls and distinct moves to the depth, with the
+// players taking turns. A roll with no legal move passes the turn.
+pub fn perft(position: &PackedPosition, color: Color, depth: usize) -> u64 {
+    let mut buffers: Vec<MoveBuffer> = (0..depth).map(|_| MoveBuffer::new()).collect();
+    return perft_r(position, color, &mut buffers);
+}
+
+fn perft_r(position: &PackedPosition, color: Color, buffers: &mut [MoveBuffer]) -> u64 {
+    let (buffer, tail) = match buffers.split_first_mut() {
+        Some((head, tail)) => (head, tail),
+        None => return 1,
+    };
+    let mut leaves = 0;
+    for (roll, _) in distinct_rolls() {
+        position.generate_moves(color, roll, buffer);
+        if buffer.is_empty() {
+            leaves += perft_r(position, color.opposite(), tail);
+            continue;
+        }
+        for mov in buffer.moves() {
+            leaves += perft_r(&mov.position, color.opposite(), tail);
+        }
+    }
+    return leaves;
+}
+
+// Generate the moves for all 21 rolls from each position the given number of times.
+pub fn bench_list_moves(positions: &[ReferencePosition], iterations: u64) -> BenchResult {
+    let start = Instant::now();
+    let mut result = BenchResult { calls: 0, moves: 0, elapsed: Duration::new(0, 0) };
+    for _ in 0..iterations {
+        for reference in positions {
+            for (roll, _) in distinct_rolls() {
+                result.moves += reference.game.list_moves(reference.color, roll).len() as u64;
+                result.calls += 1;
+            }
+        }
+    }
+    result.elapsed = start.elapsed();
+    return result;
+}
+
+pub fn bench_generate_moves(positions: &[ReferencePosition], iterations: u64) -> BenchResult {
+    let packed: Vec<PackedPosition> =
+        positions.iter().map(|reference| PackedPosition::new(&reference.game)).collect();
+    let mut buffer = MoveBuffer::new();
+    let start = Instant::now();
+    let mut result = BenchResult { calls: 0, moves: 0, elapsed: Duration::new(0, 0) };
+    for _ in 0..iterations {
+        for (reference, position) in positions.iter().zip(packed.iter()) {
+            for (roll, _) in distinct_rolls() {
+                position.generate_moves(reference.color, roll, &mut buffer);
+                result.moves += buffer.len() as u64;
+                result.calls += 1;
+            }
+        }
+    }
+    result.elapsed = start.elapsed();
+    return result;
+}
+
+impl BenchResult {
+    pub fn print(&self, name: &str) {
+        let seconds = self.elapsed.as_secs() as f64 + self.elapsed.subsec_nanos() as f64 * 1e-9;
+        let micros_per_call = if self.calls == 0 { 0.0 } else { seconds * 1e6 / self.calls as f64 };
+        println!("{}: {} calls, {} moves in {:.3}s ({:.2}us per call)",
+                 name, self.calls, self.moves, seconds, micros_per_call);
+    }
+}
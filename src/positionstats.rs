@@ -0,0 +1,102 @@
+/*
+ * Rustgammon
+ *
+ * Position statistics
+ * Simple counts describing a player's position, for display and for computer players.
+ *
+ * @author ryutaroikeda94@gmail.com
+ *
+ */
+
+use std::fmt;
+
+use rustgammon::Backgammon;
+use rustgammon::BAR_POS;
+use rustgammon::BEARING_OFF_POS;
+use rustgammon::Checker;
+use rustgammon::Color;
+use rustgammon::Position;
+
+// The first position of the home board relative to the player.
+pub const HOME_BOARD_POS: Position = 19;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PositionStats {
+    pub pip_count: u32,
+    pub off: Checker,
+    pub bar: Checker,
+    // Points with a single checker.
+    pub blots: u32,
+    // Points with two or more checkers.
+    pub made_points: u32,
+    // The longest run of consecutive made points.
+    pub prime_length: u32,
+    pub home_board_points: u32,
+    // The pips the Keith count adds for stacking on the low points and gaps on the high
+    // points of the home board, a rough stand-in for the pips wasted bearing off.
+    pub keith_adjustment: u32,
+}
+
+impl PositionStats {
+    pub fn new(game: &Backgammon, color: Color) -> PositionStats {
+        let mut blots = 0;
+        let mut made_points = 0;
+        let mut prime_length = 0;
+        let mut home_board_points = 0;
+        let mut run = 0;
+        for pos in (BAR_POS + 1)..BEARING_OFF_POS {
+            let checkers = game.get_board(color, pos);
+            if checkers == 1 {
+                blots += 1;
+            }
+            if 2 <= checkers {
+                made_points += 1;
+                run += 1;
+                prime_length = ::std::cmp::max(prime_length, run);
+                if HOME_BOARD_POS <= pos {
+                    home_board_points += 1;
+                }
+            } else {
+                run = 0;
+            }
+        }
+        return PositionStats {
+            pip_count: game.pip_count(color),
+            off: game.get_board(color, BEARING_OFF_POS),
+            bar: game.get_board(color, BAR_POS),
+            blots: blots,
+            made_points: made_points,
+            prime_length: prime_length,
+            home_board_points: home_board_points,
+            keith_adjustment: keith_adjustment(game, color),
+        };
+    }
+}
+
+// The point numbered from the player's side, where 1 is the last point before bearing off.
+fn checkers_on_point(game: &Backgammon, color: Color, point: usize) -> u32 {
+    return game.get_board(color, BEARING_OFF_POS - point) as u32;
+}
+
+// Stacked checkers on the 1, 2 and 3 points and gaps on the 4, 5 and 6 points.
+// These are the adjustments of the Keith count.
+pub fn keith_adjustment(game: &Backgammon, color: Color) -> u32 {
+    let mut adjustment = 2 * checkers_on_point(game, color, 1).saturating_sub(1);
+    adjustment += checkers_on_point(game, color, 2).saturating_sub(1);
+    adjustment += checkers_on_point(game, color, 3).saturating_sub(3);
+    for point in 4..7 {
+        if checkers_on_point(game, color, point) == 0 {
+            adjustment += 1;
+        }
+    }
+    return adjustment;
+}
+
+impl fmt::Display for PositionStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "pips: {}, off: {}, bar: {}, blots: {}, points: {}, prime: {}, \
+                   home points: {}, keith adjustment: {}",
+               self.pip_count, self.off, self.bar, self.blots, self.made_points,
+               self.prime_length, self.home_board_points, self.keith_adjustment)
+    }
+}
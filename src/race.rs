@@ -0,0 +1,239 @@
+/*
+ * Rustgammon
+ *
+ * Race formulas
+ * Quick assessments of a race, where contact is broken, for deciding whether to double
+ * without a full evaluation. Each formula adjusts the pip counts in its own way and
+ * compares the counts against its thresholds for doubling and for taking.
+ * The win probability is estimated from the adjusted counts by treating the pips rolled
+ * as normally distributed, with the player on roll half a roll ahead.
+ *
+ * @author ryutaroikeda94@gmail.com
+ *
+ */
+
+use std::fmt;
+
+use evaluator::Evaluator;
+use evaluator::Probabilities;
+use evaluator::ProbabilityEvaluator;
+use positionstats::keith_adjustment;
+use rustgammon::Backgammon;
+use rustgammon::BAR_POS;
+use rustgammon::BEARING_OFF_POS;
+use rustgammon::Color;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RaceFormula {
+    Keith,
+    Thorp,
+    Trice,
+    Isight,
+}
+
+pub const FORMULAS: [RaceFormula; 4] =
+    [RaceFormula::Keith, RaceFormula::Thorp, RaceFormula::Trice, RaceFormula::Isight];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CubeAction {
+    NoDouble,
+    DoubleTake,
+    DoublePass,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RaceAssessment {
+    pub formula: RaceFormula,
+    // The adjusted counts of the player on roll and of the opponent, before the player on
+    // roll is given the adjustment for being on roll.
+    pub count: f64,
+    pub opponent_count: f64,
+    // The estimated probability that the player on roll wins.
+    pub win_probability: f64,
+    pub action: CubeAction,
+}
+
+// Estimates the win probability from the Keith counts, without gammons. It needs no
+// weights, and is good in a race but only a rough guide while the players are in contact.
+pub struct RaceEvaluator;
+
+// The mean and the variance of the pips of a roll, where doubles are played four times.
+const ROLL_MEAN: f64 = 49.0 / 6.0;
+const ROLL_VARIANCE: f64 = 665.0 / 36.0;
+
+const START_OF_HOME_BOARD: usize = 19;
+
+fn checkers_on_board(game: &Backgammon, color: Color) -> u32 {
+    return (BAR_POS..BEARING_OFF_POS).map(|pos| game.get_board(color, pos) as u32).sum();
+}
+
+fn home_points_occupied(game: &Backgammon, color: Color) -> u32 {
+    return (START_OF_HOME_BOARD..BEARING_OFF_POS)
+        .filter(|&pos| 0 < game.get_board(color, pos))
+        .count() as u32;
+}
+
+// The pip count plus 2 pips for each checker on board, 1 pip for each checker on the one
+// point and less 1 pip for each occupied home point.
+fn thorp_count(game: &Backgammon, color: Color) -> f64 {
+    let count = game.pip_count(color) + 2 * checkers_on_board(game, color) +
+        game.get_board(color, BEARING_OFF_POS - 1) as u32;
+    return (count - home_points_occupied(game, color)) as f64;
+}
+
+// The pip count plus the adjustments for stacking and gaps in the home board.
+fn keith_count(game: &Backgammon, color: Color) -> f64 {
+    return (game.pip_count(color) + keith_adjustment(game, color)) as f64;
+}
+
+// The Keith count plus 1 pip for each checker on board more than the opponent has.
+fn isight_count(game: &Backgammon, color: Color) -> f64 {
+    let extra_checkers = checkers_on_board(game, color)
+        .saturating_sub(checkers_on_board(game, color.opposite()));
+    return keith_count(game, color) + extra_checkers as f64;
+}
+
+fn erf(x: f64) -> f64 {
+    // Abramowitz and Stegun 7.1.26, with an absolute error below 1.5e-7.
+    let t = 1.0 / (1.0 + 0.327_591_1 * x.abs());
+    let poly = t * (0.254_829_592 + t * (-0.284_496_736 + t * (1.421_413_741 +
+        t * (-1.453_152_027 + t * 1.061_405_429))));
+    let y = 1.0 - poly * (-x * x).exp();
+    return if x < 0.0 { -y } else { y };
+}
+
+fn normal_cdf(x: f64) -> f64 {
+    return 0.5 * (1.0 + erf(x / 2f64.sqrt()));
+}
+
+// The probability that the player on roll with the count wins the race against the
+// opponent count. The pips rolled before the race ends have a variance proportional to
+// the number of rolls, which is about the total count divided by the mean roll.
+pub fn win_probability(count: f64, opponent_count: f64) -> f64 {
+    let lead = opponent_count - count + ROLL_MEAN / 2.0;
+    let deviation = ((count + opponent_count) * ROLL_VARIANCE / ROLL_MEAN).sqrt();
+    if deviation == 0.0 {
+        return if 0.0 <= lead { 1.0 } else { 0.0 };
+    }
+    return normal_cdf(lead / deviation);
+}
+
+// Double if the adjusted count of the player on roll exceeds the opponent's by at most the
+// doubling margin, and take if it exceeds it by at least the taking margin.
+fn cube_action(excess: f64, doubling_margin: f64, taking_margin: f64) -> CubeAction {
+    if doubling_margin < excess {
+        return CubeAction::NoDouble;
+    }
+    if excess < taking_margin {
+        return CubeAction::DoublePass;
+    }
+    return CubeAction::DoubleTake;
+}
+
+// Assess the race for the player on roll, or None if the players are not racing.
+// The cube is centered for an initial double, or else owned by the player on roll.
+pub fn assess(game: &Backgammon, color: Color, formula: RaceFormula, centered: bool)
+    -> Option<RaceAssessment> {
+    if !game.is_race() || game.winner().is_some() {
+        return None;
+    }
+    let opponent = color.opposite();
+    let (count, opponent_count, action) = match formula {
+        // Increase the count on roll by a seventh. Double with an excess of at most 4, or
+        // redouble with at most 3, and take with an excess of at least 2.
+        RaceFormula::Keith => {
+            let count = keith_count(game, color);
+            let opponent_count = keith_count(game, opponent);
+            let excess = count * 8.0 / 7.0 - opponent_count;
+            let doubling_margin = if centered { 4.0 } else { 3.0 };
+            (count, opponent_count, cube_action(excess, doubling_margin, 2.0))
+        },
+        // Increase the count on roll by a tenth if it is over 30. Double with an excess of at
+        // most 2, or redouble with at most 1, and take with an excess of at least -2.
+        RaceFormula::Thorp => {
+            let count = thorp_count(game, color);
+            let opponent_count = thorp_count(game, opponent);
+            let adjusted = if 30.0 < count { count * 1.1 } else { count };
+            let doubling_margin = if centered { 2.0 } else { 1.0 };
+            (count, opponent_count, cube_action(adjusted - opponent_count, doubling_margin, -2.0))
+        },
+        // The point of last take is a lead of a tenth of the pip count on roll plus 2 pips.
+        // Double with a lead 4 pips short of it, or redouble with a lead 3 pips short.
+        RaceFormula::Trice => {
+            let count = game.pip_count(color) as f64;
+            let opponent_count = game.pip_count(opponent) as f64;
+            let last_take = count / 10.0 + 2.0;
+            let doubling_margin = if centered { 4.0 } else { 3.0 } - last_take;
+            (count, opponent_count, cube_action(count - opponent_count, doubling_margin,
+                                                -last_take))
+        },
+        // Increase the count on roll by a tenth if it is over 68, or else by 7 pips, and use
+        // the margins of the Keith count.
+        RaceFormula::Isight => {
+            let count = isight_count(game, color);
+            let opponent_count = isight_count(game, opponent);
+            let adjusted = if 68.0 < count { count * 1.1 } else { count + 7.0 };
+            let doubling_margin = if centered { 4.0 } else { 3.0 };
+            (count, opponent_count, cube_action(adjusted - opponent_count, doubling_margin, 2.0))
+        },
+    };
+    return Some(RaceAssessment {
+        formula: formula,
+        count: count,
+        opponent_count: opponent_count,
+        win_probability: win_probability(count, opponent_count),
+        action: action,
+    });
+}
+
+// Assess the race with every formula.
+pub fn assess_all(game: &Backgammon, color: Color, centered: bool) -> Vec<RaceAssessment> {
+    return FORMULAS.iter().filter_map(|&formula| assess(game, color, formula, centered)).collect();
+}
+
+impl RaceFormula {
+    pub fn name(&self) -> &'static str {
+        return match *self {
+            RaceFormula::Keith => "keith",
+            RaceFormula::Thorp => "thorp",
+            RaceFormula::Trice => "trice",
+            RaceFormula::Isight => "isight",
+        };
+    }
+}
+
+impl fmt::Display for CubeAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match *self {
+            CubeAction::NoDouble => "no double",
+            CubeAction::DoubleTake => "double, take",
+            CubeAction::DoublePass => "double, pass",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+impl fmt::Display for RaceAssessment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {:.1} v {:.1}, {:.1}% wins, {}", self.formula.name(), self.count,
+               self.opponent_count, 100.0 * self.win_probability, self.action)
+    }
+}
+
+impl Evaluator for RaceEvaluator {
+    fn evaluate(&self, game: &Backgammon, color: Color) -> f64 {
+        return self.probabilities(game, color).equity();
+    }
+}
+
+impl ProbabilityEvaluator for RaceEvaluator {
+    fn probabilities(&self, game: &Backgammon, color: Color) -> Probabilities {
+        // The player has just moved, so the opponent is on roll.
+        let opponent = color.opposite();
+        let win = match game.winner() {
+            Some(winner) => if winner == color { 1.0 } else { 0.0 },
+            None => 1.0 - win_probability(keith_count(game, opponent), keith_count(game, color)),
+        };
+        return Probabilities { win: win, ..Default::default() };
+    }
+}
@@ -0,0 +1,165 @@
+/*
+ * Rustgammon
+ *
+ * Analysis report
+ * Write an analysed game as a single HTML page with no external files, so that it can be
+ * mailed around and opened in any browser. Each turn shows the board before the move as
+ * an inline SVG diagram, the cube decision, the move played against the best moves with
+ * their equities, and the luck of the roll. The page starts with the error and luck
+ * statistics of both players.
+ *
+ * @author ryutaroikeda94@gmail.com
+ *
+ */
+
+use std::io;
+use std::io::Write;
+
+use analysis::CubeAnalysis;
+use analysis::GameAnalysis;
+use analysis::MoveAnalysis;
+use cube::Cube;
+use luck::LuckAnalysis;
+use notation::format_move_from;
+use packed::PackedPosition;
+use rustgammon::Color;
+use svg::Diagram;
+use svg::SvgRenderer;
+
+const STYLE: &str = "
+body { font-family: sans-serif; max-width: 50em; margin: auto; color: #222; }
+table { border-collapse: collapse; margin: 0.5em 0; }
+td, th { padding: 0.2em 0.8em; text-align: left; }
+th { border-bottom: 1px solid #888; }
+.number { text-align: right; font-family: monospace; }
+.turn { border-top: 1px solid #ccc; padding: 0.5em 0; }
+.played { font-weight: bold; }
+.doubtful { background: #fff6d5; }
+.error { background: #ffe0c0; }
+.blunder { background: #ffc8c8; }
+.luck { color: #555; }
+";
+
+pub fn escape(text: &str) -> String {
+    return text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+        .replace('"', "&quot;");
+}
+
+fn write_summary<W: Write>(out: &mut W, analysis: &GameAnalysis, luck: &LuckAnalysis)
+    -> io::Result<()> {
+    writeln!(out, "<table>")?;
+    writeln!(out, "<tr><th></th><th>Red</th><th>White</th></tr>")?;
+    let row = |out: &mut W, name: &str, values: [String; 2]| {
+        writeln!(out, "<tr><td>{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td></tr>",
+                 name, values[0], values[1])
+    };
+    let colors = [Color::Red, Color::White];
+    let summaries = [analysis.summary(Color::Red), analysis.summary(Color::White)];
+    row(out, "Checker decisions", [0, 1].map(|i| summaries[i].checker_decisions.to_string()))?;
+    row(out, "Cube decisions", [0, 1].map(|i| summaries[i].cube_decisions.to_string()))?;
+    row(out, "Equity lost", [0, 1].map(|i| format!("{:.3}", summaries[i].total_error())))?;
+    row(out, "Doubtful", [0, 1].map(|i| summaries[i].doubtful.to_string()))?;
+    row(out, "Errors", [0, 1].map(|i| summaries[i].errors.to_string()))?;
+    row(out, "Blunders", [0, 1].map(|i| summaries[i].blunders.to_string()))?;
+    row(out, "Performance rating", [0, 1].map(|i| {
+        format!("{:.1} ({})", summaries[i].performance_rating(), summaries[i].rating_name())
+    }))?;
+    row(out, "Luck", [0, 1].map(|i| format!("{:+.3}", luck.luck(colors[i]))))?;
+    if luck.result.is_some() {
+        row(out, "Result", [0, 1].map(|i| format!("{:+}", luck.result(colors[i]).unwrap_or(0.0))))?;
+        row(out, "Luck-adjusted result", [0, 1].map(|i| {
+            format!("{:+.3}", luck.luck_adjusted_result(colors[i]).unwrap_or(0.0))
+        }))?;
+    }
+    writeln!(out, "</table>")?;
+    return Ok(());
+}
+
+fn write_cube<W: Write>(out: &mut W, cube: &CubeAnalysis) -> io::Result<()> {
+    let severity = cube.doubler_severity.max(cube.taker_severity);
+    let class = severity.map(|severity| severity.to_string()).unwrap_or_default();
+    writeln!(out, "<p class=\"{}\">Cube {}: {}</p>", class, cube.cube.value,
+             escape(&cube.to_string()))?;
+    return Ok(());
+}
+
+fn write_move<W: Write>(out: &mut W, analysis: &MoveAnalysis, perspective: Color) -> io::Result<()> {
+    let mut played_game = analysis.position;
+    played_game.do_move(analysis.color, &analysis.played);
+    let played_position = PackedPosition::new(&played_game);
+    let best_equity = analysis.candidates.first().map(|&(_, equity)| equity)
+        .unwrap_or(analysis.played_equity);
+    let mut found = false;
+    writeln!(out, "<table>")?;
+    writeln!(out, "<tr><th></th><th>Move</th><th>Equity</th><th>Difference</th></tr>")?;
+    for (rank, candidate) in analysis.candidates.iter().enumerate() {
+        let mut game = analysis.position;
+        game.do_move(analysis.color, &candidate.0);
+        let is_played = PackedPosition::new(&game) == played_position;
+        found |= is_played;
+        writeln!(out, "<tr{}><td>{}</td><td>{}</td><td class=\"number\">{:+.3}</td>\
+                       <td class=\"number\">{:+.3}</td></tr>",
+                 if is_played { " class=\"played\"" } else { "" }, rank + 1,
+                 escape(&format_move_from(perspective, &analysis.position, analysis.color,
+                                          &candidate.0)),
+                 candidate.1, candidate.1 - best_equity)?;
+    }
+    if !found {
+        writeln!(out, "<tr class=\"played\"><td></td><td>{}</td><td class=\"number\">{:+.3}</td>\
+                       <td class=\"number\">{:+.3}</td></tr>",
+                 escape(&format_move_from(perspective, &analysis.position, analysis.color,
+                                         &analysis.played)),
+                 analysis.played_equity, analysis.played_equity - best_equity)?;
+    }
+    writeln!(out, "</table>")?;
+    return Ok(());
+}
+
+// Write the page for the analysis of a game and the luck of its rolls. Each turn is shown
+// from the perspective if one is given, or else from the side of the player on roll.
+pub fn write_report<W: Write>(out: &mut W, title: &str, analysis: &GameAnalysis, luck: &LuckAnalysis,
+                              perspective: Option<Color>) -> io::Result<()> {
+    writeln!(out, "<!DOCTYPE html>")?;
+    writeln!(out, "<html><head><meta charset=\"utf-8\"><title>{}</title>", escape(title))?;
+    writeln!(out, "<style>{}</style></head><body>", STYLE)?;
+    writeln!(out, "<h1>{}</h1>", escape(title))?;
+    write_summary(out, analysis, luck)?;
+    let renderer = |color: Color| SvgRenderer { perspective: perspective.unwrap_or(color),
+                                                ..Default::default() };
+    let mut cube: Cube = Default::default();
+    let mut cube_decisions = analysis.cube_decisions.iter().peekable();
+    for move_analysis in &analysis.moves {
+        let class = move_analysis.severity.map(|severity| severity.to_string()).unwrap_or_default();
+        writeln!(out, "<div class=\"turn\">")?;
+        writeln!(out, "<h3 class=\"{}\">Turn {}: {} rolls {}-{}{}</h3>", class,
+                 move_analysis.turn + 1, move_analysis.color, move_analysis.roll.0,
+                 move_analysis.roll.1, move_analysis.severity
+                     .map(|severity| format!(" ({})", severity)).unwrap_or_default())?;
+        renderer(move_analysis.color).write(out, &Diagram {
+            position: move_analysis.position,
+            dice: Some((move_analysis.color, move_analysis.roll)),
+            cube: Some(cube),
+        })?;
+        while let Some(cube_analysis) = cube_decisions.next_if(|cube| cube.turn <= move_analysis.turn) {
+            write_cube(out, cube_analysis)?;
+            if cube_analysis.taken == Some(true) {
+                cube = Cube { value: 2 * cube.value, owner: Some(cube_analysis.color.opposite()) };
+            }
+        }
+        write_move(out, move_analysis, perspective.unwrap_or(move_analysis.color))?;
+        if let Some(roll_luck) = luck.rolls.iter().find(|roll| roll.turn == move_analysis.turn) {
+            writeln!(out, "<p class=\"luck\">Luck {:+.3}{}</p>", roll_luck.luck,
+                     roll_luck.description().map(|text| format!(" ({})", text)).unwrap_or_default())?;
+        }
+        writeln!(out, "</div>")?;
+    }
+    for cube_analysis in cube_decisions {
+        writeln!(out, "<div class=\"turn\">")?;
+        renderer(cube_analysis.color).write(out, &Diagram { position: cube_analysis.position, dice: None,
+                                                           cube: Some(cube) })?;
+        write_cube(out, cube_analysis)?;
+        writeln!(out, "</div>")?;
+    }
+    writeln!(out, "</body></html>")?;
+    return Ok(());
+}
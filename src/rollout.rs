@@ -0,0 +1,251 @@
+/*
+ * Rustgammon
+ *
+ * Rollouts
+ * Estimate the equity of a position by playing it out many times with a fast player
+ * that plays the best move for the evaluator, optionally stopping after a number of
+ * plies and taking the evaluator's probabilities.
+ * Every game has its own dice derived from the seed, so the result does not depend on
+ * the number of threads. Candidates share the dice of each game and the first rolls
+ * cycle through all 36 rolls, which removes much of the luck from comparisons.
+ *
+ * @author ryutaroikeda94@gmail.com
+ *
+ */
+
+use std::thread;
+
+use evaluator::Probabilities;
+use evaluator::ProbabilityEvaluator;
+use fairdice::FairDice;
+use packed::MoveBuffer;
+use packed::PackedPosition;
+use rustgammon::Backgammon;
+use rustgammon::Color;
+use rustgammon::Dice;
+use rustgammon::DiceRoll;
+use rustgammon::Move;
+use sha256::Digest;
+use sha256::sha256;
+
+#[derive(Clone, Debug)]
+pub struct RolloutConfig {
+    pub games: usize,
+    // Stop after this many plies and use the evaluator's probabilities.
+    pub truncation: Option<usize>,
+    pub threads: usize,
+    pub seed: Digest,
+    // Play every candidate with the same dice.
+    pub duplicate_dice: bool,
+    // Cycle the first roll of the games through all 36 rolls.
+    pub quasi_random: bool,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct RolloutResult {
+    pub games: usize,
+    // The rates of the results, averaged with the evaluator's probabilities in
+    // truncated games.
+    pub probabilities: Probabilities,
+    pub equity: f64,
+    pub standard_error: f64,
+}
+
+impl Default for RolloutConfig {
+    fn default() -> RolloutConfig {
+        return RolloutConfig {
+            games: 1296,
+            truncation: None,
+            threads: thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+            seed: [0; 32],
+            duplicate_dice: true,
+            quasi_random: true,
+        };
+    }
+}
+
+// Sums over the games played.
+#[derive(Copy, Clone, Default)]
+struct Totals {
+    games: usize,
+    probabilities: Probabilities,
+    equity: f64,
+    equity_squared: f64,
+}
+
+impl Totals {
+    fn add(&mut self, result: &Probabilities) {
+        self.games += 1;
+        self.probabilities.win += result.win;
+        self.probabilities.win_gammon += result.win_gammon;
+        self.probabilities.win_backgammon += result.win_backgammon;
+        self.probabilities.lose_gammon += result.lose_gammon;
+        self.probabilities.lose_backgammon += result.lose_backgammon;
+        let equity = result.equity();
+        self.equity += equity;
+        self.equity_squared += equity * equity;
+    }
+
+    fn merge(&mut self, other: &Totals) {
+        self.games += other.games;
+        self.probabilities.win += other.probabilities.win;
+        self.probabilities.win_gammon += other.probabilities.win_gammon;
+        self.probabilities.win_backgammon += other.probabilities.win_backgammon;
+        self.probabilities.lose_gammon += other.probabilities.lose_gammon;
+        self.probabilities.lose_backgammon += other.probabilities.lose_backgammon;
+        self.equity += other.equity;
+        self.equity_squared += other.equity_squared;
+    }
+
+    fn result(&self) -> RolloutResult {
+        if self.games == 0 {
+            return Default::default();
+        }
+        let n = self.games as f64;
+        let mean = self.equity / n;
+        let variance = if 1 < self.games {
+            ((self.equity_squared - n * mean * mean) / (n - 1.0)).max(0.0)
+        } else {
+            0.0
+        };
+        return RolloutResult {
+            games: self.games,
+            probabilities: Probabilities {
+                win: self.probabilities.win / n,
+                win_gammon: self.probabilities.win_gammon / n,
+                win_backgammon: self.probabilities.win_backgammon / n,
+                lose_gammon: self.probabilities.lose_gammon / n,
+                lose_backgammon: self.probabilities.lose_backgammon / n,
+            },
+            equity: mean,
+            standard_error: (variance / n).sqrt(),
+        };
+    }
+}
+
+// Dice with a fixed first roll.
+struct FirstRoll {
+    first: Option<DiceRoll>,
+    dice: FairDice,
+}
+
+impl Dice for FirstRoll {
+    fn roll(&mut self) -> DiceRoll {
+        return match self.first.take() {
+            Some(roll) => roll,
+            None => self.dice.roll(),
+        }
+    }
+}
+
+// The dice of a game of a candidate.
+fn game_dice(config: &RolloutConfig, candidate: usize, game_idx: usize) -> FirstRoll {
+    let salt = if config.duplicate_dice { 0 } else { candidate as u64 };
+    let mut data = Vec::with_capacity(48);
+    data.extend_from_slice(&config.seed);
+    for value in &[salt, game_idx as u64] {
+        for i in 0..8 {
+            data.push((value >> (56 - 8 * i)) as u8);
+        }
+    }
+    let first = if config.quasi_random {
+        let roll_idx = game_idx % 36;
+        Some((roll_idx / 6 + 1, roll_idx % 6 + 1))
+    } else {
+        None
+    };
+    return FirstRoll { first: first, dice: FairDice::new(sha256(&data)) };
+}
+
+// The result of a finished game for the player.
+pub fn game_result(game: &Backgammon, winner: Color, color: Color) -> Probabilities {
+    let points = game.points_won(winner);
+    let gammon = if 2 <= points { 1.0 } else { 0.0 };
+    let backgammon = if 3 <= points { 1.0 } else { 0.0 };
+    let result = Probabilities {
+        win: 1.0,
+        win_gammon: gammon,
+        win_backgammon: backgammon,
+        lose_gammon: 0.0,
+        lose_backgammon: 0.0,
+    };
+    return if winner == color { result } else { result.flip() };
+}
+
+// Play out the game after the player has moved, with the opponent to roll.
+fn play_out<E: ProbabilityEvaluator, D: Dice>(start: &Backgammon, color: Color, evaluator: &E,
+                                              truncation: Option<usize>, dice: &mut D,
+                                              buffer: &mut MoveBuffer) -> Probabilities {
+    let mut position = PackedPosition::new(start);
+    let mut to_move = color.opposite();
+    let mut plies = 0;
+    loop {
+        if truncation.is_some_and(|max_plies| max_plies <= plies) {
+            let moved = to_move.opposite();
+            let probabilities = evaluator.probabilities(&position.to_backgammon(), moved);
+            return if moved == color { probabilities } else { probabilities.flip() };
+        }
+        position.generate_moves(to_move, dice.roll(), buffer);
+        let mut best = None;
+        let mut best_score = f64::NEG_INFINITY;
+        for mov in buffer.moves() {
+            let score = evaluator.evaluate(&mov.position.to_backgammon(), to_move);
+            if best.is_none() || best_score < score {
+                best_score = score;
+                best = Some(mov.position);
+            }
+        }
+        if let Some(next) = best {
+            position = next;
+            let game = position.to_backgammon();
+            if let Some(winner) = game.winner() {
+                return game_result(&game, winner, color);
+            }
+        }
+        to_move = to_move.opposite();
+        plies += 1;
+    }
+}
+
+fn rollout_candidate<E: ProbabilityEvaluator + Sync>(game: &Backgammon, color: Color,
+                                                     evaluator: &E, config: &RolloutConfig,
+                                                     candidate: usize) -> RolloutResult {
+    let threads = ::std::cmp::max(config.threads, 1);
+    let mut totals: Totals = Default::default();
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads).map(|thread_idx| {
+            scope.spawn(move || {
+                let mut buffer = MoveBuffer::new();
+                let mut totals: Totals = Default::default();
+                for game_idx in (thread_idx..config.games).step_by(threads) {
+                    let mut dice = game_dice(config, candidate, game_idx);
+                    let result = play_out(game, color, evaluator, config.truncation, &mut dice,
+                                          &mut buffer);
+                    totals.add(&result);
+                }
+                totals
+            })
+        }).collect();
+        for handle in handles {
+            totals.merge(&handle.join().expect("rollout thread panicked"));
+        }
+    });
+    return totals.result();
+}
+
+// Roll out the position for the player who has just moved.
+pub fn rollout<E: ProbabilityEvaluator + Sync>(game: &Backgammon, color: Color, evaluator: &E,
+                                               config: &RolloutConfig) -> RolloutResult {
+    return rollout_candidate(game, color, evaluator, config, 0);
+}
+
+// Roll out the position after each of the player's candidate moves.
+pub fn rollout_moves<E: ProbabilityEvaluator + Sync>(game: &Backgammon, color: Color,
+                                                     moves: &[Move], evaluator: &E,
+                                                     config: &RolloutConfig) -> Vec<RolloutResult> {
+    return moves.iter().enumerate().map(|(candidate, mov)| {
+        let mut next = *game;
+        next.do_move(color, mov);
+        rollout_candidate(&next, color, evaluator, config, candidate)
+    }).collect();
+}
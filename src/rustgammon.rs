@@ -13,10 +13,17 @@ extern crate rand;
 
 use std;
 use std::io;
-use std::io::Write;
 use std::fmt;
 use rand::Rng;
 
+use gamerecord::GameRecord;
+use gamerecord::Turn;
+use notation::Orientation;
+use notation::format_move_from;
+use textboard;
+use textboard::BoardStyle;
+use textboard::BoardView;
+
 pub const BOARD_SIZE: usize = 26;
 pub const BAR_POS: usize = 0;
 pub const BEARING_OFF_POS: usize = BOARD_SIZE - 1;
@@ -32,7 +39,7 @@ pub struct Board {
     board: InternalBoard,
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum Color {
     Red,
     White,
@@ -43,13 +50,13 @@ pub type DiceRoll = (Die, Die);
 
 // The `from` in Submove is relative to the player.
 // 0 is the bar and 25 the bearing-off point for either player.
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub struct Submove {
     pub from: Position,
     pub die: Die,
 }
 
-#[derive(PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Move {
     pub submoves: Vec<Submove>,
 }
@@ -64,6 +71,42 @@ pub trait Player {
     fn get_color(&self) -> Color;
 
     fn make_move(&self, game: &Backgammon, roll: DiceRoll) -> Move;
+
+    // The perspective and orientation the board is shown in before the player's turn.
+    fn view(&self) -> (Color, Orientation) {
+        return (Color::Red, Orientation::HomeRight);
+    }
+}
+
+// A player chosen at run time.
+impl<P: Player + ?Sized> Player for Box<P> {
+    fn get_color(&self) -> Color {
+        return (**self).get_color();
+    }
+
+    fn make_move(&self, game: &Backgammon, roll: DiceRoll) -> Move {
+        return (**self).make_move(game, roll);
+    }
+
+    fn view(&self) -> (Color, Orientation) {
+        return (**self).view();
+    }
+}
+
+// A source of dice rolls for a game.
+pub trait Dice {
+    fn roll(&mut self) -> DiceRoll;
+}
+
+// Dice rolled with the thread local random number generator.
+pub struct RandomDice;
+
+impl Dice for RandomDice {
+    fn roll(&mut self) -> DiceRoll {
+        let a = rand::thread_rng().gen_range(1, 7);
+        let b = rand::thread_rng().gen_range(1, 7);
+        return (a, b);
+    }
 }
 
 impl fmt::Display for Color {
@@ -75,24 +118,38 @@ impl fmt::Display for Color {
     }
 }
 
+impl std::str::FromStr for Color {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Color, String> {
+        return match s {
+            "red" => Ok(Color::Red),
+            "white" => Ok(Color::White),
+            _ => Err(format!("unknown color {}", s)),
+        }
+    }
+}
+
 impl fmt::Display for Submove {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "from: {}, die: {}", self.from, self.die)
     }
 }
 
-impl Move {
-    pub fn print(&self) {
-        for submove in &self.submoves {
-            print!("{}, ", submove);
-        }
-        print!("\n");
-        match io::stdout().flush() {
-            Ok(_) => (),
-            Err(e) => println!("error: {}", e),
-        }
+// The submoves in the order they are stored, which is the reverse of the order of play.
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let submoves: Vec<String> = self.submoves.iter().map(|submove| submove.to_string()).collect();
+        write!(f, "{}", submoves.join(", "))
     }
+}
 
+// The compact grid of the position.
+impl fmt::Display for Backgammon {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return textboard::write_board(f, self, BoardStyle::Compact, Color::Red,
+                                      Orientation::HomeRight);
+    }
 }
 
 impl Board {
@@ -112,7 +169,7 @@ const INITIAL_BOARD: InternalBoard = [
 ];
 
 impl Color {
-    fn opposite(self) -> Color {
+    pub fn opposite(self) -> Color {
         return match self {
             Color::Red => Color::White,
             Color::White => Color::Red,
@@ -121,7 +178,7 @@ impl Color {
 }
 
 impl Submove {
-    fn destination(&self) -> Position {
+    pub fn destination(&self) -> Position {
         if self.from + self.die >= BOARD_SIZE {
             return BEARING_OFF_POS;
         }
@@ -166,6 +223,15 @@ impl Backgammon {
         return false;
     }
 
+    // The number of pips the player must move to bear off every checker.
+    pub fn pip_count(&self, color: Color) -> u32 {
+        let mut pips = 0;
+        for pos in 0..BEARING_OFF_POS {
+            pips += self.get_board(color, pos) as u32 * (BEARING_OFF_POS - pos) as u32;
+        }
+        return pips;
+    }
+
     pub fn is_all_home(&self, color: Color) -> bool {
         let end_of_outer_board = 19;
         for pos in 0..end_of_outer_board {
@@ -176,6 +242,18 @@ impl Backgammon {
         return true;
     }
 
+    // Whether contact is broken, so that no checker can hit or block another.
+    // This is when the rearmost checkers of the players have passed each other.
+    pub fn is_race(&self) -> bool {
+        let rearmost = |color: Color| {
+            (BAR_POS..BEARING_OFF_POS).find(|&pos| 0 < self.get_board(color, pos))
+        };
+        return match (rearmost(Color::Red), rearmost(Color::White)) {
+            (Some(red), Some(white)) => BEARING_OFF_POS <= red + white,
+            _ => true,
+        };
+    }
+
     pub fn can_do_submove(&self, color: Color, submove: &Submove) -> bool {
         // Make sure there is a checker to move.
         if 0 == self.get_board(color, submove.from) {
@@ -198,10 +276,12 @@ impl Backgammon {
             }
             // A die may not be used to bear off a lower numbered point unless there are no 
             // checkers on any higher points.
-            let start_pos = BEARING_OFF_POS - submove.die;
-            for pos in start_pos..submove.from {
-                if 0 < self.get_board(color, pos) {
-                    return false;
+            if submove.from + submove.die > BEARING_OFF_POS {
+                let start_of_home_board = 19;
+                for pos in start_of_home_board..submove.from {
+                    if 0 < self.get_board(color, pos) {
+                        return false;
+                    }
                 }
             }
         }
@@ -279,7 +359,10 @@ impl Backgammon {
         let is_double = roll.0 == roll.1;
         if is_double {
             let dice = vec!(roll.0, roll.0, roll.0, roll.0);
-            return self.list_moves_with_ordered_dice_r(color, &dice);
+            let moves = self.list_moves_with_ordered_dice_r(color, &dice);
+            // Play as many dice as possible.
+            let max_len = moves.iter().map(|mov| mov.submoves.len()).max().unwrap_or(0);
+            return moves.into_iter().filter(|mov| mov.submoves.len() == max_len).collect();
         }
         // We didn't roll a double.
         let high = std::cmp::max(roll.0, roll.1);
@@ -316,8 +399,12 @@ impl Backgammon {
         }
     }
 
+    // A turn without a legal move is passed with the empty move.
     pub fn can_do_move(&self, color: Color, roll: DiceRoll, mov: &Move) -> bool {
         let legal_moves = self.list_moves(color, roll);
+        if legal_moves.is_empty() {
+            return mov.submoves.is_empty();
+        }
         for legal_move in &legal_moves {
             if mov == legal_move {
                 return true;
@@ -326,6 +413,21 @@ impl Backgammon {
         return false;
     }
 
+    // The player's move, or the first legal move if the player's move is illegal, so that a
+    // faulty computer player cannot stall a game that has no one to ask again.
+    pub fn legal_move_of<T: Player>(&self, player: &T, roll: DiceRoll) -> Move {
+        let color = player.get_color();
+        let mov = player.make_move(self, roll);
+        if self.can_do_move(color, roll, &mov) {
+            return mov;
+        }
+        // The empty move is legal when there is no other, so there is one to list.
+        let legal = self.list_moves(color, roll).remove(0);
+        debug!("{} played an illegal move with {}-{}, playing {} instead",
+               color, roll.0, roll.1, format_move_from(color, self, color, &legal));
+        return legal;
+    }
+
     pub fn do_move(&mut self, color: Color, mov: &Move) {
         let mut move_idx = mov.submoves.len();
         while move_idx > 0 {
@@ -345,123 +447,124 @@ impl Backgammon {
         return false;
     }
 
-    fn is_game_over(&self) -> bool {
-        let red_checkers = self.get_board(Color::Red, BEARING_OFF_POS);
-        if 15 <= red_checkers {
-            return true;
+    // The winner of a finished game.
+    pub fn winner(&self) -> Option<Color> {
+        if 15 <= self.get_board(Color::Red, BEARING_OFF_POS) {
+            return Some(Color::Red);
         }
-        let white_checkers = self.get_board(Color::White, BEARING_OFF_POS);
-        if 15 <= white_checkers {
-            return true;
+        if 15 <= self.get_board(Color::White, BEARING_OFF_POS) {
+            return Some(Color::White);
         }
-        return false;
+        return None;
     }
 
-    pub fn print(&self) {
-        for pos in 13..19 {
-            print!(" {}", pos);
-        }
-        print!("   ");
-        for pos in 19..25 {
-            print!(" {}", pos);
-        }
-        print!("\n");
-
-        let print_checker = |pos: Position| {
-            let red_checker = self.get_board(Color::Red, pos);
-            if 0 < red_checker {
-                print!(" R{}", red_checker);
-                return;
-            }
-            let white_pos = self.get_opposite_pos(pos);
-            let white_checker = self.get_board(Color::White, white_pos);
-            if 0 < white_checker {
-                print!(" W{}", white_checker);
-                return;
-            }
-            print!(" ..");
-        };
-
-        let mut pos = 13;
-        let end_pos = 1;
-        while pos > end_pos {
-            pos -= 1;
-            if pos == 6 {
-                print!(" ||");
-            }
-            print_checker(pos);
-        }
-        print!("\tRed bar: {}\tRed off: {}",
-               self.get_board(Color::Red, 0),
-               self.get_board(Color::Red, BEARING_OFF_POS));
-        print!("\n");
-
-        let mut pos = 12;
-        let end_pos = 24;
-        while pos < end_pos {
-            pos += 1;
-            if pos == 19 {
-                print!(" ||");
+    // The points won without the cube: 2 for a gammon, where the loser has borne off no
+    // checkers, and 3 for a backgammon, where the loser also has a checker on the bar or
+    // in the winner's home board.
+    pub fn points_won(&self, winner: Color) -> u32 {
+        let loser = winner.opposite();
+        if 0 < self.get_board(loser, BEARING_OFF_POS) {
+            return 1;
+        }
+        for pos in BAR_POS..7 {
+            if 0 < self.get_board(loser, pos) {
+                return 3;
             }
-            print_checker(pos);
         }
-        print!("\tWhite bar: {}\tWhite off: {}",
-               self.get_board(Color::White, 0),
-               self.get_board(Color::White, BEARING_OFF_POS));
-        print!("\n");
+        return 2;
+    }
 
-        for pos in 0..6 {
-            print!(" {number:>width$}", number=12-pos, width=2);
-        }
-        print!("   ");
-        for pos in 6..12 {
-            print!(" {number:>width$}", number=12-pos, width=2);
-        }
-        print!("\n");
+    pub fn view(&self, style: BoardStyle) -> BoardView<'_> {
+        return self.view_from(style, Color::Red, Orientation::HomeRight);
     }
 
-    fn roll_dice(&self) -> DiceRoll {
-        let a = rand::thread_rng().gen_range(1, 7);
-        let b = rand::thread_rng().gen_range(1, 7);
-        return (a, b);
+    // The board seen from the perspective of a player.
+    pub fn view_from(&self, style: BoardStyle, perspective: Color, orientation: Orientation)
+        -> BoardView<'_> {
+        return BoardView { game: self, style: style, perspective: perspective, orientation: orientation };
+    }
+
+    // Write the board in the style.
+    pub fn write<W: io::Write>(&self, out: &mut W, style: BoardStyle) -> io::Result<()> {
+        return write!(out, "{}", self.view(style));
     }
 
     pub fn run<S: Player, T: Player>(&mut self, first: &S, second: &T) {
+        self.run_with_dice(first, second, &mut RandomDice);
+    }
+
+    // Play a game with the given dice and return the record of the game.
+    pub fn run_with_dice<S: Player, T: Player, D: Dice>(&mut self, first: &S, second: &T,
+                                                        dice: &mut D) -> GameRecord {
+        return self.run_game(first, second, dice, true);
+    }
+
+    // Play a game without printing, for computer players.
+    pub fn play_game<S: Player, T: Player, D: Dice>(&mut self, first: &S, second: &T,
+                                                    dice: &mut D) -> GameRecord {
+        return self.run_game(first, second, dice, false);
+    }
+
+    fn run_game<S: Player, T: Player, D: Dice>(&mut self, first: &S, second: &T, dice: &mut D,
+                                               verbose: bool) -> GameRecord {
+        let mut record = GameRecord::new(self);
         loop {
-            self.print();
-            if self.is_game_over() {
-                println!("player {} won", second.get_color());
-                break;
+            if verbose {
+                self.print_for(first);
             }
-            println!("player {} to play", first.get_color());
-            let roll = self.roll_dice();
-            if self.list_moves(first.get_color(), roll).is_empty() {
-                println!("rolled {}-{}, no legal moves", roll.0, roll.1);
-            } else { 
-                println!("rolled {}-{}", roll.0, roll.1);
-                loop {
-                    if self.play_move(roll, first) {
-                        break;
-                    }
+            if self.winner().is_some() {
+                if verbose {
+                    println!("player {} won", second.get_color());
                 }
+                record.winner = Some(second.get_color());
+                break;
             }
+            self.play_turn(first, dice, &mut record, verbose);
 
-            self.print();
-            if self.is_game_over() {
-                println!("player {} won", first.get_color());
+            if verbose {
+                self.print_for(second);
+            }
+            if self.winner().is_some() {
+                if verbose {
+                    println!("player {} won", first.get_color());
+                }
+                record.winner = Some(first.get_color());
                 break;
             }
-            println!("player {} to play", second.get_color());
-            let roll = self.roll_dice();
-            if self.list_moves(second.get_color(), roll).is_empty() {
+            self.play_turn(second, dice, &mut record, verbose);
+        }
+        return record;
+    }
+
+    // Print the board as the player about to move sees it.
+    fn print_for<T: Player>(&self, player: &T) {
+        let (perspective, orientation) = player.view();
+        print!("{}", self.view_from(BoardStyle::Compact, perspective, orientation));
+    }
+
+    fn play_turn<T: Player, D: Dice>(&mut self, player: &T, dice: &mut D,
+                                     record: &mut GameRecord, verbose: bool) {
+        let color = player.get_color();
+        if verbose {
+            println!("player {} to play", color);
+        }
+        let roll = dice.roll();
+        if self.list_moves(color, roll).is_empty() {
+            if verbose {
                 println!("rolled {}-{}, no legal moves", roll.0, roll.1);
-            } else {
-                println!("rolled {}-{}", roll.0, roll.1);
-                loop {
-                    if self.play_move(roll, second) {
-                        break;
-                    }
-                }
+            }
+            record.turns.push(Turn { color: color, roll: roll, mov: Move { submoves: Vec::new() } });
+            return;
+        }
+        if verbose {
+            println!("rolled {}-{}", roll.0, roll.1);
+        }
+        loop {
+            let player_move = player.make_move(self, roll);
+            if self.can_do_move(color, roll, &player_move) {
+                self.do_move(color, &player_move);
+                record.turns.push(Turn { color: color, roll: roll, mov: player_move });
+                return;
             }
         }
     }
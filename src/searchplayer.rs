@@ -0,0 +1,129 @@
+/*
+ * Rustgammon
+ *
+ * Search player
+ * Look ahead a number of plies over all 21 rolls with expectiminimax, scoring the
+ * positions at the horizon with an evaluator.
+ * Only the moves with the best static evaluation are searched, which keeps a 2-ply
+ * decision on the opening position within a second.
+ *
+ * @author ryutaroikeda94@gmail.com
+ *
+ */
+
+use evaluator::Evaluator;
+use evaluator::distinct_moves;
+use perft::distinct_rolls;
+use rustgammon::Backgammon;
+use rustgammon::Color;
+use rustgammon::DiceRoll;
+use rustgammon::Move;
+use rustgammon::Player;
+
+pub struct SearchPlayer<E: Evaluator> {
+    pub color: Color,
+    pub evaluator: E,
+    // The number of rolls to look ahead after the move. 0 plays the best static move.
+    pub plies: usize,
+    // The number of moves searched at the root.
+    pub candidates: usize,
+    // The number of moves searched at deeper plies.
+    pub inner_candidates: usize,
+}
+
+impl<E: Evaluator> SearchPlayer<E> {
+    pub fn new(color: Color, evaluator: E, plies: usize) -> SearchPlayer<E> {
+        return SearchPlayer {
+            color: color,
+            evaluator: evaluator,
+            plies: plies,
+            candidates: 8,
+            inner_candidates: 2,
+        };
+    }
+
+    // Keep the positions with the best static evaluation for the player who moved.
+    fn prune(&self, positions: &mut Vec<(f64, Backgammon)>, color: Color, keep: usize) {
+        for entry in positions.iter_mut() {
+            entry.0 = self.evaluator.evaluate(&entry.1, color);
+        }
+        positions.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(::std::cmp::Ordering::Equal));
+        positions.truncate(keep);
+    }
+
+    // The score for us of a position where the player has just moved.
+    fn score(&self, game: &Backgammon) -> f64 {
+        return match game.winner() {
+            Some(winner) => self.evaluator.evaluate_finished(game, winner, self.color),
+            None => self.evaluator.evaluate(game, self.color),
+        }
+    }
+
+    // The expected score for us with the player to move about to roll.
+    fn expected(&self, game: &Backgammon, to_move: Color, plies: usize) -> f64 {
+        if plies == 0 || game.winner().is_some() {
+            return self.score(game);
+        }
+        let mut total = 0.0;
+        for (roll, ways) in distinct_rolls() {
+            total += ways as f64 * self.best_reply(game, to_move, roll, plies);
+        }
+        return total / 36.0;
+    }
+
+    // The score for us after the player to move plays its best move with the roll.
+    fn best_reply(&self, game: &Backgammon, to_move: Color, roll: DiceRoll, plies: usize) -> f64 {
+        let mut positions: Vec<(f64, Backgammon)> = distinct_moves(game, to_move, roll)
+            .into_iter().map(|(_, next)| (0.0, next)).collect();
+        if positions.is_empty() {
+            return self.expected(game, to_move.opposite(), plies - 1);
+        }
+        let is_us = to_move == self.color;
+        if plies == 1 {
+            // The horizon: the player picks the move that is best for it statically.
+            self.prune(&mut positions, to_move, 1);
+            return self.score(&positions[0].1);
+        }
+        self.prune(&mut positions, to_move, self.inner_candidates);
+        let mut best = if is_us { f64::NEG_INFINITY } else { f64::INFINITY };
+        for (_, next) in &positions {
+            let score = self.expected(next, to_move.opposite(), plies - 1);
+            if (is_us && best < score) || (!is_us && score < best) {
+                best = score;
+            }
+        }
+        return best;
+    }
+}
+
+impl<E: Evaluator> Player for SearchPlayer<E> {
+    fn get_color(&self) -> Color {
+        return self.color;
+    }
+
+    fn make_move(&self, game: &Backgammon, roll: DiceRoll) -> Move {
+        let mut candidates: Vec<(f64, Move, Backgammon)> = distinct_moves(game, self.color, roll)
+            .into_iter()
+            .map(|(mov, next)| (self.evaluator.evaluate(&next, self.color), mov, next))
+            .collect();
+        candidates.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(::std::cmp::Ordering::Equal));
+        if self.plies == 0 || candidates.len() <= 1 {
+            return match candidates.into_iter().next() {
+                Some((_, mov, _)) => mov,
+                None => Move { submoves: Vec::new() },
+            }
+        }
+        candidates.truncate(self.candidates);
+        let mut best_move = Move { submoves: Vec::new() };
+        let mut best_score = f64::NEG_INFINITY;
+        for (_, mov, next) in candidates {
+            let score = self.expected(&next, self.color.opposite(), self.plies);
+            debug!("search {} plies: {:?} scores {}", self.plies, mov.submoves, score);
+            if best_score < score {
+                best_score = score;
+                best_move = mov;
+            }
+        }
+        return best_move;
+    }
+}
@@ -0,0 +1,380 @@
+/*
+ * Rustgammon
+ *
+ * SVG board diagrams
+ * Draw a position as an SVG image seen from the perspective of a player, with the points
+ * numbered for that player, stacks of up to five
+ * checkers with the count written on the fifth, the checkers on the bar, the trays of
+ * borne off checkers, and the pip counts of both players. The dice of the player on roll
+ * and the cube may be added.
+ * The diagram is laid out on a grid of squares as wide as a point: a column for the cube,
+ * six points, the bar, six points and the tray, and from top to bottom the other player's
+ * pip count, the point numbers, five checkers, a gap for the dice, five checkers, the
+ * point numbers and the pip count of the player seen from. Its home board is at the
+ * bottom, on the right unless the orientation puts it on the left. Unless told otherwise
+ * the board is seen from Red.
+ *
+ * @author ryutaroikeda94@gmail.com
+ *
+ */
+
+use std::io;
+use std::io::Write;
+
+use cube::Cube;
+use notation::Orientation;
+use notation::point_position;
+use rustgammon::Backgammon;
+use rustgammon::BAR_POS;
+use rustgammon::BEARING_OFF_POS;
+use rustgammon::Color;
+use rustgammon::DiceRoll;
+use rustgammon::Die;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Theme {
+    pub name: &'static str,
+    pub background: &'static str,
+    pub board: &'static str,
+    pub light_point: &'static str,
+    pub dark_point: &'static str,
+    pub bar: &'static str,
+    pub red: &'static str,
+    pub white: &'static str,
+    pub outline: &'static str,
+    pub text: &'static str,
+    pub die: &'static str,
+    pub die_pip: &'static str,
+}
+
+pub const CLASSIC: Theme = Theme {
+    name: "classic",
+    background: "#ffffff",
+    board: "#e8d8b0",
+    light_point: "#f4ecd8",
+    dark_point: "#a05030",
+    bar: "#6b4226",
+    red: "#c03030",
+    white: "#fafafa",
+    outline: "#333333",
+    text: "#222222",
+    die: "#fafafa",
+    die_pip: "#222222",
+};
+
+pub const FELT: Theme = Theme {
+    name: "felt",
+    background: "#1e3b2a",
+    board: "#2e6b45",
+    light_point: "#d9c9a3",
+    dark_point: "#8b2e2e",
+    bar: "#173022",
+    red: "#d04040",
+    white: "#f0f0e8",
+    outline: "#101010",
+    text: "#f0f0e8",
+    die: "#f0f0e8",
+    die_pip: "#101010",
+};
+
+// Black and white for printing.
+pub const PRINT: Theme = Theme {
+    name: "print",
+    background: "#ffffff",
+    board: "#ffffff",
+    light_point: "#ffffff",
+    dark_point: "#bbbbbb",
+    bar: "#dddddd",
+    red: "#333333",
+    white: "#ffffff",
+    outline: "#000000",
+    text: "#000000",
+    die: "#ffffff",
+    die_pip: "#000000",
+};
+
+pub const THEMES: [Theme; 3] = [CLASSIC, FELT, PRINT];
+
+pub const DEFAULT_POINT_WIDTH: u32 = 24;
+
+// The number of grid squares across and down the diagram.
+const COLUMNS: u32 = 15;
+const ROWS: u32 = 15;
+const BAR_COLUMN: u32 = 7;
+const TRAY_COLUMN: u32 = 14;
+const CUBE_COLUMN: u32 = 0;
+// The rows of the top of the checkers in the top half, of the gap between the halves and
+// of the bottom of the checkers in the bottom half.
+const TOP_ROW: u32 = 2;
+const MIDDLE_ROW: u32 = 7;
+const BOTTOM_ROW: u32 = 13;
+const MAX_STACK: i8 = 5;
+
+// The position to draw with the optional dice and cube.
+#[derive(Clone)]
+pub struct Diagram {
+    pub position: Backgammon,
+    // The player on roll and its roll.
+    pub dice: Option<(Color, DiceRoll)>,
+    pub cube: Option<Cube>,
+}
+
+pub struct SvgRenderer {
+    pub perspective: Color,
+    pub orientation: Orientation,
+    pub theme: Theme,
+    pub point_width: u32,
+    pub point_numbers: bool,
+    pub pip_counts: bool,
+}
+
+impl Theme {
+    pub fn by_name(name: &str) -> Option<Theme> {
+        return THEMES.iter().find(|theme| theme.name == name).cloned();
+    }
+
+    fn checker(&self, color: Color) -> &'static str {
+        return match color {
+            Color::Red => self.red,
+            Color::White => self.white,
+        };
+    }
+}
+
+impl Diagram {
+    pub fn new(game: &Backgammon) -> Diagram {
+        return Diagram { position: *game, dice: None, cube: None };
+    }
+}
+
+impl Default for SvgRenderer {
+    fn default() -> SvgRenderer {
+        return SvgRenderer {
+            perspective: Color::Red,
+            orientation: Orientation::HomeRight,
+            theme: CLASSIC,
+            point_width: DEFAULT_POINT_WIDTH,
+            point_numbers: true,
+            pip_counts: true,
+        };
+    }
+}
+
+// The column of a point with the home board on the right.
+fn point_column(point: usize) -> u32 {
+    let index = if 13 <= point { point - 13 } else { 12 - point } as u32;
+    return if index < 6 { 1 + index } else { 2 + index };
+}
+
+// The pips of a die as positions on a 4 by 4 grid over the face.
+fn die_pips(value: Die) -> &'static [(u32, u32)] {
+    return match value {
+        1 => &[(2, 2)],
+        2 => &[(1, 1), (3, 3)],
+        3 => &[(1, 1), (2, 2), (3, 3)],
+        4 => &[(1, 1), (3, 1), (1, 3), (3, 3)],
+        5 => &[(1, 1), (3, 1), (2, 2), (1, 3), (3, 3)],
+        _ => &[(1, 1), (3, 1), (1, 2), (3, 2), (1, 3), (3, 3)],
+    };
+}
+
+impl SvgRenderer {
+    pub fn width(&self) -> u32 {
+        return COLUMNS * self.point_width;
+    }
+
+    pub fn height(&self) -> u32 {
+        return ROWS * self.point_width;
+    }
+
+    // The area of a point numbered for the player seen from, as x, y, width and height.
+    // Its bar is point 25 and the tray it bears off into is point 0.
+    pub fn point_area(&self, point: usize) -> (u32, u32, u32, u32) {
+        let w = self.point_width;
+        let half = self.y(BOTTOM_ROW - MIDDLE_ROW - 1);
+        return match point {
+            0 => (self.x(TRAY_COLUMN), self.y(MIDDLE_ROW + 1), w, half),
+            BEARING_OFF_POS => (self.x(BAR_COLUMN), self.y(TOP_ROW), w, self.y(BOTTOM_ROW - TOP_ROW)),
+            _ if 13 <= point => (self.x(point_column(point)), self.y(TOP_ROW), w, half),
+            _ => (self.x(point_column(point)), self.y(MIDDLE_ROW + 1), w, half),
+        };
+    }
+
+    // The left edge of a column, mirrored when the home board is on the left.
+    fn x(&self, column: u32) -> u32 {
+        let column = match self.orientation {
+            Orientation::HomeRight => column,
+            Orientation::HomeLeft => COLUMNS - 1 - column,
+        };
+        return column * self.point_width;
+    }
+
+    fn y(&self, row: u32) -> u32 {
+        return row * self.point_width;
+    }
+
+    pub fn render(&self, diagram: &Diagram) -> String {
+        let mut out = Vec::new();
+        self.write(&mut out, diagram).expect("writing to memory");
+        return String::from_utf8(out).expect("the diagram is ASCII");
+    }
+
+    pub fn write<W: Write>(&self, out: &mut W, diagram: &Diagram) -> io::Result<()> {
+        let game = &diagram.position;
+        let w = self.point_width;
+        writeln!(out, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" \
+                       viewBox=\"0 0 {} {}\" font-family=\"sans-serif\">",
+                 self.width(), self.height(), self.width(), self.height())?;
+        writeln!(out, "<rect width=\"{}\" height=\"{}\" fill=\"{}\"/>", self.width(), self.height(),
+                 self.theme.background)?;
+        let left = self.x(1).min(self.x(COLUMNS - 2));
+        writeln!(out, "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" stroke=\"{}\"/>",
+                 left, self.y(TOP_ROW), 13 * w, self.y(BOTTOM_ROW - TOP_ROW), self.theme.board,
+                 self.theme.outline)?;
+        writeln!(out, "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+                 self.x(BAR_COLUMN), self.y(TOP_ROW), w, self.y(BOTTOM_ROW - TOP_ROW), self.theme.bar)?;
+        for point in 1..BEARING_OFF_POS {
+            self.write_point(out, game, point)?;
+        }
+        self.write_bar(out, game)?;
+        for &color in [Color::Red, Color::White].iter() {
+            self.write_tray(out, color, game.get_board(color, BEARING_OFF_POS))?;
+        }
+        if self.pip_counts {
+            let rows = [(self.perspective.opposite(), 0), (self.perspective, ROWS - 1)];
+            for &(color, row) in rows.iter() {
+                writeln!(out, "<text x=\"{}\" y=\"{}\" font-size=\"{}\" text-anchor=\"middle\" \
+                               fill=\"{}\">{} pips {}</text>",
+                         self.width() / 2, self.y(row) + 2 * w / 3, w / 2, self.theme.text, color,
+                         game.pip_count(color))?;
+            }
+        }
+        if let Some((color, roll)) = diagram.dice {
+            self.write_dice(out, color, roll)?;
+        }
+        if let Some(cube) = diagram.cube {
+            self.write_cube(out, &cube)?;
+        }
+        writeln!(out, "</svg>")?;
+        return Ok(());
+    }
+
+    fn write_point<W: Write>(&self, out: &mut W, game: &Backgammon, point: usize) -> io::Result<()> {
+        let w = self.point_width;
+        let x = self.x(point_column(point));
+        let top = 13 <= point;
+        let (base, tip) = if top {
+            (self.y(TOP_ROW), self.y(TOP_ROW + MAX_STACK as u32) - w / 4)
+        } else {
+            (self.y(BOTTOM_ROW), self.y(BOTTOM_ROW - MAX_STACK as u32) + w / 4)
+        };
+        let fill = if point % 2 == 1 { self.theme.light_point } else { self.theme.dark_point };
+        writeln!(out, "<polygon points=\"{},{} {},{} {},{}\" fill=\"{}\"/>", x, base, x + w, base,
+                 x + w / 2, tip, fill)?;
+        if self.point_numbers {
+            let label_y = if top { self.y(TOP_ROW) - w / 4 } else { self.y(BOTTOM_ROW) + 2 * w / 3 };
+            writeln!(out, "<text x=\"{}\" y=\"{}\" font-size=\"{}\" text-anchor=\"middle\" \
+                           fill=\"{}\">{}</text>", x + w / 2, label_y, w * 2 / 5, self.theme.text, point)?;
+        }
+        let (own, other) = (self.perspective, self.perspective.opposite());
+        let own_count = game.get_board(own, point_position(own, own, point));
+        let other_count = game.get_board(other, point_position(own, other, point));
+        let (color, count) = if 0 < own_count { (own, own_count) } else { (other, other_count) };
+        let edge = if top { self.y(TOP_ROW) } else { self.y(BOTTOM_ROW) };
+        return self.write_stack(out, x, edge, top, color, count);
+    }
+
+    // A stack of checkers from the edge, downwards if from the top, with the count on the
+    // last checker drawn if there are more.
+    fn write_stack<W: Write>(&self, out: &mut W, x: u32, edge: u32, downwards: bool, color: Color,
+                             count: i8) -> io::Result<()> {
+        let w = self.point_width;
+        let radius = w / 2;
+        let drawn = count.min(MAX_STACK) as u32;
+        for i in 0..drawn {
+            let offset = radius + i * w;
+            let y = if downwards { edge + offset } else { edge - offset };
+            writeln!(out, "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\" stroke=\"{}\"/>",
+                     x + radius, y, radius - 1, self.theme.checker(color), self.theme.outline)?;
+            if i + 1 == drawn && MAX_STACK < count {
+                writeln!(out, "<text x=\"{}\" y=\"{}\" font-size=\"{}\" text-anchor=\"middle\" \
+                               fill=\"{}\">{}</text>",
+                         x + radius, y + w / 6, w / 2, self.theme.checker(color.opposite()), count)?;
+            }
+        }
+        return Ok(());
+    }
+
+    // The checkers on the bar of the player seen from above the gap and the other player's
+    // below, on the side of the board where each enters.
+    fn write_bar<W: Write>(&self, out: &mut W, game: &Backgammon) -> io::Result<()> {
+        let (own, other) = (self.perspective, self.perspective.opposite());
+        let x = self.x(BAR_COLUMN);
+        let middle = self.y(MIDDLE_ROW);
+        self.write_stack(out, x, middle, false, own, game.get_board(own, BAR_POS))?;
+        return self.write_stack(out, x, middle + self.point_width, true, other,
+                                game.get_board(other, BAR_POS));
+    }
+
+    // The borne off checkers as thin bars in the tray beside the home board of the player.
+    fn write_tray<W: Write>(&self, out: &mut W, color: Color, count: i8) -> io::Result<()> {
+        let w = self.point_width;
+        let x = self.x(TRAY_COLUMN);
+        let thickness = w / 3;
+        let bottom = color == self.perspective;
+        for i in 0..(count.max(0) as u32) {
+            let y = if bottom {
+                self.y(BOTTOM_ROW) - (i + 1) * thickness
+            } else {
+                self.y(TOP_ROW) + i * thickness
+            };
+            writeln!(out, "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" stroke=\"{}\"/>",
+                     x + 2, y, w - 4, thickness, self.theme.checker(color), self.theme.outline)?;
+        }
+        if 0 < count {
+            let y = if bottom { self.y(BOTTOM_ROW) + 2 * w / 3 } else { self.y(TOP_ROW) - w / 4 };
+            writeln!(out, "<text x=\"{}\" y=\"{}\" font-size=\"{}\" text-anchor=\"middle\" \
+                           fill=\"{}\">{}</text>", x + w / 2, y, w * 2 / 5, self.theme.text, count)?;
+        }
+        return Ok(());
+    }
+
+    // The dice in the gap between the halves, on the right for the player seen from and on
+    // the left for the other as each player rolls to its right.
+    fn write_dice<W: Write>(&self, out: &mut W, color: Color, roll: DiceRoll) -> io::Result<()> {
+        let w = self.point_width;
+        let columns = if color == self.perspective { [9, 11] } else { [3, 5] };
+        let y = self.y(MIDDLE_ROW);
+        for (&column, &value) in columns.iter().zip([roll.0, roll.1].iter()) {
+            let x = self.x(column);
+            writeln!(out, "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"{}\" fill=\"{}\" \
+                           stroke=\"{}\"/>", x + 1, y + 1, w - 2, w - 2, w / 6, self.theme.die,
+                     self.theme.outline)?;
+            for &(i, j) in die_pips(value).iter() {
+                writeln!(out, "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\"/>", x + i * w / 4,
+                         y + j * w / 4, (w / 12).max(1), self.theme.die_pip)?;
+            }
+        }
+        return Ok(());
+    }
+
+    // The cube in the middle when centered, or else on the side of its owner, which is the
+    // bottom for the player seen from. A centered cube shows 64.
+    fn write_cube<W: Write>(&self, out: &mut W, cube: &Cube) -> io::Result<()> {
+        let w = self.point_width;
+        let x = self.x(CUBE_COLUMN);
+        let row = match cube.owner {
+            None => MIDDLE_ROW,
+            Some(owner) if owner == self.perspective => BOTTOM_ROW - 1,
+            Some(_) => TOP_ROW,
+        };
+        let value = if cube.owner.is_none() && cube.value == 1 { 64 } else { cube.value };
+        writeln!(out, "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"{}\" fill=\"{}\" \
+                       stroke=\"{}\"/>", x + 1, self.y(row) + 1, w - 2, w - 2, w / 6, self.theme.die,
+                 self.theme.outline)?;
+        writeln!(out, "<text x=\"{}\" y=\"{}\" font-size=\"{}\" text-anchor=\"middle\" \
+                       fill=\"{}\">{}</text>", x + w / 2, self.y(row) + 2 * w / 3, w * 2 / 5,
+                 self.theme.die_pip, value)?;
+        return Ok(());
+    }
+}
@@ -0,0 +1,286 @@
+/*
+ * Rustgammon
+ *
+ * Text boards
+ * Write a position as text to any writer in one of several styles: the compact grid of
+ * checker counts, a classic board like gnubg's with the checkers stacked vertically as X
+ * for Red and O for White, and the classic board drawn with box characters and checkers
+ * coloured by ANSI escapes for terminals.
+ * The board is seen from the perspective of a player, with its point numbers and its home
+ * board at the bottom, on the right unless the orientation puts it on the left.
+ * Stacks of more than five checkers show the count in place of the fifth checker.
+ *
+ * @author ryutaroikeda94@gmail.com
+ *
+ */
+
+use std::fmt;
+use std::str::FromStr;
+
+use positionstats::PositionStats;
+use notation::Orientation;
+use notation::point_position;
+use rustgammon::Backgammon;
+use rustgammon::BAR_POS;
+use rustgammon::BEARING_OFF_POS;
+use rustgammon::Checker;
+use rustgammon::Color;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BoardStyle {
+    Compact,
+    Classic,
+    Unicode,
+}
+
+pub const STYLES: [BoardStyle; 3] = [BoardStyle::Compact, BoardStyle::Classic, BoardStyle::Unicode];
+
+// A position with the style and the perspective to display it in.
+pub struct BoardView<'a> {
+    pub game: &'a Backgammon,
+    pub style: BoardStyle,
+    pub perspective: Color,
+    pub orientation: Orientation,
+}
+
+const STACK_HEIGHT: usize = 5;
+
+// The characters of the classic styles.
+struct Glyphs {
+    red: &'static str,
+    white: &'static str,
+    // The escapes starting the colour of each player and resetting it.
+    red_colour: &'static str,
+    white_colour: &'static str,
+    reset: &'static str,
+    horizontal: char,
+    vertical: &'static str,
+    top: [&'static str; 4],
+    bottom: [&'static str; 4],
+}
+
+const ASCII: Glyphs = Glyphs {
+    red: "X",
+    white: "O",
+    red_colour: "",
+    white_colour: "",
+    reset: "",
+    horizontal: '-',
+    vertical: "|",
+    top: ["+", "+", "+", "+"],
+    bottom: ["+", "+", "+", "+"],
+};
+
+const UNICODE: Glyphs = Glyphs {
+    red: "\u{25cf}",
+    white: "\u{25cb}",
+    red_colour: "\x1b[1;31m",
+    white_colour: "\x1b[1;37m",
+    reset: "\x1b[0m",
+    horizontal: '\u{2500}',
+    vertical: "\u{2502}",
+    top: ["\u{250c}", "\u{252c}", "\u{252c}", "\u{2510}"],
+    bottom: ["\u{2514}", "\u{2534}", "\u{2534}", "\u{2518}"],
+};
+
+impl BoardStyle {
+    pub fn name(&self) -> &'static str {
+        return match *self {
+            BoardStyle::Compact => "compact",
+            BoardStyle::Classic => "classic",
+            BoardStyle::Unicode => "unicode",
+        };
+    }
+}
+
+impl FromStr for BoardStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<BoardStyle, String> {
+        return match STYLES.iter().find(|style| style.name() == s) {
+            Some(style) => Ok(*style),
+            None => Err(format!("unknown board style {}", s)),
+        };
+    }
+}
+
+pub fn write_board<W: fmt::Write>(out: &mut W, game: &Backgammon, style: BoardStyle,
+                                  perspective: Color, orientation: Orientation) -> fmt::Result {
+    let board = Board { game: game, perspective: perspective, orientation: orientation };
+    return match style {
+        BoardStyle::Compact => write_compact(out, &board),
+        BoardStyle::Classic => write_classic(out, &board, &ASCII),
+        BoardStyle::Unicode => write_classic(out, &board, &UNICODE),
+    };
+}
+
+// A position as seen from the perspective of a player.
+struct Board<'a> {
+    game: &'a Backgammon,
+    perspective: Color,
+    orientation: Orientation,
+}
+
+impl<'a> Board<'a> {
+    // The point numbers of the top half from left to right.
+    fn top_points(&self) -> Vec<usize> {
+        return match self.orientation {
+            Orientation::HomeRight => (13..25).collect(),
+            Orientation::HomeLeft => (13..25).rev().collect(),
+        };
+    }
+
+    // The point numbers of the bottom half from left to right.
+    fn bottom_points(&self) -> Vec<usize> {
+        return match self.orientation {
+            Orientation::HomeRight => (1..13).rev().collect(),
+            Orientation::HomeLeft => (1..13).collect(),
+        };
+    }
+
+    // The checkers on a point numbered from the perspective.
+    fn point_checkers(&self, point: usize) -> (Color, Checker) {
+        let own = self.game.get_board(self.perspective,
+                                      point_position(self.perspective, self.perspective, point));
+        if 0 < own {
+            return (self.perspective, own);
+        }
+        let other = self.perspective.opposite();
+        return (other, self.game.get_board(other, point_position(self.perspective, other, point)));
+    }
+}
+
+fn capitalized(color: Color) -> &'static str {
+    return match color {
+        Color::Red => "Red",
+        Color::White => "White",
+    };
+}
+
+// The counts of the checkers on each point in two rows, with the numbers of the points
+// above and below.
+fn write_compact<W: fmt::Write>(out: &mut W, board: &Board) -> fmt::Result {
+    let game = board.game;
+    let write_points = |out: &mut W, points: &[usize], separator: &str| {
+        for (i, point) in points.iter().enumerate() {
+            if i == 6 {
+                write!(out, "{}", separator)?;
+            }
+            write!(out, " {:>2}", point)?;
+        }
+        return writeln!(out);
+    };
+    let write_checkers = |out: &mut W, points: &[usize], color: Color| {
+        for (i, &point) in points.iter().enumerate() {
+            if i == 6 {
+                write!(out, " ||")?;
+            }
+            match board.point_checkers(point) {
+                (Color::Red, count) if 0 < count => write!(out, " R{}", count)?,
+                (Color::White, count) if 0 < count => write!(out, " W{}", count)?,
+                _ => write!(out, " ..")?,
+            }
+        }
+        return writeln!(out, "\t{} bar: {}\t{} off: {}", capitalized(color),
+                        game.get_board(color, BAR_POS), capitalized(color),
+                        game.get_board(color, BEARING_OFF_POS));
+    };
+    write_points(out, &board.top_points(), "   ")?;
+    write_checkers(out, &board.top_points(), board.perspective)?;
+    write_checkers(out, &board.bottom_points(), board.perspective.opposite())?;
+    write_points(out, &board.bottom_points(), "   ")?;
+    writeln!(out, "red {}", PositionStats::new(game, Color::Red))?;
+    writeln!(out, "white {}", PositionStats::new(game, Color::White))?;
+    return Ok(());
+}
+
+// The cell of a stack at a distance from its base, three characters wide.
+fn write_cell<W: fmt::Write>(out: &mut W, glyphs: &Glyphs, color: Color, count: Checker,
+                             height: usize) -> fmt::Result {
+    let (checker, colour) = match color {
+        Color::Red => (glyphs.red, glyphs.red_colour),
+        Color::White => (glyphs.white, glyphs.white_colour),
+    };
+    if (count as usize) <= height {
+        return write!(out, "   ");
+    }
+    if height + 1 == STACK_HEIGHT && STACK_HEIGHT < count as usize {
+        return write!(out, "{}{:>2}{} ", colour, count, glyphs.reset);
+    }
+    return write!(out, " {}{}{} ", colour, checker, glyphs.reset);
+}
+
+// A border with the point numbers, as "+12-11-10--9--8--7-+---+-6--5--...".
+fn write_border<W: fmt::Write>(out: &mut W, glyphs: &Glyphs, corners: &[&str; 4],
+                               points: &[usize]) -> fmt::Result {
+    let horizontal = glyphs.horizontal.to_string();
+    write!(out, "{}", corners[0])?;
+    for (i, point) in points.iter().enumerate() {
+        if i == 6 {
+            write!(out, "{}{}{}", corners[1], horizontal.repeat(3), corners[2])?;
+        }
+        let number = point.to_string();
+        write!(out, "{}{}{}", horizontal.repeat(2 - number.len()), number, horizontal)?;
+    }
+    return writeln!(out, "{}", corners[3]);
+}
+
+fn write_classic<W: fmt::Write>(out: &mut W, board: &Board, glyphs: &Glyphs) -> fmt::Result {
+    let game = board.game;
+    let (own, other) = (board.perspective, board.perspective.opposite());
+    let top_points = board.top_points();
+    let bottom_points = board.bottom_points();
+    write_border(out, glyphs, &glyphs.top, &top_points)?;
+    // The rows of the top half from the edge, the bar and the rows of the bottom half
+    // towards the edge. The checkers on the bar of the player seen from are above it, on
+    // the side where they enter, and the other player's are below.
+    for row in 0..(2 * STACK_HEIGHT + 1) {
+        let (points, height) = if row < STACK_HEIGHT {
+            (&top_points, row)
+        } else if STACK_HEIGHT < row {
+            (&bottom_points, 2 * STACK_HEIGHT - row)
+        } else {
+            (&top_points, 0)
+        };
+        write!(out, "{}", glyphs.vertical)?;
+        for (i, &point) in points.iter().enumerate() {
+            if i == 6 {
+                write!(out, "{}", glyphs.vertical)?;
+                if row == STACK_HEIGHT {
+                    write!(out, "BAR")?;
+                } else if row < STACK_HEIGHT {
+                    write_cell(out, glyphs, own, game.get_board(own, BAR_POS),
+                               STACK_HEIGHT - 1 - row)?;
+                } else {
+                    write_cell(out, glyphs, other, game.get_board(other, BAR_POS),
+                               row - STACK_HEIGHT - 1)?;
+                }
+                write!(out, "{}", glyphs.vertical)?;
+            }
+            if row == STACK_HEIGHT {
+                write!(out, "   ")?;
+            } else {
+                let (color, count) = board.point_checkers(point);
+                write_cell(out, glyphs, color, count, height)?;
+            }
+        }
+        write!(out, "{}", glyphs.vertical)?;
+        if row == 0 {
+            write!(out, "  {} off: {}", other, game.get_board(other, BEARING_OFF_POS))?;
+        } else if row == 2 * STACK_HEIGHT {
+            write!(out, "  {} off: {}", own, game.get_board(own, BEARING_OFF_POS))?;
+        }
+        writeln!(out)?;
+    }
+    write_border(out, glyphs, &glyphs.bottom, &bottom_points)?;
+    writeln!(out, "red {}{}{}: {} pips, white {}{}{}: {} pips",
+             glyphs.red_colour, glyphs.red, glyphs.reset, game.pip_count(Color::Red),
+             glyphs.white_colour, glyphs.white, glyphs.reset, game.pip_count(Color::White))?;
+    return Ok(());
+}
+
+impl<'a> fmt::Display for BoardView<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write_board(f, self.game, self.style, self.perspective, self.orientation);
+    }
+}
@@ -0,0 +1,255 @@
+/*
+ * Rustgammon
+ *
+ * Training
+ * Train the neural network by self-play with TD(lambda), as in TD-Gammon.
+ * Both sides of each game are played by the network through the Player trait, then the
+ * network learns from the game record, once from each player's side.
+ *
+ * @author ryutaroikeda94@gmail.com
+ *
+ */
+
+use std::fs;
+
+use gamerecord::GameRecord;
+use heuristicplayer::HeuristicPlayer;
+use neuralnet::NeuralNet;
+use neuralnet::NeuralNetError;
+use neuralnet::NUM_INPUTS;
+use neuralnet::NUM_OUTPUTS;
+use neuralnet::encode;
+use neuralnet::to_outputs;
+use randomplayer::RandomPlayer;
+use rollout::game_result;
+use rustgammon::Backgammon;
+use rustgammon::Color;
+use rustgammon::Dice;
+use rustgammon::Player;
+use searchplayer::SearchPlayer;
+
+// Where training saves the weights unless told otherwise, so that it does not overwrite
+// the weights shipped in config/.
+pub const DEFAULT_CHECKPOINT_PATH: &str = "data/neuralnet.txt";
+
+pub struct TdTrainer {
+    pub net: NeuralNet,
+    // The learning rate.
+    pub alpha: f64,
+    // The decay of the eligibility traces.
+    pub lambda: f64,
+    // The trace of each output for each hidden weight.
+    hidden_traces: Vec<f64>,
+    // An output weight only affects its own output, so it has one trace.
+    output_traces: Vec<f64>,
+}
+
+#[derive(Clone, Debug)]
+pub struct TrainingConfig {
+    pub games: u64,
+    pub checkpoint_interval: u64,
+    pub checkpoint_path: String,
+    pub benchmark_interval: u64,
+    pub benchmark_games: u64,
+}
+
+// The results of a match from the first player's side.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct MatchResult {
+    pub games: u64,
+    pub wins: u64,
+    pub points_won: u64,
+    pub points_lost: u64,
+}
+
+impl TdTrainer {
+    pub fn new(net: NeuralNet, alpha: f64, lambda: f64) -> TdTrainer {
+        let hidden_traces = vec![0.0; NUM_OUTPUTS * net.hidden_weights.len()];
+        let output_traces = vec![0.0; net.output_weights.len()];
+        return TdTrainer {
+            net: net,
+            alpha: alpha,
+            lambda: lambda,
+            hidden_traces: hidden_traces,
+            output_traces: output_traces,
+        };
+    }
+
+    // Play a game of the network against itself and learn from it.
+    pub fn train_game<D: Dice>(&mut self, dice: &mut D) -> GameRecord {
+        let record = {
+            let red = SearchPlayer::new(Color::Red, &self.net, 0);
+            let white = SearchPlayer::new(Color::White, &self.net, 0);
+            let mut game: Backgammon = Default::default();
+            game.init();
+            game.play_game(&red, &white, dice)
+        };
+        self.learn(&record, Color::Red);
+        self.learn(&record, Color::White);
+        return record;
+    }
+
+    // Update the weights towards the evaluation of each following position from the
+    // player's side, and towards the result at the end of the game.
+    pub fn learn(&mut self, record: &GameRecord, color: Color) {
+        let winner = match record.winner {
+            Some(winner) => winner,
+            None => return,
+        };
+        for trace in self.hidden_traces.iter_mut().chain(self.output_traces.iter_mut()) {
+            *trace = 0.0;
+        }
+        let mut game = record.start;
+        let mut on_roll = match record.turns.first() {
+            Some(turn) => turn.color == color,
+            None => true,
+        };
+        let mut previous = self.accumulate(&encode(&game, color, on_roll));
+        for turn in &record.turns {
+            game.do_move(turn.color, &turn.mov);
+            if game.winner().is_some() {
+                break;
+            }
+            on_roll = turn.color != color;
+            let inputs = encode(&game, color, on_roll);
+            let (_, outputs) = self.net.forward(&inputs);
+            let mut errors = [0.0; NUM_OUTPUTS];
+            for k in 0..NUM_OUTPUTS {
+                errors[k] = outputs[k] - previous[k];
+            }
+            self.update(&errors);
+            previous = self.accumulate(&inputs);
+        }
+        let target = to_outputs(&game_result(&game, winner, color));
+        let mut errors = [0.0; NUM_OUTPUTS];
+        for k in 0..NUM_OUTPUTS {
+            errors[k] = target[k] - previous[k];
+        }
+        self.update(&errors);
+    }
+
+    // Decay the traces and add the gradient of the outputs for the inputs.
+    fn accumulate(&mut self, inputs: &[f64]) -> [f64; NUM_OUTPUTS] {
+        let (hidden, outputs) = self.net.forward(inputs);
+        let num_hidden = self.net.hidden;
+        let num_hidden_weights = self.net.hidden_weights.len();
+        for (k, output) in outputs.iter().enumerate() {
+            let output_gradient = output * (1.0 - output);
+            let output_weights = &self.net.output_weights[k * (num_hidden + 1)..];
+            let output_traces = &mut self.output_traces[k * (num_hidden + 1)..(k + 1) * (num_hidden + 1)];
+            for (j, trace) in output_traces.iter_mut().enumerate() {
+                let activation = if j < num_hidden { hidden[j] } else { 1.0 };
+                *trace = self.lambda * *trace + output_gradient * activation;
+            }
+            let hidden_traces = &mut self.hidden_traces[k * num_hidden_weights..(k + 1) * num_hidden_weights];
+            for j in 0..num_hidden {
+                let gradient = output_gradient * output_weights[j] * hidden[j] * (1.0 - hidden[j]);
+                let unit_traces = &mut hidden_traces[j * (NUM_INPUTS + 1)..(j + 1) * (NUM_INPUTS + 1)];
+                for (i, trace) in unit_traces.iter_mut().enumerate() {
+                    let input = if i < NUM_INPUTS { inputs[i] } else { 1.0 };
+                    *trace = self.lambda * *trace + gradient * input;
+                }
+            }
+        }
+        return outputs;
+    }
+
+    fn update(&mut self, errors: &[f64; NUM_OUTPUTS]) {
+        let num_hidden = self.net.hidden;
+        let num_hidden_weights = self.net.hidden_weights.len();
+        for (k, error) in errors.iter().enumerate() {
+            let step = self.alpha * error;
+            if step == 0.0 {
+                continue;
+            }
+            let output_range = k * (num_hidden + 1)..(k + 1) * (num_hidden + 1);
+            for (weight, trace) in self.net.output_weights[output_range.clone()].iter_mut()
+                .zip(self.output_traces[output_range].iter()) {
+                *weight += step * trace;
+            }
+            let hidden_traces = &self.hidden_traces[k * num_hidden_weights..(k + 1) * num_hidden_weights];
+            for (weight, trace) in self.net.hidden_weights.iter_mut().zip(hidden_traces.iter()) {
+                *weight += step * trace;
+            }
+        }
+    }
+}
+
+impl MatchResult {
+    pub fn losses(&self) -> u64 {
+        return self.games - self.wins;
+    }
+
+    // The average points won per game.
+    pub fn points_per_game(&self) -> f64 {
+        if self.games == 0 {
+            return 0.0;
+        }
+        return (self.points_won as f64 - self.points_lost as f64) / self.games as f64;
+    }
+}
+
+// Play games between the players, alternating who moves first.
+pub fn play_match<S: Player, T: Player, D: Dice>(player: &S, opponent: &T, games: u64,
+                                                 dice: &mut D) -> MatchResult {
+    let mut result: MatchResult = Default::default();
+    for game_idx in 0..games {
+        let mut game: Backgammon = Default::default();
+        game.init();
+        let record = if game_idx % 2 == 0 {
+            game.play_game(player, opponent, dice)
+        } else {
+            game.play_game(opponent, player, dice)
+        };
+        let points = match record.winner {
+            Some(winner) => game.points_won(winner) as u64,
+            None => continue,
+        };
+        result.games += 1;
+        if record.winner == Some(player.get_color()) {
+            result.wins += 1;
+            result.points_won += points;
+        } else {
+            result.points_lost += points;
+        }
+    }
+    return result;
+}
+
+// Play the network against the random and the heuristic players.
+pub fn benchmark<D: Dice>(net: &NeuralNet, games: u64, dice: &mut D) -> (MatchResult, MatchResult) {
+    let player = SearchPlayer::new(Color::Red, net, 0);
+    let random = play_match(&player, &RandomPlayer { color: Color::White }, games, dice);
+    let heuristic = play_match(&player, &HeuristicPlayer::new(Color::White), games, dice);
+    return (random, heuristic);
+}
+
+// Save the weights through a temporary file so an interrupted write keeps the last
+// checkpoint.
+pub fn checkpoint(net: &NeuralNet, path: &str) -> Result<(), NeuralNetError> {
+    let temporary_path = format!("{}.tmp", path);
+    net.save(&temporary_path)?;
+    return fs::rename(&temporary_path, path).map_err(|e| format!("{}: {}", path, e));
+}
+
+// Train for the configured number of games, checkpointing and benchmarking as we go.
+pub fn train<D: Dice>(trainer: &mut TdTrainer, config: &TrainingConfig,
+                      dice: &mut D) -> Result<(), NeuralNetError> {
+    let mut moves = 0;
+    for game_idx in 1..(config.games + 1) {
+        moves += trainer.train_game(dice).turns.len();
+        if 0 < config.checkpoint_interval && game_idx % config.checkpoint_interval == 0 {
+            checkpoint(&trainer.net, &config.checkpoint_path)?;
+            info!("game {}: {:.1} turns per game, saved {}", game_idx,
+                  moves as f64 / config.checkpoint_interval as f64, config.checkpoint_path);
+            moves = 0;
+        }
+        if 0 < config.benchmark_interval && game_idx % config.benchmark_interval == 0 {
+            let (random, heuristic) = benchmark(&trainer.net, config.benchmark_games, dice);
+            info!("game {}: vs random {}/{} wins {:+.3} ppg, vs heuristic {}/{} wins {:+.3} ppg",
+                  game_idx, random.wins, random.games, random.points_per_game(),
+                  heuristic.wins, heuristic.games, heuristic.points_per_game());
+        }
+    }
+    return checkpoint(&trainer.net, &config.checkpoint_path);
+}
@@ -0,0 +1,789 @@
+/*
+ * Rustgammon
+ *
+ * Terminal user interface
+ * Play against a computer player on a full screen board in the terminal. The checkers are
+ * stacked on the points as on a real board, and a checker is moved by selecting its point
+ * and then one of the highlighted destinations, with the arrow keys and enter or with the
+ * mouse. The rolls are animated, the moves of both players are listed beside the board
+ * and the best moves for the roll can be shown below it.
+ * The screen is drawn into a grid of cells first, so that everything but the terminal
+ * itself can be tested. The board is seen from the human's side, with the home board at
+ * the bottom, on the right unless the orientation puts it on the left. Its columns are,
+ * from the left with the home board on the right, six points, the bar, six points and the
+ * tray of borne off checkers, and its rows are the point numbers, five checkers, the bar
+ * and the dice, five checkers and the point numbers.
+ *
+ * @author ryutaroikeda94@gmail.com
+ *
+ */
+
+use std::io;
+use std::io::Write;
+use std::thread;
+use std::time::Duration;
+
+use rand;
+use rand::Rng;
+use termion;
+use termion::color;
+use termion::cursor;
+use termion::event::Event;
+use termion::event::Key;
+use termion::event::MouseButton;
+use termion::event::MouseEvent;
+use termion::input::MouseTerminal;
+use termion::input::TermRead;
+use termion::raw::IntoRawMode;
+use termion::screen::AlternateScreen;
+use termion::style;
+
+use commandlineplayer::CommandLinePlayer;
+use commandlineplayer::DEFAULT_HINTS;
+use gamerecord::GameRecord;
+use gamerecord::Turn;
+use notation::Orientation;
+use notation::format_move_from;
+use notation::point_position;
+use rustgammon::Backgammon;
+use rustgammon::BAR_POS;
+use rustgammon::BEARING_OFF_POS;
+use rustgammon::Checker;
+use rustgammon::Color;
+use rustgammon::Dice;
+use rustgammon::DiceRoll;
+use rustgammon::Die;
+use rustgammon::Move;
+use rustgammon::Player;
+use rustgammon::Position;
+use rustgammon::Submove;
+
+// The size of the screen, which fits a standard terminal.
+pub const WIDTH: usize = 80;
+pub const HEIGHT: usize = 24;
+
+// The left edges of the columns of the board with the home board on the right: six
+// points, the bar, six points and the tray.
+const COLUMN_X: [usize; 14] = [1, 4, 7, 10, 13, 16, 20, 24, 27, 30, 33, 36, 39, 43];
+const BAR_COLUMN: usize = 6;
+const TRAY_COLUMN: usize = 13;
+const BORDER_X: [usize; 5] = [0, 19, 23, 42, 46];
+const BOARD_WIDTH: usize = 47;
+const TOP_ROW: usize = 0;
+const MIDDLE_ROW: usize = 6;
+const BOTTOM_ROW: usize = 12;
+const STACK_HEIGHT: usize = 5;
+const STATUS_ROW: usize = 13;
+const MESSAGE_ROW: usize = 14;
+const HINT_ROW: usize = 16;
+const HELP_ROW: usize = 23;
+const LOG_X: usize = 49;
+
+const ROLL_FRAMES: usize = 8;
+const FRAME_TIME: u64 = 60;
+// How long a move of the computer stays on the screen before the next roll.
+const PAUSE_TIME: u64 = 800;
+
+const HELP: &str = "arrows/click: choose  enter: select  esc: cancel  u: undo  h: hint  q: quit";
+
+// The user's actions, whichever way they were made.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Input {
+    Left,
+    Right,
+    Up,
+    Down,
+    Select,
+    Cancel,
+    Undo,
+    Hint,
+    Quit,
+    // A click on the cell of the column and row.
+    Click(usize, usize),
+}
+
+// What the game loop does after an input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Continue,
+    Move(Move),
+    Quit,
+}
+
+// A place on the board a checker can be moved from or to. Points are numbered for the
+// human.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Slot {
+    Point(usize),
+    Bar,
+    Off,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Ink {
+    Plain,
+    Border,
+    Red,
+    White,
+    Dim,
+    Title,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Paper {
+    Plain,
+    Cursor,
+    Selected,
+    Target,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Cell {
+    pub ch: char,
+    pub ink: Ink,
+    pub paper: Paper,
+}
+
+// The screen as a grid of cells.
+pub struct Canvas {
+    pub width: usize,
+    pub height: usize,
+    cells: Vec<Cell>,
+}
+
+// The submoves the human has played so far with a roll, checked against the legal moves.
+pub struct TurnInput {
+    pub color: Color,
+    pub roll: DiceRoll,
+    pub start: Backgammon,
+    pub position: Backgammon,
+    // The submoves in the order they were played.
+    pub played: Vec<Submove>,
+    // The legal moves with their submoves in the order they are played.
+    legal: Vec<Vec<Submove>>,
+}
+
+pub struct Tui {
+    pub game: Backgammon,
+    pub human: Color,
+    pub orientation: Orientation,
+    // The roll the human is playing.
+    pub turn: Option<TurnInput>,
+    pub cursor: Slot,
+    pub selected: Option<Slot>,
+    // The player who rolled and the faces shown.
+    pub dice: Option<(Color, DiceRoll)>,
+    pub log: Vec<(Color, String)>,
+    pub hints: Vec<String>,
+    // Ranks the moves for hints, keeping its evaluator loaded between them.
+    pub hint_player: CommandLinePlayer,
+    pub message: String,
+}
+
+impl Canvas {
+    pub fn new(width: usize, height: usize) -> Canvas {
+        let blank = Cell { ch: ' ', ink: Ink::Plain, paper: Paper::Plain };
+        return Canvas { width: width, height: height, cells: vec![blank; width * height] };
+    }
+
+    pub fn cell(&self, x: usize, y: usize) -> Cell {
+        return self.cells[y * self.width + x];
+    }
+
+    // Write the text from the cell, cut at the edge of the canvas.
+    pub fn put(&mut self, x: usize, y: usize, text: &str, ink: Ink) {
+        if self.height <= y {
+            return;
+        }
+        for (i, ch) in text.chars().enumerate() {
+            if self.width <= x + i {
+                break;
+            }
+            let cell = &mut self.cells[y * self.width + x + i];
+            cell.ch = ch;
+            cell.ink = ink;
+        }
+    }
+
+    pub fn paint(&mut self, x: usize, y: usize, width: usize, paper: Paper) {
+        for i in x..::std::cmp::min(x + width, self.width) {
+            self.cells[y * self.width + i].paper = paper;
+        }
+    }
+
+    // The characters of a row without the trailing blanks.
+    pub fn line(&self, y: usize) -> String {
+        let line: String = self.cells[y * self.width..(y + 1) * self.width].iter()
+            .map(|cell| cell.ch).collect();
+        return line.trim_end().to_string();
+    }
+}
+
+impl TurnInput {
+    pub fn new(game: &Backgammon, color: Color, roll: DiceRoll) -> TurnInput {
+        let legal = game.list_moves(color, roll).into_iter()
+            .map(|mov| mov.submoves.into_iter().rev().collect()).collect();
+        return TurnInput { color: color, roll: roll, start: *game, position: *game,
+                           played: Vec::new(), legal: legal };
+    }
+
+    // The dice of the roll, four of them for a double.
+    pub fn dice(&self) -> Vec<Die> {
+        if self.roll.0 == self.roll.1 {
+            return vec![self.roll.0; 4];
+        }
+        return vec![self.roll.0, self.roll.1];
+    }
+
+    // Whether each die of the roll has been played.
+    pub fn used_dice(&self) -> Vec<bool> {
+        let dice = self.dice();
+        let mut used = vec![false; dice.len()];
+        for submove in &self.played {
+            if let Some(i) = (0..dice.len()).find(|&i| !used[i] && dice[i] == submove.die) {
+                used[i] = true;
+            }
+        }
+        return used;
+    }
+
+    // The submoves of the unplayed dice that continue a legal move.
+    pub fn legal_submoves(&self) -> Vec<Submove> {
+        let used = self.used_dice();
+        let mut dice: Vec<Die> = self.dice().into_iter().zip(used).filter(|&(_, used)| !used)
+            .map(|(die, _)| die).collect();
+        dice.dedup();
+        let count = self.played.len();
+        let mut submoves = Vec::new();
+        for die in dice {
+            for submove in self.position.list_submoves(self.color, die) {
+                if self.legal.iter().any(|mov| count < mov.len() && mov[..count] == self.played[..] &&
+                                                mov[count] == submove) {
+                    submoves.push(submove);
+                }
+            }
+        }
+        return submoves;
+    }
+
+    // The positions a checker can be moved to from a position.
+    pub fn destinations(&self, from: Position) -> Vec<Position> {
+        let mut destinations: Vec<Position> = self.legal_submoves().iter()
+            .filter(|submove| submove.from == from).map(|submove| submove.destination()).collect();
+        destinations.sort();
+        destinations.dedup();
+        return destinations;
+    }
+
+    // The submove between the positions with the lowest die that makes it.
+    pub fn submove_to(&self, from: Position, to: Position) -> Option<Submove> {
+        return self.legal_submoves().into_iter()
+            .filter(|submove| submove.from == from && submove.destination() == to)
+            .min_by_key(|submove| submove.die);
+    }
+
+    pub fn play(&mut self, submove: Submove) {
+        self.position.do_submove(self.color, &submove);
+        self.played.push(submove);
+    }
+
+    // Take back the last submove, returning whether there was one.
+    pub fn undo(&mut self) -> bool {
+        if self.played.pop().is_none() {
+            return false;
+        }
+        self.position = self.start;
+        for submove in &self.played {
+            self.position.do_submove(self.color, submove);
+        }
+        return true;
+    }
+
+    // Whether the submoves played make a legal move.
+    pub fn is_complete(&self) -> bool {
+        return self.legal.contains(&self.played);
+    }
+
+    pub fn to_move(&self) -> Move {
+        return Move { submoves: self.played.iter().rev().cloned().collect() };
+    }
+}
+
+fn glyph(color: Color) -> &'static str {
+    return match color {
+        Color::Red => "X",
+        Color::White => "O",
+    };
+}
+
+fn ink(color: Color) -> Ink {
+    return match color {
+        Color::Red => Ink::Red,
+        Color::White => Ink::White,
+    };
+}
+
+impl Tui {
+    pub fn new(game: &Backgammon, human: Color, orientation: Orientation) -> Tui {
+        return Tui {
+            game: *game,
+            human: human,
+            orientation: orientation,
+            turn: None,
+            // The human's mid point.
+            cursor: Slot::Point(13),
+            selected: None,
+            dice: None,
+            log: Vec::new(),
+            hints: Vec::new(),
+            hint_player: CommandLinePlayer { orientation: orientation, ..CommandLinePlayer::new(human) },
+            message: String::new(),
+        };
+    }
+
+    // The position shown, with the submoves the human has played so far.
+    pub fn position(&self) -> &Backgammon {
+        return match self.turn {
+            Some(ref turn) => &turn.position,
+            None => &self.game,
+        };
+    }
+
+    // The human's position of a slot.
+    pub fn slot_position(&self, slot: Slot) -> Position {
+        return match slot {
+            Slot::Point(point) => point_position(self.human, self.human, point),
+            Slot::Bar => BAR_POS,
+            Slot::Off => BEARING_OFF_POS,
+        };
+    }
+
+    pub fn position_slot(&self, pos: Position) -> Slot {
+        return match pos {
+            BAR_POS => Slot::Bar,
+            BEARING_OFF_POS => Slot::Off,
+            _ => Slot::Point(point_position(self.human, self.human, pos)),
+        };
+    }
+
+    // The left edge of a column of the board.
+    fn column_x(&self, column: usize) -> usize {
+        return match self.orientation {
+            Orientation::HomeRight => COLUMN_X[column],
+            Orientation::HomeLeft => BOARD_WIDTH - 3 - COLUMN_X[column],
+        };
+    }
+
+    // The column of a slot and whether it is in the top half. The bar is selected from the
+    // top half, where the human's checkers on it are, and the tray from the bottom.
+    fn slot_column(&self, slot: Slot) -> (usize, bool) {
+        return match slot {
+            Slot::Point(point) => {
+                let index = if 13 <= point { point - 13 } else { 12 - point };
+                (if index < 6 { index } else { index + 1 }, 13 <= point)
+            },
+            Slot::Bar => (BAR_COLUMN, true),
+            Slot::Off => (TRAY_COLUMN, false),
+        };
+    }
+
+    pub fn slot_x(&self, slot: Slot) -> usize {
+        return self.column_x(self.slot_column(slot).0);
+    }
+
+    fn slots() -> Vec<Slot> {
+        let mut slots: Vec<Slot> = (1..25).map(Slot::Point).collect();
+        slots.push(Slot::Bar);
+        slots.push(Slot::Off);
+        return slots;
+    }
+
+    // The slot under a cell of the screen.
+    pub fn slot_at(&self, x: usize, y: usize) -> Option<Slot> {
+        if BOTTOM_ROW < y {
+            return None;
+        }
+        let top = y < MIDDLE_ROW;
+        return Tui::slots().into_iter().find(|&slot| {
+            let slot_x = self.slot_x(slot);
+            let in_half = slot == Slot::Bar || self.slot_column(slot).1 == top;
+            slot_x <= x && x < slot_x + 3 && in_half && (y != MIDDLE_ROW || slot == Slot::Bar)
+        });
+    }
+
+    // The next slot in the direction on the screen.
+    fn step(&self, input: Input) -> Slot {
+        let (_, top) = self.slot_column(self.cursor);
+        let x = self.slot_x(self.cursor);
+        let in_half = |top: bool| -> Vec<(usize, Slot)> {
+            let mut slots: Vec<(usize, Slot)> = Tui::slots().into_iter()
+                .filter(|&slot| self.slot_column(slot).1 == top)
+                .map(|slot| (self.slot_x(slot), slot)).collect();
+            slots.sort_by_key(|&(x, _)| x);
+            slots
+        };
+        return match input {
+            Input::Left => in_half(top).into_iter().rev().find(|&(slot_x, _)| slot_x < x)
+                .map(|(_, slot)| slot).unwrap_or(self.cursor),
+            Input::Right => in_half(top).into_iter().find(|&(slot_x, _)| x < slot_x)
+                .map(|(_, slot)| slot).unwrap_or(self.cursor),
+            // Down from the top half or up from the bottom half, to the nearest slot.
+            Input::Up | Input::Down if top == (input == Input::Down) => in_half(!top).into_iter()
+                .min_by_key(|&(slot_x, _)| (slot_x as isize - x as isize).abs())
+                .map(|(_, slot)| slot).unwrap_or(self.cursor),
+            _ => self.cursor,
+        };
+    }
+
+    // Start the human's turn with a roll.
+    pub fn start_turn(&mut self, roll: DiceRoll) {
+        let turn = TurnInput::new(&self.game, self.human, roll);
+        self.message = format!("you rolled {}-{}", roll.0, roll.1);
+        self.dice = Some((self.human, roll));
+        self.turn = Some(turn);
+        self.selected = None;
+        self.hints.clear();
+    }
+
+    // Play a move of either player and add it to the log.
+    pub fn play(&mut self, color: Color, roll: DiceRoll, mov: &Move) {
+        let text = if mov.submoves.is_empty() {
+            "cannot move".to_string()
+        } else {
+            format_move_from(self.human, &self.game, color, mov)
+        };
+        let entry = format!("{:>2}. {} {}{} {}", self.log.len() + 1, glyph(color), roll.0, roll.1, text);
+        self.log.push((color, entry));
+        self.game.do_move(color, mov);
+        self.turn = None;
+        self.selected = None;
+    }
+
+    pub fn handle(&mut self, input: Input) -> Outcome {
+        match input {
+            Input::Quit => return Outcome::Quit,
+            Input::Left | Input::Right | Input::Up | Input::Down => self.cursor = self.step(input),
+            Input::Select => return self.choose(self.cursor),
+            Input::Click(x, y) => {
+                if let Some(slot) = self.slot_at(x, y) {
+                    self.cursor = slot;
+                    return self.choose(slot);
+                }
+            },
+            Input::Cancel => self.selected = None,
+            Input::Undo => {
+                self.selected = None;
+                if let Some(ref mut turn) = self.turn {
+                    if turn.undo() {
+                        self.message = "took back the last checker".to_string();
+                    }
+                }
+            },
+            Input::Hint => {
+                if let Some(ref turn) = self.turn {
+                    self.hints = self.hint_player.hint(&turn.start, turn.roll, DEFAULT_HINTS);
+                }
+            },
+        }
+        return Outcome::Continue;
+    }
+
+    // Select the checkers on a slot, move the selected checker there, or finish the move.
+    fn choose(&mut self, slot: Slot) -> Outcome {
+        let pos = self.slot_position(slot);
+        let selected = self.selected.map(|selected| self.slot_position(selected));
+        let (complete, submove, can_move_from) = match self.turn {
+            Some(ref turn) => (turn.is_complete(),
+                               selected.and_then(|from| turn.submove_to(from, pos)),
+                               !turn.destinations(pos).is_empty()),
+            None => return Outcome::Continue,
+        };
+        if complete {
+            return Outcome::Move(self.turn.as_ref().map(|turn| turn.to_move()).unwrap());
+        }
+        if self.selected == Some(slot) {
+            self.selected = None;
+        } else if let Some(submove) = submove {
+            let turn = self.turn.as_mut().unwrap();
+            turn.play(submove);
+            self.selected = None;
+            self.message = if turn.is_complete() {
+                "press enter to play the move or u to take it back".to_string()
+            } else {
+                String::new()
+            };
+        } else if can_move_from {
+            self.selected = Some(slot);
+            self.message = String::new();
+        } else {
+            self.message = "no legal move from there".to_string();
+        }
+        return Outcome::Continue;
+    }
+
+    pub fn draw(&self) -> Canvas {
+        let mut canvas = Canvas::new(WIDTH, HEIGHT);
+        self.draw_board(&mut canvas);
+        self.draw_highlights(&mut canvas);
+        let game = self.position();
+        let status = format!("red X: {} pips, {} off   white O: {} pips, {} off",
+                             game.pip_count(Color::Red), game.get_board(Color::Red, BEARING_OFF_POS),
+                             game.pip_count(Color::White), game.get_board(Color::White, BEARING_OFF_POS));
+        canvas.put(0, STATUS_ROW, &status, Ink::Plain);
+        canvas.put(0, MESSAGE_ROW, &self.message, Ink::Title);
+        canvas.put(0, HINT_ROW, "Hints", Ink::Title);
+        for (i, hint) in self.hints.iter().enumerate() {
+            canvas.put(0, HINT_ROW + 1 + i, hint, Ink::Plain);
+        }
+        canvas.put(LOG_X, 0, "Moves", Ink::Title);
+        let shown = HELP_ROW - 1;
+        let first = self.log.len().saturating_sub(shown);
+        for (i, &(color, ref entry)) in self.log[first..].iter().enumerate() {
+            canvas.put(LOG_X, 1 + i, entry, ink(color));
+        }
+        canvas.put(0, HELP_ROW, HELP, Ink::Dim);
+        return canvas;
+    }
+
+    fn draw_board(&self, canvas: &mut Canvas) {
+        let game = self.position();
+        let (own, other) = (self.human, self.human.opposite());
+        for &row in [TOP_ROW, BOTTOM_ROW].iter() {
+            canvas.put(0, row, &"-".repeat(BOARD_WIDTH), Ink::Border);
+        }
+        for &x in BORDER_X.iter() {
+            let x = match self.orientation {
+                Orientation::HomeRight => x,
+                Orientation::HomeLeft => BOARD_WIDTH - 1 - x,
+            };
+            canvas.put(x, TOP_ROW, "+", Ink::Border);
+            canvas.put(x, BOTTOM_ROW, "+", Ink::Border);
+            for row in (TOP_ROW + 1)..BOTTOM_ROW {
+                canvas.put(x, row, "|", Ink::Border);
+            }
+        }
+        for point in 1..BEARING_OFF_POS {
+            let slot = Slot::Point(point);
+            let (_, top) = self.slot_column(slot);
+            let x = self.slot_x(slot);
+            let number = format!("{:->2}-", point);
+            canvas.put(x, if top { TOP_ROW } else { BOTTOM_ROW }, &number, Ink::Border);
+            let own_count = game.get_board(own, point_position(own, own, point));
+            let (color, count) = if 0 < own_count {
+                (own, own_count)
+            } else {
+                (other, game.get_board(other, point_position(own, other, point)))
+            };
+            if top {
+                self.draw_stack(canvas, x, TOP_ROW + 1, false, color, count);
+            } else {
+                self.draw_stack(canvas, x, BOTTOM_ROW - 1, true, color, count);
+            }
+        }
+        let bar_x = self.column_x(BAR_COLUMN);
+        canvas.put(bar_x, MIDDLE_ROW, "BAR", Ink::Border);
+        // The human's checkers on the bar are above it, on the side where they enter.
+        self.draw_stack(canvas, bar_x, MIDDLE_ROW - 1, true, own, game.get_board(own, BAR_POS));
+        self.draw_stack(canvas, bar_x, MIDDLE_ROW + 1, false, other, game.get_board(other, BAR_POS));
+        // Each player bears off into the tray beside its home board.
+        let tray_x = self.column_x(TRAY_COLUMN);
+        self.draw_stack(canvas, tray_x, TOP_ROW + 1, false, other,
+                        game.get_board(other, BEARING_OFF_POS));
+        self.draw_stack(canvas, tray_x, BOTTOM_ROW - 1, true, own,
+                        game.get_board(own, BEARING_OFF_POS));
+        if let Some((color, roll)) = self.dice {
+            let (dice, used) = match self.turn {
+                Some(ref turn) => (turn.dice(), turn.used_dice()),
+                None => (vec![roll.0, roll.1], vec![false, false]),
+            };
+            // The roller's dice are on its right.
+            let first = if color == self.human { BAR_COLUMN + 2 } else { 1 };
+            for (i, (die, used)) in dice.into_iter().zip(used).enumerate() {
+                let x = self.column_x(first + i);
+                canvas.put(x, MIDDLE_ROW, &format!("[{}]", die), if used { Ink::Dim } else { ink(color) });
+            }
+        }
+    }
+
+    // A stack of checkers from the row of its base, with the count on the fifth checker if
+    // there are more.
+    fn draw_stack(&self, canvas: &mut Canvas, x: usize, base: usize, upwards: bool, color: Color,
+                  count: Checker) {
+        let drawn = ::std::cmp::min(count.max(0) as usize, STACK_HEIGHT);
+        for height in 0..drawn {
+            let row = if upwards { base - height } else { base + height };
+            let text = if height + 1 == STACK_HEIGHT && STACK_HEIGHT < count as usize {
+                format!("{:>2} ", count)
+            } else {
+                format!(" {} ", glyph(color))
+            };
+            canvas.put(x, row, &text, ink(color));
+        }
+    }
+
+    // The cursor, the selected checkers and where they can go, during the human's turn.
+    fn draw_highlights(&self, canvas: &mut Canvas) {
+        let turn = match self.turn {
+            Some(ref turn) => turn,
+            None => return,
+        };
+        let mut papers = vec![(self.cursor, Paper::Cursor)];
+        if let Some(selected) = self.selected {
+            for pos in turn.destinations(self.slot_position(selected)) {
+                papers.push((self.position_slot(pos), Paper::Target));
+            }
+            papers.push((selected, Paper::Selected));
+        }
+        for (slot, paper) in papers {
+            let x = self.slot_x(slot);
+            let rows = match slot {
+                Slot::Bar => (TOP_ROW + 1)..BOTTOM_ROW,
+                _ if self.slot_column(slot).1 => TOP_ROW..MIDDLE_ROW,
+                _ => (MIDDLE_ROW + 1)..(BOTTOM_ROW + 1),
+            };
+            for row in rows {
+                canvas.paint(x, row, 3, paper);
+            }
+        }
+    }
+}
+
+fn input(event: Event) -> Option<Input> {
+    return match event {
+        Event::Key(Key::Left) | Event::Key(Key::Char('a')) => Some(Input::Left),
+        Event::Key(Key::Right) | Event::Key(Key::Char('d')) => Some(Input::Right),
+        Event::Key(Key::Up) | Event::Key(Key::Char('w')) => Some(Input::Up),
+        Event::Key(Key::Down) | Event::Key(Key::Char('s')) => Some(Input::Down),
+        Event::Key(Key::Char('\n')) | Event::Key(Key::Char(' ')) => Some(Input::Select),
+        Event::Key(Key::Esc) => Some(Input::Cancel),
+        Event::Key(Key::Char('u')) | Event::Key(Key::Backspace) => Some(Input::Undo),
+        Event::Key(Key::Char('h')) => Some(Input::Hint),
+        Event::Key(Key::Char('q')) | Event::Key(Key::Ctrl('c')) => Some(Input::Quit),
+        Event::Mouse(MouseEvent::Press(MouseButton::Left, x, y)) =>
+            Some(Input::Click((x as usize).saturating_sub(1), (y as usize).saturating_sub(1))),
+        _ => None,
+    };
+}
+
+fn write_ink<W: Write>(out: &mut W, ink: Ink) -> io::Result<()> {
+    return match ink {
+        Ink::Plain => Ok(()),
+        Ink::Border => write!(out, "{}", color::Fg(color::Yellow)),
+        Ink::Red => write!(out, "{}{}", style::Bold, color::Fg(color::LightRed)),
+        Ink::White => write!(out, "{}{}", style::Bold, color::Fg(color::LightWhite)),
+        Ink::Dim => write!(out, "{}", color::Fg(color::LightBlack)),
+        Ink::Title => write!(out, "{}", style::Bold),
+    };
+}
+
+fn write_paper<W: Write>(out: &mut W, paper: Paper) -> io::Result<()> {
+    return match paper {
+        Paper::Plain => Ok(()),
+        Paper::Cursor => write!(out, "{}", color::Bg(color::Blue)),
+        Paper::Selected => write!(out, "{}", color::Bg(color::Magenta)),
+        Paper::Target => write!(out, "{}", color::Bg(color::Green)),
+    };
+}
+
+// Copy the canvas to the terminal.
+fn show<W: Write>(out: &mut W, canvas: &Canvas) -> io::Result<()> {
+    for y in 0..canvas.height {
+        write!(out, "{}{}", cursor::Goto(1, y as u16 + 1), style::Reset)?;
+        let mut last = None;
+        for x in 0..canvas.width {
+            let cell = canvas.cell(x, y);
+            if last != Some((cell.ink, cell.paper)) {
+                write!(out, "{}", style::Reset)?;
+                write_ink(out, cell.ink)?;
+                write_paper(out, cell.paper)?;
+                last = Some((cell.ink, cell.paper));
+            }
+            write!(out, "{}", cell.ch)?;
+        }
+    }
+    write!(out, "{}", style::Reset)?;
+    return out.flush();
+}
+
+// Tumble the dice before showing the roll.
+fn roll_dice<W: Write, D: Dice>(out: &mut W, tui: &mut Tui, color: Color, dice: &mut D)
+    -> io::Result<DiceRoll> {
+    let roll = dice.roll();
+    let mut rng = rand::thread_rng();
+    for _ in 0..ROLL_FRAMES {
+        tui.dice = Some((color, (rng.gen_range(1, 7), rng.gen_range(1, 7))));
+        show(out, &tui.draw())?;
+        thread::sleep(Duration::from_millis(FRAME_TIME));
+    }
+    tui.dice = Some((color, roll));
+    return Ok(roll);
+}
+
+fn play_game<W: Write, T: Player, D: Dice, I: Iterator<Item = io::Result<Event>>>(
+    out: &mut W, tui: &mut Tui, opponent: &T, dice: &mut D, events: &mut I)
+    -> io::Result<GameRecord> {
+    let mut record = GameRecord::new(&tui.game);
+    let mut color = Color::Red;
+    loop {
+        if let Some(winner) = tui.game.winner() {
+            record.winner = Some(winner);
+            tui.message = format!("{} wins {} points, press any key", winner,
+                                  tui.game.points_won(winner));
+            show(out, &tui.draw())?;
+            events.next();
+            return Ok(record);
+        }
+        tui.message = format!("{} to roll", color);
+        let roll = roll_dice(out, tui, color, dice)?;
+        let mov = if tui.game.list_moves(color, roll).is_empty() {
+            tui.message = format!("{} rolled {}-{} and cannot move", color, roll.0, roll.1);
+            show(out, &tui.draw())?;
+            thread::sleep(Duration::from_millis(PAUSE_TIME));
+            Move { submoves: Vec::new() }
+        } else if color == tui.human {
+            tui.start_turn(roll);
+            loop {
+                show(out, &tui.draw())?;
+                let event = match events.next() {
+                    Some(event) => event?,
+                    None => return Ok(record),
+                };
+                match input(event).map(|input| tui.handle(input)) {
+                    Some(Outcome::Move(mov)) => break mov,
+                    Some(Outcome::Quit) => return Ok(record),
+                    _ => (),
+                }
+            }
+        } else {
+            tui.message = format!("{} rolled {}-{} and is thinking", color, roll.0, roll.1);
+            show(out, &tui.draw())?;
+            let mov = tui.game.legal_move_of(opponent, roll);
+            tui.message = format!("{} rolled {}-{}", color, roll.0, roll.1);
+            mov
+        };
+        tui.play(color, roll, &mov);
+        record.turns.push(Turn { color: color, roll: roll, mov: mov });
+        if color != tui.human {
+            show(out, &tui.draw())?;
+            thread::sleep(Duration::from_millis(PAUSE_TIME));
+        }
+        color = color.opposite();
+    }
+}
+
+// Play a game against the opponent in the terminal, Red moving first, and return the
+// record of the game, which has no winner if the human quit.
+pub fn play<T: Player, D: Dice>(human: Color, opponent: &T, orientation: Orientation,
+                               dice: &mut D) -> io::Result<GameRecord> {
+    let mut game: Backgammon = Default::default();
+    game.init();
+    let mut tui = Tui::new(&game, human, orientation);
+    let stdin = io::stdin();
+    let mut events = stdin.lock().events();
+    let mut out = AlternateScreen::from(MouseTerminal::from(io::stdout().into_raw_mode()?));
+    write!(out, "{}{}", cursor::Hide, termion::clear::All)?;
+    let record = play_game(&mut out, &mut tui, opponent, dice, &mut events);
+    write!(out, "{}", cursor::Show)?;
+    out.flush()?;
+    return record;
+}
@@ -0,0 +1,520 @@
+/*
+ * Rustgammon
+ *
+ * Local web interface
+ * Serve a board to a browser on this machine so that a human can play a computer player
+ * with the mouse. The page is embedded in the binary. It asks for the state of the game by
+ * long polling: a request for the state is answered when the state has changed from the
+ * version the page has, or after a timeout. The page sends the points the human clicks,
+ * and the server checks the submoves against the legal moves as the terminal interface
+ * does. Requests are only answered for the host names of this machine and from the page
+ * itself, so that other sites open in the browser can't play. The server listens on the
+ * loopback addresses of IPv4 and, where there is one, IPv6. The computer player moves on
+ * the thread that started the server, and every connection is served on a thread of its
+ * own.
+ * The board is the SVG diagram seen from the human's side, with the area of each point
+ * sent along so that the page can find the point clicked. Points are numbered for the
+ * human, with its bar as 25 and its tray as 0.
+ *
+ * @author ryutaroikeda94@gmail.com
+ *
+ */
+
+use std::io;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::sync::Arc;
+use std::sync::Condvar;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+
+use gamerecord::GameRecord;
+use gamerecord::Turn;
+use notation::format_move_from;
+use notation::point_position;
+use rustgammon::Backgammon;
+use rustgammon::BEARING_OFF_POS;
+use rustgammon::Color;
+use rustgammon::Dice;
+use rustgammon::DiceRoll;
+use rustgammon::Move;
+use rustgammon::Player;
+use rustgammon::Position;
+use rustgammon::RandomDice;
+use svg::Diagram;
+use svg::SvgRenderer;
+use tui::TurnInput;
+
+pub type WebError = String;
+
+pub const DEFAULT_PORT: u16 = 8080;
+
+// How long a request for the state waits for a change.
+const POLL_TIMEOUT: Duration = Duration::from_secs(25);
+// The longest request body read, which is plenty for a point number.
+const MAX_BODY: usize = 1024;
+// How long a connection may take to send its request or to read the response.
+const IO_TIMEOUT: Duration = Duration::from_secs(10);
+// The host names the server answers to, so that other sites can't reach it through the
+// browser.
+const LOCAL_HOSTS: [&str; 3] = ["localhost", "127.0.0.1", "[::1]"];
+
+const PAGE: &str = include_str!("../static/index.html");
+
+// A game between the human and the computer as the page shows it.
+pub struct Session {
+    pub game: Backgammon,
+    pub human: Color,
+    pub to_move: Color,
+    // The roll of the player to move, or none before the dice are rolled.
+    pub roll: Option<DiceRoll>,
+    // The submoves the human has played with its roll.
+    pub turn: Option<TurnInput>,
+    // The position of the human's selected checker.
+    pub selected: Option<Position>,
+    pub log: Vec<String>,
+    pub message: String,
+    pub record: GameRecord,
+    // The number of the game, which tells a computer move from an abandoned game.
+    pub game_number: u64,
+    // Counts the changes, so that the page can wait for the next.
+    pub version: u64,
+}
+
+// The session with the signal of its changes.
+pub struct Shared {
+    pub session: Mutex<Session>,
+    pub changed: Condvar,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub query: String,
+    pub host: Option<String>,
+    pub origin: Option<String>,
+    pub body: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Response {
+    pub status: &'static str,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+// The text as a JSON string.
+pub fn json_string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    return json;
+}
+
+impl Session {
+    pub fn new(human: Color) -> Session {
+        let mut game: Backgammon = Default::default();
+        game.init();
+        return Session {
+            game: game,
+            human: human,
+            to_move: Color::Red,
+            roll: None,
+            turn: None,
+            selected: None,
+            log: Vec::new(),
+            message: String::new(),
+            record: GameRecord::new(&game),
+            game_number: 0,
+            version: 0,
+        };
+    }
+
+    // Start a new game, keeping the version counting.
+    pub fn restart(&mut self) {
+        let (game_number, version) = (self.game_number, self.version);
+        *self = Session::new(self.human);
+        self.game_number = game_number + 1;
+        self.version = version;
+        self.changed();
+    }
+
+    fn changed(&mut self) {
+        self.version += 1;
+    }
+
+    pub fn is_over(&self) -> bool {
+        return self.game.winner().is_some();
+    }
+
+    // Whether the human may click.
+    pub fn is_human_turn(&self) -> bool {
+        return self.turn.is_some();
+    }
+
+    // Roll for the player to move, passing its turn if it can't move.
+    pub fn begin_turn(&mut self, roll: DiceRoll) {
+        let color = self.to_move;
+        self.roll = Some(roll);
+        if self.game.list_moves(color, roll).is_empty() {
+            self.finish_turn(Move { submoves: Vec::new() });
+            return;
+        }
+        if color == self.human {
+            self.turn = Some(TurnInput::new(&self.game, color, roll));
+            self.message = format!("you rolled {}-{}", roll.0, roll.1);
+        } else {
+            self.message = format!("{} rolled {}-{}", color, roll.0, roll.1);
+        }
+        self.changed();
+    }
+
+    // Play the move of the player to move with its roll and pass the dice.
+    pub fn finish_turn(&mut self, mov: Move) {
+        let (color, roll) = match self.roll {
+            Some(roll) => (self.to_move, roll),
+            None => return,
+        };
+        let text = if mov.submoves.is_empty() {
+            "cannot move".to_string()
+        } else {
+            format_move_from(self.human, &self.game, color, &mov)
+        };
+        self.log.push(format!("{} {}-{}: {}", color, roll.0, roll.1, text));
+        self.game.do_move(color, &mov);
+        self.record.turns.push(Turn { color: color, roll: roll, mov: mov });
+        self.to_move = color.opposite();
+        self.roll = None;
+        self.turn = None;
+        self.selected = None;
+        if let Some(winner) = self.game.winner() {
+            self.record.winner = Some(winner);
+            self.message = format!("{} wins {} points", winner, self.game.points_won(winner));
+        }
+        self.changed();
+    }
+
+    // Select the checkers on a point of the human or move the selected checker there.
+    pub fn click(&mut self, point: usize) {
+        let pos = point_position(self.human, self.human, point);
+        let (submove, can_move_from) = match self.turn {
+            Some(ref turn) => (self.selected.and_then(|from| turn.submove_to(from, pos)),
+                               !turn.destinations(pos).is_empty()),
+            None => return,
+        };
+        if self.selected == Some(pos) {
+            self.selected = None;
+        } else if let Some(submove) = submove {
+            let turn = self.turn.as_mut().unwrap();
+            turn.play(submove);
+            self.selected = None;
+            self.message = if turn.is_complete() {
+                "play the move or undo it".to_string()
+            } else {
+                String::new()
+            };
+        } else if can_move_from {
+            self.selected = Some(pos);
+            self.message = String::new();
+        } else {
+            self.message = "no legal move from there".to_string();
+        }
+        self.changed();
+    }
+
+    pub fn undo(&mut self) {
+        self.selected = None;
+        if let Some(ref mut turn) = self.turn {
+            turn.undo();
+        }
+        self.changed();
+    }
+
+    // Play the human's move if its submoves make a legal move.
+    pub fn play(&mut self) {
+        let mov = match self.turn {
+            Some(ref turn) if turn.is_complete() => turn.to_move(),
+            _ => return,
+        };
+        self.finish_turn(mov);
+    }
+
+    // The state of the game as the page reads it.
+    pub fn to_json(&self) -> String {
+        let renderer = SvgRenderer { perspective: self.human, ..Default::default() };
+        let position = match self.turn {
+            Some(ref turn) => turn.position,
+            None => self.game,
+        };
+        let diagram = Diagram {
+            position: position,
+            dice: self.roll.map(|roll| (self.to_move, roll)),
+            cube: None,
+        };
+        let mut hotspots = Vec::new();
+        for point in 0..(BEARING_OFF_POS + 1) {
+            let pos = point_position(self.human, self.human, point);
+            let state = match self.turn {
+                Some(_) if self.selected == Some(pos) => "selected",
+                Some(ref turn) if self.selected.is_some_and(|from| turn.submove_to(from, pos).is_some()) =>
+                    "target",
+                Some(ref turn) if self.selected.is_none() && !turn.destinations(pos).is_empty() => "movable",
+                _ => "",
+            };
+            let (x, y, width, height) = renderer.point_area(point);
+            hotspots.push(format!("{{\"point\":{},\"x\":{},\"y\":{},\"width\":{},\"height\":{},\
+                                   \"state\":\"{}\"}}", point, x, y, width, height, state));
+        }
+        let log: Vec<String> = self.log.iter().map(|entry| json_string(entry)).collect();
+        let can_play = self.turn.as_ref().is_some_and(|turn| turn.is_complete());
+        let can_undo = self.turn.as_ref().is_some_and(|turn| !turn.played.is_empty());
+        let winner = match self.game.winner() {
+            Some(winner) => json_string(&winner.to_string()),
+            None => "null".to_string(),
+        };
+        return format!("{{\"version\":{},\"human\":\"{}\",\"toMove\":\"{}\",\"turns\":{},\
+                        \"svg\":{},\"hotspots\":[{}],\"message\":{},\"log\":[{}],\
+                        \"canPlay\":{},\"canUndo\":{},\"winner\":{}}}",
+                       self.version, self.human, self.to_move, self.record.turns.len(),
+                       json_string(&renderer.render(&diagram)), hotspots.join(","),
+                       json_string(&self.message), log.join(","), can_play, can_undo, winner);
+    }
+}
+
+impl Shared {
+    pub fn new(human: Color) -> Shared {
+        return Shared { session: Mutex::new(Session::new(human)), changed: Condvar::new() };
+    }
+
+    // Change the session and wake the threads waiting for a change.
+    fn update<F: FnOnce(&mut Session)>(&self, change: F) {
+        let mut session = self.session.lock().unwrap();
+        change(&mut session);
+        self.changed.notify_all();
+    }
+
+    // The state once its version differs from the known version, or after the timeout.
+    fn wait_for_change(&self, known: u64, timeout: Duration) -> String {
+        let deadline = Instant::now() + timeout;
+        let mut session = self.session.lock().unwrap();
+        while session.version == known {
+            let now = Instant::now();
+            if deadline <= now {
+                break;
+            }
+            session = self.changed.wait_timeout(session, deadline - now).unwrap().0;
+        }
+        return session.to_json();
+    }
+}
+
+fn parse_version(query: &str) -> Option<u64> {
+    return query.split('&').filter_map(|pair| {
+        let mut parts = pair.splitn(2, '=');
+        match (parts.next(), parts.next()) {
+            (Some("version"), Some(value)) => value.parse::<u64>().ok(),
+            _ => None,
+        }
+    }).next();
+}
+
+// Read a request line, the headers and the body of the length they give.
+pub fn read_request<R: BufRead>(reader: &mut R) -> Result<Request, WebError> {
+    let mut line = String::new();
+    reader.read_line(&mut line).map_err(|e| e.to_string())?;
+    let mut parts = line.split_whitespace();
+    let (method, target) = match (parts.next(), parts.next()) {
+        (Some(method), Some(target)) => (method.to_string(), target),
+        _ => return Err(format!("bad request line {:?}", line.trim_end())),
+    };
+    let (path, query) = match target.find('?') {
+        Some(i) => (target[..i].to_string(), target[i + 1..].to_string()),
+        None => (target.to_string(), String::new()),
+    };
+    let mut length = 0;
+    let mut host = None;
+    let mut origin = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).map_err(|e| e.to_string())? == 0 {
+            break;
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(i) = header.find(':') {
+            let (name, value) = (&header[..i], header[i + 1..].trim());
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.parse::<usize>().map_err(|e| format!("content length: {}", e))?;
+            } else if name.eq_ignore_ascii_case("host") {
+                host = Some(value.to_string());
+            } else if name.eq_ignore_ascii_case("origin") {
+                origin = Some(value.to_string());
+            }
+        }
+    }
+    if MAX_BODY < length {
+        return Err(format!("body of {} bytes is too long", length));
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).map_err(|e| e.to_string())?;
+    let body = String::from_utf8(body).map_err(|e| e.to_string())?;
+    return Ok(Request { method: method, path: path, query: query, host: host, origin: origin,
+                        body: body });
+}
+
+fn ok(content_type: &'static str, body: String) -> Response {
+    return Response { status: "200 OK", content_type: content_type, body: body };
+}
+
+fn error(status: &'static str) -> Response {
+    return Response { status: status, content_type: "text/plain", body: status.to_string() };
+}
+
+// Whether the request comes from the page served here: the host must be this machine, and
+// a browser sending a request from another page gives that page as the origin.
+fn is_local(request: &Request) -> bool {
+    let host = match request.host {
+        Some(ref host) => host,
+        None => return false,
+    };
+    let name = match host.rfind(':') {
+        Some(i) if host[i + 1..].parse::<u16>().is_ok() => &host[..i],
+        _ => &host[..],
+    };
+    if !LOCAL_HOSTS.contains(&name) {
+        return false;
+    }
+    return match request.origin {
+        Some(ref origin) => *origin == format!("http://{}", host),
+        None => true,
+    };
+}
+
+pub fn handle(shared: &Shared, request: &Request) -> Response {
+    if !is_local(request) {
+        return error("403 Forbidden");
+    }
+    return match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/") => ok("text/html; charset=utf-8", PAGE.to_string()),
+        ("GET", "/state") => {
+            let json = match parse_version(&request.query) {
+                Some(known) => shared.wait_for_change(known, POLL_TIMEOUT),
+                None => shared.session.lock().unwrap().to_json(),
+            };
+            ok("application/json", json)
+        },
+        ("POST", "/click") => match request.body.trim().parse::<usize>() {
+            Ok(point) if point <= BEARING_OFF_POS => {
+                shared.update(|session| session.click(point));
+                ok("text/plain", "ok".to_string())
+            },
+            _ => error("400 Bad Request"),
+        },
+        ("POST", "/undo") => {
+            shared.update(|session| session.undo());
+            ok("text/plain", "ok".to_string())
+        },
+        ("POST", "/play") => {
+            shared.update(|session| session.play());
+            ok("text/plain", "ok".to_string())
+        },
+        ("POST", "/new") => {
+            shared.update(|session| session.restart());
+            ok("text/plain", "ok".to_string())
+        },
+        (_, "/") | (_, "/state") | (_, "/click") | (_, "/undo") | (_, "/play") | (_, "/new") =>
+            error("405 Method Not Allowed"),
+        _ => error("404 Not Found"),
+    };
+}
+
+pub fn write_response<W: Write>(out: &mut W, response: &Response) -> io::Result<()> {
+    write!(out, "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\n\
+                 Connection: close\r\n\r\n{}",
+           response.status, response.content_type, response.body.len(), response.body)?;
+    return out.flush();
+}
+
+fn serve_connection(shared: &Shared, stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let response = match read_request(&mut reader) {
+        Ok(request) => handle(shared, &request),
+        Err(e) => {
+            debug!("bad request: {}", e);
+            error("400 Bad Request")
+        },
+    };
+    let mut stream = stream;
+    return write_response(&mut stream, &response);
+}
+
+// Roll the dice and move for the computer whenever it is its turn, forever.
+fn drive<T: Player, D: Dice>(shared: &Shared, opponent: &T, dice: &mut D) {
+    loop {
+        let mut session = shared.session.lock().unwrap();
+        while session.is_over() || session.is_human_turn() {
+            session = shared.changed.wait(session).unwrap();
+        }
+        if session.roll.is_none() {
+            session.begin_turn(dice.roll());
+            shared.changed.notify_all();
+            continue;
+        }
+        let (game, roll, game_number) = (session.game, session.roll.unwrap(), session.game_number);
+        drop(session);
+        let mov = game.legal_move_of(opponent, roll);
+        shared.update(|session| {
+            // The human may have started a new game in the meantime.
+            if session.game_number == game_number {
+                session.finish_turn(mov);
+            }
+        });
+    }
+}
+
+// Serve the game on the listeners with the human playing the opponent's opposite colour.
+// This never returns unless a listener fails to start.
+pub fn serve<T: Player>(listeners: Vec<TcpListener>, opponent: &T) -> io::Result<()> {
+    let shared = Arc::new(Shared::new(opponent.get_color().opposite()));
+    for listener in listeners {
+        let accepting = shared.clone();
+        thread::Builder::new().name("web".to_string()).spawn(move || {
+            for stream in listener.incoming() {
+                let shared = accepting.clone();
+                match stream {
+                    Ok(stream) => {
+                        thread::spawn(move || {
+                            if let Err(e) = serve_connection(&shared, stream) {
+                                debug!("connection: {}", e);
+                            }
+                        });
+                    },
+                    Err(e) => debug!("accept: {}", e),
+                }
+            }
+        })?;
+    }
+    drive(&shared, opponent, &mut RandomDice);
+    return Ok(());
+}
@@ -0,0 +1,154 @@
+extern crate rustgammon;
+
+mod common;
+
+use std::io::BufReader;
+
+use rustgammon::analysis::*;
+use rustgammon::gamerecord::*;
+use rustgammon::race::CubeAction;
+use rustgammon::rustgammon::*;
+
+use common::PipEvaluator;
+
+// Red with a roll of 5-1 can hit the White blot on Red's 15 position, or run past it.
+fn hitting_position() -> Backgammon {
+    let mut game: Backgammon = Default::default();
+    game.set_board(Color::Red, 10, 2);
+    game.set_board(Color::Red, 24, 13);
+    game.set_board(Color::White, 10, 1);
+    game.set_board(Color::White, 24, 14);
+    return game;
+}
+
+fn running_move() -> Move {
+    // Submoves are a stack, so 10:1 is played first.
+    return Move { submoves: vec!(Submove { from: 11, die: 5 }, Submove { from: 10, die: 1 }) };
+}
+
+// Red is far ahead in the race, and doubles before its first turn.
+fn doubled_record(taken: bool) -> GameRecord {
+    let mut game: Backgammon = Default::default();
+    game.set_board(Color::Red, 24, 15);
+    game.set_board(Color::White, 19, 15);
+    let mut record = GameRecord::new(&game);
+    record.doubles.push(Double { turn: 0, color: Color::Red, taken: taken });
+    if taken {
+        record.turns.push(Turn {
+            color: Color::Red,
+            roll: (6, 6),
+            mov: Move { submoves: vec!(Submove { from: 24, die: 6 }; 4) },
+        });
+    } else {
+        record.winner = Some(Color::Red);
+    }
+    return record;
+}
+
+#[test]
+fn test_missed_hit_is_a_blunder() {
+    let analyzer = Analyzer::new(PipEvaluator);
+    let game = hitting_position();
+    let analysis = analyzer.analyze_move(&game, 0, Color::Red, (5, 1), &running_move());
+    assert!(1 < analysis.legal_moves);
+    assert_eq!(analysis.severity, Some(Severity::Blunder), "{}", analysis);
+    let &(ref best, best_equity) = analysis.candidates.first().unwrap();
+    let mut next = game;
+    next.do_move(Color::Red, best);
+    assert_eq!(next.get_board(Color::White, BAR_POS), 1);
+    assert!((best_equity - analysis.played_equity - analysis.error).abs() < 1e-9);
+    assert!(analysis.candidates.len() <= DEFAULT_CANDIDATES);
+}
+
+#[test]
+fn test_best_move_has_no_error() {
+    let analyzer = Analyzer::new(PipEvaluator);
+    let game = hitting_position();
+    let best = analyzer.analyze_move(&game, 0, Color::Red, (5, 1), &running_move())
+        .candidates[0].0.clone();
+    let analysis = analyzer.analyze_move(&game, 0, Color::Red, (5, 1), &best);
+    assert_eq!(analysis.error, 0.0);
+    assert_eq!(analysis.severity, None);
+}
+
+#[test]
+fn test_summary_counts_checker_errors() {
+    let mut record = GameRecord::new(&hitting_position());
+    record.turns.push(Turn { color: Color::Red, roll: (5, 1), mov: running_move() });
+    let analysis = Analyzer::new(PipEvaluator).analyze(&record);
+    assert_eq!(analysis.moves.len(), 1);
+    // A game without doubles is analysed without the cube unless asked.
+    assert!(analysis.cube_decisions.is_empty());
+    let red = analysis.summary(Color::Red);
+    assert_eq!(red.checker_decisions, 1);
+    assert_eq!(red.blunders, 1);
+    assert_eq!(red.error_rate(), analysis.moves[0].error);
+    assert_eq!(red.performance_rating(), 500.0 * red.error_rate());
+    assert_eq!(analysis.summary(Color::White).decisions(), 0);
+    assert_eq!(analysis.summary(Color::White).rating_name(), "awesome");
+}
+
+#[test]
+fn test_cube_is_analysed_when_asked_or_used() {
+    // The same game with the double left out, which counts as a missed double.
+    let mut record = doubled_record(true);
+    record.doubles.clear();
+    let mut analyzer = Analyzer::new(PipEvaluator);
+    assert!(analyzer.analyze(&record).cube_decisions.is_empty());
+    analyzer.analyze_cube = true;
+    assert!(!analyzer.analyze(&record).cube_decisions.is_empty());
+    analyzer.analyze_cube = false;
+    assert!(!analyzer.analyze(&doubled_record(true)).cube_decisions.is_empty());
+}
+
+#[test]
+fn test_wrong_take_is_a_taker_error() {
+    let analysis = Analyzer::new(PipEvaluator).analyze(&doubled_record(true));
+    let cube = &analysis.cube_decisions[0];
+    assert_eq!(cube.color, Color::Red);
+    assert_eq!(cube.decision.action, CubeAction::DoublePass, "{}", cube);
+    assert_eq!(cube.taken, Some(true));
+    assert!(cube.doubler_error < 1e-6, "{}", cube);
+    assert_eq!(cube.taker_severity, Some(Severity::Blunder), "{}", cube);
+    assert_eq!(analysis.summary(Color::White).cube_decisions, 1);
+    assert_eq!(analysis.summary(Color::White).blunders, 1);
+    // After the take White owns the cube, so Red cannot redouble before its second turn.
+    assert_eq!(analysis.moves.len(), 1);
+    assert!(analysis.cube_decisions.iter().all(|cube| cube.turn == 0 || cube.color == Color::White));
+}
+
+#[test]
+fn test_pass_ends_the_analysis() {
+    let analysis = Analyzer::new(PipEvaluator).analyze(&doubled_record(false));
+    assert!(analysis.moves.is_empty());
+    assert_eq!(analysis.cube_decisions.len(), 1);
+    let cube = &analysis.cube_decisions[0];
+    assert_eq!(cube.taken, Some(false));
+    assert_eq!(cube.taker_error, 0.0);
+    assert_eq!(analysis.summary(Color::White).total_error(), 0.0);
+}
+
+#[test]
+fn test_doubles_are_saved_in_records() {
+    for &taken in [true, false].iter() {
+        let record = doubled_record(taken);
+        let mut text = Vec::new();
+        record.write(&mut text).unwrap();
+        let read = GameRecord::read(BufReader::new(&text[..])).unwrap();
+        assert_eq!(read.doubles, record.doubles);
+        assert_eq!(read.turns.len(), record.turns.len());
+        assert_eq!(read.winner, record.winner);
+    }
+    assert!(GameRecord::read(BufReader::new(&b"double red drop\n"[..])).is_err());
+}
+
+#[test]
+fn test_moves_are_described_from_the_movers_side() {
+    let mut game: Backgammon = Default::default();
+    game.init();
+    let mov = Move { submoves: vec!(Submove { from: 19, die: 1 }, Submove { from: 17, die: 3 }) };
+    let analysis = Analyzer::new(PipEvaluator).analyze_move(&game, 1, Color::White, (3, 1), &mov);
+    assert!(analysis.to_string().starts_with("turn 2 white 3-1: 8/5 6/5 "), "{}", analysis);
+    assert_eq!(analysis.to_string(), analysis.describe(Color::White));
+    assert!(!analysis.describe(Color::Red).contains("8/5 6/5"));
+}
@@ -0,0 +1,197 @@
+extern crate rustgammon;
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use rustgammon::bearoff::*;
+use rustgammon::evaluator::Evaluator;
+use rustgammon::evaluator::ProbabilityEvaluator;
+use rustgammon::heuristicplayer::Heuristic;
+use rustgammon::rustgammon::*;
+
+fn bearoff_position(red: &HomeBoard, white: &HomeBoard) -> Backgammon {
+    let mut game: Backgammon = Default::default();
+    for point in 0..HOME_POINTS {
+        game.set_board(Color::Red, BEARING_OFF_POS - point - 1, red[point] as Checker);
+        game.set_board(Color::White, BEARING_OFF_POS - point - 1, white[point] as Checker);
+    }
+    return game;
+}
+
+#[test]
+fn test_position_index_is_a_bijection() {
+    let max_checkers = 4;
+    let count = count_positions(max_checkers, HOME_POINTS);
+    assert_eq!(count, 210);
+    let mut indices = HashSet::new();
+    for code in 0..5usize.pow(HOME_POINTS as u32) {
+        let mut board = [0; HOME_POINTS];
+        let mut rest = code;
+        for checkers in board.iter_mut() {
+            *checkers = (rest % 5) as u8;
+            rest /= 5;
+        }
+        if max_checkers < board.iter().map(|&c| c as usize).sum() {
+            continue;
+        }
+        let index = position_index(&board, max_checkers);
+        assert!(index < count);
+        assert!(indices.insert(index));
+    }
+    assert_eq!(indices.len(), count);
+    assert_eq!(position_index(&[0; HOME_POINTS], max_checkers), 0);
+}
+
+#[test]
+fn test_distributions() {
+    let database = OneSidedBearoff::generate(4);
+    assert_eq!(database.len(), 210);
+    // Two checkers on the one point always come off in one roll.
+    assert_eq!(database.expected_rolls(&[2, 0, 0, 0, 0, 0]), Some(1.0));
+    // A checker on the six point needs 6 pips, which 27 of the 36 rolls give.
+    let distribution = database.distribution(&[0, 0, 0, 0, 0, 1]).unwrap();
+    assert!((distribution[1] - 0.75).abs() < 1e-4);
+    assert!((distribution[2] - 0.25).abs() < 1e-4);
+    assert_eq!(database.distribution(&[0, 0, 0, 0, 0, 5]), None);
+    // Four checkers on the six point come off in one roll with 6-6 only.
+    let distribution = database.first_off_distribution(&[0, 0, 0, 0, 0, 4]).unwrap();
+    assert!((distribution[1] - 17.0 / 36.0).abs() < 1e-4);
+    let distribution = database.distribution(&[0, 0, 0, 0, 0, 4]).unwrap();
+    assert!((distribution[1] - 1.0 / 36.0).abs() < 1e-4);
+}
+
+#[test]
+fn test_race_probabilities() {
+    let database = OneSidedBearoff::generate(4);
+    // White is on roll and bears off its last checker.
+    let game = bearoff_position(&[0, 0, 0, 0, 0, 2], &[1, 0, 0, 0, 0, 0]);
+    let probabilities = database.probabilities(&game, Color::Red).unwrap();
+    assert!(probabilities.win < 1e-9);
+    // Red has borne off no checkers, so it loses a gammon.
+    assert!((probabilities.lose_gammon - 1.0).abs() < 1e-4);
+    assert!((probabilities.equity() + 2.0).abs() < 1e-3);
+
+    // White misses with a quarter of the rolls and Red then bears off with any roll.
+    let mut game = bearoff_position(&[1, 0, 0, 0, 0, 0], &[0, 0, 0, 0, 0, 1]);
+    game.set_board(Color::Red, BEARING_OFF_POS, 14);
+    game.set_board(Color::White, BEARING_OFF_POS, 14);
+    let probabilities = database.probabilities(&game, Color::Red).unwrap();
+    assert!((probabilities.win - 0.25).abs() < 1e-4);
+    assert_eq!(probabilities.win_gammon, 0.0);
+    assert_eq!(probabilities.lose_gammon, 0.0);
+
+    let mut game = bearoff_position(&[1, 0, 0, 0, 0, 0], &[1, 0, 0, 0, 0, 0]);
+    game.set_board(Color::White, 12, 1);
+    assert_eq!(database.probabilities(&game, Color::Red), None);
+}
+
+#[test]
+fn test_database_round_trip() {
+    let database = OneSidedBearoff::generate(3);
+    let mut data = Vec::new();
+    database.write(&mut data).unwrap();
+    let read = OneSidedBearoff::read(&mut &data[..]).unwrap();
+    assert_eq!(read.max_checkers, 3);
+    let board = [1, 0, 1, 0, 0, 1];
+    assert_eq!(read.distribution(&board), database.distribution(&board));
+    assert!(OneSidedBearoff::read(&mut &data[..data.len() - 1]).is_err());
+    assert!(OneSidedBearoff::read(&mut &b"bearoff 3\n"[..]).is_err());
+}
+
+#[test]
+fn test_bearoff_evaluator_falls_back() {
+    let databases = BearoffDatabases { one_sided: Some(OneSidedBearoff::generate(2)), two_sided: None };
+    let evaluator = BearoffEvaluator { databases: Arc::new(databases), evaluator: Heuristic::default() };
+    let mut game: Backgammon = Default::default();
+    game.init();
+    assert_eq!(evaluator.evaluate(&game, Color::Red), Heuristic::default().evaluate(&game, Color::Red));
+    let game = bearoff_position(&[0, 0, 0, 0, 0, 2], &[1, 0, 0, 0, 0, 0]);
+    assert!((evaluator.evaluate(&game, Color::Red) + 2.0).abs() < 1e-3);
+}
+
+#[test]
+fn test_bearoff_evaluator_probabilities() {
+    struct Even;
+    impl Evaluator for Even {
+        fn evaluate(&self, _: &Backgammon, _: Color) -> f64 {
+            return 0.0;
+        }
+    }
+    impl ProbabilityEvaluator for Even {
+        fn probabilities(&self, _: &Backgammon, _: Color) -> rustgammon::evaluator::Probabilities {
+            return rustgammon::evaluator::Probabilities { win: 0.5, ..Default::default() };
+        }
+    }
+    let databases = BearoffDatabases { one_sided: Some(OneSidedBearoff::generate(2)), two_sided: None };
+    let evaluator = BearoffEvaluator { databases: Arc::new(databases), evaluator: Even };
+    let game = bearoff_position(&[0, 0, 0, 0, 0, 2], &[1, 0, 0, 0, 0, 0]);
+    assert!(evaluator.probabilities(&game, Color::Red).win < 1e-9);
+    let mut game: Backgammon = Default::default();
+    game.init();
+    assert_eq!(evaluator.probabilities(&game, Color::Red).win, 0.5);
+}
+
+#[test]
+fn test_two_sided_win_probabilities() {
+    let database = TwoSidedBearoff::generate(3);
+    assert_eq!(database.len(), 84 * 84);
+    assert_eq!(database.win_probability(&[0, 0, 0, 0, 0, 1], &[1, 0, 0, 0, 0, 0]), Some(0.75));
+    assert_eq!(database.win_probability(&[2, 0, 0, 0, 0, 0], &[1, 0, 0, 0, 0, 0]), Some(1.0));
+    // If the six point checker misses it reaches the one, two or three point.
+    let p = database.win_probability(&[1, 0, 0, 0, 0, 0], &[0, 0, 0, 0, 0, 1]).unwrap();
+    assert_eq!(p, 1.0);
+    let p = database.win_probability(&[0, 0, 0, 0, 0, 1], &[0, 0, 0, 0, 0, 1]).unwrap();
+    assert!((p - (0.75 + 0.25 * 0.25)).abs() < 1e-6);
+    assert_eq!(database.win_probability(&[4, 0, 0, 0, 0, 0], &[1, 0, 0, 0, 0, 0]), None);
+}
+
+#[test]
+fn test_two_sided_is_close_to_one_sided() {
+    let two_sided = TwoSidedBearoff::generate(3);
+    let one_sided = OneSidedBearoff::generate(3);
+    let boards = [[0, 0, 1, 1, 0, 1], [2, 0, 0, 0, 1, 0], [0, 1, 0, 0, 0, 2], [1, 1, 1, 0, 0, 0]];
+    for red in &boards {
+        for white in &boards {
+            let mut game = bearoff_position(red, white);
+            game.set_board(Color::Red, BEARING_OFF_POS, 12);
+            game.set_board(Color::White, BEARING_OFF_POS, 12);
+            let exact = two_sided.probabilities(&game, Color::Red).unwrap();
+            let estimate = one_sided.probabilities(&game, Color::Red).unwrap();
+            assert!((exact.win - estimate.win).abs() < 0.03);
+            assert_eq!(exact.win_gammon, 0.0);
+        }
+    }
+}
+
+#[test]
+fn test_two_sided_needs_checkers_off() {
+    let database = TwoSidedBearoff::generate(2);
+    let game = bearoff_position(&[1, 0, 0, 0, 0, 0], &[1, 0, 0, 0, 0, 0]);
+    assert_eq!(database.probabilities(&game, Color::Red), None);
+}
+
+#[test]
+fn test_two_sided_round_trip() {
+    let database = TwoSidedBearoff::generate(2);
+    let mut data = Vec::new();
+    database.write(&mut data).unwrap();
+    let read = TwoSidedBearoff::read(&mut &data[..]).unwrap();
+    assert_eq!(read.max_checkers, 2);
+    let (a, b) = ([1, 0, 0, 1, 0, 0], [0, 0, 0, 0, 0, 1]);
+    assert_eq!(read.win_probability(&a, &b), database.win_probability(&a, &b));
+    assert!(TwoSidedBearoff::read(&mut &data[..data.len() - 4]).is_err());
+    let mut one_sided = Vec::new();
+    OneSidedBearoff::generate(2).write(&mut one_sided).unwrap();
+    assert!(TwoSidedBearoff::read(&mut &one_sided[..]).is_err());
+}
+
+#[test]
+fn test_bearoff_evaluator_prefers_two_sided() {
+    let databases = BearoffDatabases { one_sided: Some(OneSidedBearoff::generate(2)), two_sided: Some(TwoSidedBearoff::generate(2)) };
+    let evaluator = BearoffEvaluator { databases: Arc::new(databases), evaluator: Heuristic::default() };
+    let mut game = bearoff_position(&[0, 0, 0, 0, 1, 1], &[0, 0, 0, 1, 0, 1]);
+    game.set_board(Color::Red, BEARING_OFF_POS, 13);
+    game.set_board(Color::White, BEARING_OFF_POS, 13);
+    let exact = evaluator.databases.two_sided.as_ref().unwrap().probabilities(&game, Color::Red).unwrap();
+    assert_eq!(evaluator.databases.probabilities(&game, Color::Red), Some(exact));
+}
@@ -2,11 +2,12 @@ extern crate rustgammon;
 
 use rustgammon::rustgammon::*;
 use rustgammon::commandlineplayer::*;
+use rustgammon::heuristicplayer::Heuristic;
 
 #[test]
 fn test_parse_move_bear_off_first_and_move_other() {
     let mut game: Backgammon = Default::default();
-    let player = CommandLinePlayer { color: Color::White };
+    let player = CommandLinePlayer { perspective: Color::Red, ..CommandLinePlayer::new(Color::White) };
     game.white_board.set(24, 1);
     game.white_board.set(21, 1);
     let roll = (2, 3);
@@ -22,7 +23,7 @@ fn test_parse_move_bear_off_first_and_move_other() {
 #[test]
 fn test_parse_move_bear_off_second_and_move_other() {
     let mut game: Backgammon = Default::default();
-    let player = CommandLinePlayer { color: Color::White };
+    let player = CommandLinePlayer { perspective: Color::Red, ..CommandLinePlayer::new(Color::White) };
     game.white_board.set(24, 1);
     game.white_board.set(21, 1);
     let roll = (2, 3);
@@ -38,7 +39,7 @@ fn test_parse_move_bear_off_second_and_move_other() {
 #[test]
 fn test_parse_move_bear_off_two() {
     let mut game: Backgammon = Default::default();
-    let player = CommandLinePlayer { color: Color::White };
+    let player = CommandLinePlayer { perspective: Color::Red, ..CommandLinePlayer::new(Color::White) };
     game.white_board.set(24, 1);
     game.white_board.set(23, 1);
     let roll = (2, 3);
@@ -54,7 +55,7 @@ fn test_parse_move_bear_off_two() {
 #[test]
 fn test_parse_move_bear_off_four() {
     let mut game: Backgammon = Default::default();
-    let player = CommandLinePlayer { color: Color::White };
+    let player = CommandLinePlayer { perspective: Color::Red, ..CommandLinePlayer::new(Color::White) };
     game.white_board.set(24, 1);
     game.white_board.set(23, 3);
     let roll = (2, 2);
@@ -74,7 +75,7 @@ fn test_parse_move_bear_off_four() {
 #[test]
 fn test_bear_off_one_checker() {
     let mut game: Backgammon = Default::default();
-    let player = CommandLinePlayer { color: Color::Red };
+    let player = CommandLinePlayer::new(Color::Red);
     game.red_board.set(24, 1);
     let roll = (4, 5);
     let command = "1/off";
@@ -84,3 +85,72 @@ fn test_bear_off_one_checker() {
     assert_eq!(mov.submoves[0].die, 5);
 }
 
+
+#[test]
+fn test_hint_lines() {
+    let mut game: Backgammon = Default::default();
+    game.init();
+    let player = CommandLinePlayer::new(Color::Red);
+    let heuristic: Heuristic = Default::default();
+    let lines = player.hint_lines(&game, (3, 1), &heuristic, 3);
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with(" 1. 8/5 6/5 "), "{}", lines[0]);
+    assert!(lines[0].ends_with("(+0.000)"), "{}", lines[0]);
+    assert!(lines[1].starts_with(" 2. "));
+}
+
+#[test]
+fn test_hint_command() {
+    let mut game: Backgammon = Default::default();
+    game.init();
+    let player = CommandLinePlayer { perspective: Color::Red, ..CommandLinePlayer::new(Color::White) };
+    assert_eq!(player.parse_command(&game, (6, 5), "hint 2"), Err("".to_string()));
+    assert!(player.parse_command(&game, (6, 5), "hint two").unwrap_err().starts_with("hint"));
+    // The evaluator is loaded by the first hint and kept for the next.
+    assert!(player.network.get().is_some());
+    assert_eq!(player.hint(&game, (6, 5), 2).len(), 2);
+}
+
+#[test]
+fn test_heuristic_hint_is_labelled() {
+    let mut game: Backgammon = Default::default();
+    game.init();
+    let player = CommandLinePlayer::new(Color::Red);
+    assert!(player.network.set(Err("no weights".to_string())).is_ok());
+    let lines = player.hint(&game, (3, 1), 2);
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0], "heuristic scores, not equities:");
+    assert!(lines[1].starts_with(" 1. "));
+}
+
+#[test]
+fn test_cube_hint_command() {
+    let mut game: Backgammon = Default::default();
+    game.init();
+    let player = CommandLinePlayer::new(Color::Red);
+    assert_eq!(player.parse_command(&game, (2, 1), "cube hint"), Err("".to_string()));
+}
+
+#[test]
+fn test_parse_move_from_own_perspective() {
+    let mut game: Backgammon = Default::default();
+    let player = CommandLinePlayer::new(Color::White);
+    game.white_board.set(24, 1);
+    game.white_board.set(21, 1);
+    let mov = player.parse_command(&game, (2, 3), "4/1 1/off").unwrap();
+    assert_eq!(mov.submoves[1].from, 21);
+    assert_eq!(mov.submoves[1].die, 3);
+    assert_eq!(mov.submoves[0].from, 24);
+    assert_eq!(mov.submoves[0].die, 2);
+    assert!(player.parse_command(&game, (2, 3), "26/23").is_err());
+}
+
+#[test]
+fn test_hint_lines_from_own_perspective() {
+    let mut game: Backgammon = Default::default();
+    game.init();
+    let player = CommandLinePlayer::new(Color::White);
+    let heuristic: Heuristic = Default::default();
+    let lines = player.hint_lines(&game, (3, 1), &heuristic, 1);
+    assert!(lines[0].starts_with(" 1. 8/5 6/5 "), "{}", lines[0]);
+}
@@ -0,0 +1,42 @@
+/*
+ * Rustgammon
+ *
+ * Evaluators shared by the tests.
+ *
+ * @author ryutaroikeda94@gmail.com
+ *
+ */
+
+// Each test crate uses only some of the fixtures.
+#![allow(dead_code)]
+
+use rustgammon::evaluator::Evaluator;
+use rustgammon::evaluator::Probabilities;
+use rustgammon::evaluator::ProbabilityEvaluator;
+use rustgammon::rustgammon::*;
+
+// Wins by the pip count, where the opponent of the player who just moved is on roll and
+// so half a roll ahead.
+pub struct PipEvaluator;
+
+impl Evaluator for PipEvaluator {
+    fn evaluate(&self, game: &Backgammon, color: Color) -> f64 {
+        return self.probabilities(game, color).equity();
+    }
+}
+
+impl ProbabilityEvaluator for PipEvaluator {
+    fn probabilities(&self, game: &Backgammon, color: Color) -> Probabilities {
+        let lead = game.pip_count(color.opposite()) as f64 - game.pip_count(color) as f64 - 4.0;
+        return Probabilities { win: 1.0 / (1.0 + (-lead / 10.0).exp()), ..Default::default() };
+    }
+}
+
+// The pip lead of the player who has just moved, in hundreds of pips.
+pub struct PipLeadEvaluator;
+
+impl Evaluator for PipLeadEvaluator {
+    fn evaluate(&self, game: &Backgammon, color: Color) -> f64 {
+        return (game.pip_count(color.opposite()) as f64 - game.pip_count(color) as f64) / 100.0;
+    }
+}
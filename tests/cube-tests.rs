@@ -0,0 +1,76 @@
+extern crate rustgammon;
+
+use rustgammon::cube::*;
+use rustgammon::evaluator::Probabilities;
+use rustgammon::met::*;
+use rustgammon::race::CubeAction;
+use rustgammon::rustgammon::*;
+
+fn probabilities(win: f64, win_gammon: f64) -> Probabilities {
+    return Probabilities { win: win, win_gammon: win_gammon, ..Default::default() };
+}
+
+fn money(win: f64, win_gammon: f64) -> CubeDecision {
+    let cube: Cube = Default::default();
+    return money_decision(&probabilities(win, win_gammon), &cube, Color::Red,
+                          DEFAULT_CUBE_EFFICIENCY).unwrap();
+}
+
+#[test]
+fn test_money_decisions() {
+    let even = money(0.5, 0.0);
+    assert_eq!(even.action, CubeAction::NoDouble);
+    assert!(even.no_double.abs() < 1e-9);
+    assert!(even.is_take());
+
+    let double_take = money(0.72, 0.0);
+    assert_eq!(double_take.action, CubeAction::DoubleTake, "{}", double_take);
+    assert!((double_take.equity() - double_take.double_take).abs() < 1e-9);
+
+    let double_pass = money(0.85, 0.0);
+    assert_eq!(double_pass.action, CubeAction::DoublePass, "{}", double_pass);
+    assert_eq!(double_pass.equity(), 1.0);
+
+    let too_good = money(0.9, 0.6);
+    assert_eq!(too_good.action, CubeAction::NoDouble, "{}", too_good);
+    assert!(too_good.is_too_good());
+    assert!(1.0 < too_good.equity());
+    assert!(too_good.to_string().ends_with("too good, pass"));
+}
+
+#[test]
+fn test_money_cube_ownership() {
+    let probabilities = probabilities(0.6, 0.1);
+    let centered: Cube = Default::default();
+    let owned = Cube { value: 2, owner: Some(Color::Red) };
+    let centered_decision =
+        money_decision(&probabilities, &centered, Color::Red, DEFAULT_CUBE_EFFICIENCY).unwrap();
+    let owned_decision =
+        money_decision(&probabilities, &owned, Color::Red, DEFAULT_CUBE_EFFICIENCY).unwrap();
+    assert!(centered_decision.no_double < owned_decision.no_double);
+    assert!(money_decision(&probabilities, &owned, Color::White, DEFAULT_CUBE_EFFICIENCY).is_none());
+    // With a dead cube the equity is the cubeless equity.
+    let dead = money_decision(&probabilities, &centered, Color::Red, 0.0).unwrap();
+    assert!((dead.no_double - 0.3).abs() < 1e-9);
+    assert!((dead.double_take - 0.6).abs() < 1e-9);
+}
+
+#[test]
+fn test_match_decisions() {
+    let table = MatchEquityTable::generate(7, 0.2);
+    let cube: Cube = Default::default();
+    let crawford = MatchScore { away: 3, opponent_away: 1, crawford: true };
+    assert!(match_decision(&probabilities(0.5, 0.1), &cube, Color::Red, &crawford, &table).is_none());
+
+    // At 2-away 2-away a double puts the match on this game.
+    let score = MatchScore { away: 2, opponent_away: 2, crawford: false };
+    let decision = match_decision(&probabilities(0.7, 0.0), &cube, Color::Red, &score, &table).unwrap();
+    assert_eq!(decision.action, CubeAction::DoubleTake, "{}", decision);
+    assert!((decision.double_take - 1.0).abs() < 1e-9);
+
+    // The trailer doubles at once after the Crawford game.
+    let post_crawford = MatchScore { away: 3, opponent_away: 1, crawford: false };
+    let decision =
+        match_decision(&probabilities(0.5, 0.1), &cube, Color::Red, &post_crawford, &table).unwrap();
+    assert_eq!(decision.action, CubeAction::DoubleTake, "{}", decision);
+}
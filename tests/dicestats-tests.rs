@@ -0,0 +1,111 @@
+extern crate rustgammon;
+
+use rustgammon::dicestats::*;
+use rustgammon::gamerecord::*;
+use rustgammon::rustgammon::*;
+
+fn all_rolls() -> Vec<DiceRoll> {
+    let mut rolls = Vec::new();
+    for a in 1..7 {
+        for b in 1..7 {
+            rolls.push((a, b));
+        }
+    }
+    return rolls;
+}
+
+#[test]
+fn test_dice_stats_for_perfectly_even_rolls() {
+    let stats = DiceStats::new(&all_rolls());
+    assert_eq!(stats.rolls, 36);
+    assert_eq!(stats.faces, [12; 6]);
+    assert_eq!(stats.doubles, 6);
+    assert!(stats.face_chi_squared.abs() < 1e-9);
+    assert!(stats.roll_chi_squared.abs() < 1e-9);
+    assert!((stats.face_p_value - 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn test_dice_stats_runs() {
+    let rolls = vec!((1, 1), (2, 2), (2, 2), (3, 1), (1, 3), (4, 5));
+    let stats = DiceStats::new(&rolls);
+    assert_eq!(stats.longest_doubles_run, 3);
+    assert_eq!(stats.longest_non_doubles_run, 3);
+    // 3-1 and 1-3 are the same roll.
+    assert_eq!(stats.longest_repeated_roll_run, 2);
+}
+
+#[test]
+fn test_dice_stats_detects_loaded_dice() {
+    let rolls = vec!((6, 6); 100);
+    let stats = DiceStats::new(&rolls);
+    assert!(stats.face_p_value < 1e-6);
+    assert!(stats.roll_p_value < 1e-6);
+}
+
+#[test]
+fn test_chi_squared_p_value() {
+    // The 5% critical value for 5 degrees of freedom.
+    assert!((chi_squared_p_value(11.0705, 5) - 0.05).abs() < 1e-4);
+    // The 1% critical value for 20 degrees of freedom.
+    assert!((chi_squared_p_value(37.5662, 20) - 0.01).abs() < 1e-4);
+}
+
+#[test]
+fn test_parse_rolls() {
+    let rolls = parse_rolls("# rolls\n3-1 42,\n6-6\n").unwrap();
+    assert_eq!(rolls, vec!((3, 1), (4, 2), (6, 6)));
+    assert!(parse_rolls("7-1").is_err());
+}
+
+#[test]
+fn test_game_record_round_trip() {
+    let mut game: Backgammon = Default::default();
+    game.init();
+    let mut record = GameRecord::new(&game);
+    let mov = Move { submoves: vec!(Submove { from: 19, die: 1 }, Submove { from: 17, die: 3 }) };
+    let mut after = game;
+    after.do_move(Color::Red, &mov);
+    record.turns.push(Turn { color: Color::Red, roll: (3, 1), mov: mov });
+    let reply = after.list_moves(Color::White, (6, 6)).remove(0);
+    record.turns.push(Turn { color: Color::White, roll: (6, 6), mov: reply });
+    record.winner = Some(Color::White);
+    let mut text = Vec::new();
+    record.write(&mut text).unwrap();
+    let read = GameRecord::read(&text[..]).unwrap();
+    assert_eq!(read.turns.len(), 2);
+    assert_eq!(read.turns[0].mov, record.turns[0].mov);
+    assert_eq!(read.turns[1].roll, (6, 6));
+    assert_eq!(read.turns[1].mov, record.turns[1].mov);
+    assert_eq!(read.winner, Some(Color::White));
+    for pos in 0..BOARD_SIZE {
+        assert_eq!(read.start.get_board(Color::Red, pos), game.get_board(Color::Red, pos));
+    }
+}
+
+#[test]
+fn test_game_record_rejects_illegal_moves() {
+    let mut game: Backgammon = Default::default();
+    game.init();
+    let mut text = Vec::new();
+    GameRecord::new(&game).write(&mut text).unwrap();
+    text.extend_from_slice(b"turn red 3-1 1:6\n");
+    let error = GameRecord::read(&text[..]).err().unwrap();
+    assert!(error.starts_with("line 4: illegal move 1:6"), "{}", error);
+    assert!(GameRecord::read(&b"turn red 3-1 1:6\n"[..]).is_err());
+}
+
+#[test]
+fn test_count_jokers_counts_unlikely_hit() {
+    let mut game: Backgammon = Default::default();
+    game.red_board.set(1, 1);
+    // A white blot eleven pips away can only be hit by 6-5.
+    game.white_board.set(game.get_opposite_pos(12), 1);
+    let mut record = GameRecord::new(&game);
+    let mov = Move { submoves: vec!(Submove { from: 7, die: 5 }, Submove { from: 1, die: 6 }) };
+    record.turns.push(Turn { color: Color::Red, roll: (6, 5), mov: mov });
+    let jokers = count_jokers(&record);
+    assert_eq!(jokers.red_turns, 1);
+    assert_eq!(jokers.red_jokers, 1);
+    assert_eq!(jokers.white_jokers, 0);
+}
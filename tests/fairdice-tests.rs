@@ -0,0 +1,99 @@
+extern crate rustgammon;
+
+use rustgammon::fairdice::*;
+use rustgammon::gamerecord::*;
+use rustgammon::randomplayer::RandomPlayer;
+use rustgammon::rustgammon::*;
+
+fn play_fair_game(first_secret: &[u8], second_secret: &[u8]) -> GameRecord {
+    let mut game: Backgammon = Default::default();
+    game.init();
+    let first = RandomPlayer { color: Color::Red };
+    let second = RandomPlayer { color: Color::White };
+    let mut dice = FairDice::from_secrets(first_secret, second_secret);
+    return game.run_with_dice(&first, &second, &mut dice);
+}
+
+#[test]
+fn test_fair_dice_rolls_are_in_range() {
+    let mut dice = FairDice::from_secrets(b"red", b"white");
+    for _ in 0..1000 {
+        let (a, b) = dice.roll();
+        assert!((1..7).contains(&a));
+        assert!((1..7).contains(&b));
+    }
+}
+
+#[test]
+fn test_fair_dice_are_reproducible() {
+    let mut first = FairDice::from_secrets(b"red", b"white");
+    let mut second = FairDice::from_secrets(b"red", b"white");
+    let mut third = FairDice::from_secrets(b"redw", b"hite");
+    let mut differs = false;
+    for _ in 0..100 {
+        let roll = first.roll();
+        assert_eq!(roll, second.roll());
+        differs = differs || roll != third.roll();
+    }
+    assert!(differs);
+}
+
+#[test]
+fn test_verify_accepts_fair_game() {
+    let first_secret = generate_secret();
+    let second_secret = generate_secret();
+    let first_commitment = commit(&first_secret);
+    let second_commitment = commit(&second_secret);
+    let record = play_fair_game(&first_secret, &second_secret);
+    assert!(record.winner.is_some());
+    assert!(verify(&first_commitment, &first_secret,
+                   &second_commitment, &second_secret, &record).is_ok());
+}
+
+#[test]
+fn test_verify_rejects_wrong_secret() {
+    let first_secret = generate_secret();
+    let second_secret = generate_secret();
+    let first_commitment = commit(&first_secret);
+    let second_commitment = commit(&second_secret);
+    let record = play_fair_game(&first_secret, &second_secret);
+    let other_secret = generate_secret();
+    assert!(verify(&first_commitment, &other_secret,
+                   &second_commitment, &second_secret, &record).is_err());
+}
+
+#[test]
+fn test_verify_rejects_tampered_roll() {
+    let first_secret = generate_secret();
+    let second_secret = generate_secret();
+    let first_commitment = commit(&first_secret);
+    let second_commitment = commit(&second_secret);
+    let mut record = play_fair_game(&first_secret, &second_secret);
+    let (a, b) = record.turns[0].roll;
+    record.turns[0].roll = (a % 6 + 1, b);
+    assert!(verify(&first_commitment, &first_secret,
+                   &second_commitment, &second_secret, &record).is_err());
+}
+
+#[test]
+fn test_verify_replays_the_game() {
+    let first_secret = generate_secret();
+    let second_secret = generate_secret();
+    let first_commitment = commit(&first_secret);
+    let second_commitment = commit(&second_secret);
+    let check = |record: &GameRecord| {
+        return verify(&first_commitment, &first_secret, &second_commitment, &second_secret, record);
+    };
+    let record = play_fair_game(&first_secret, &second_secret);
+    let mut wrong_winner = record.clone();
+    wrong_winner.winner = wrong_winner.winner.map(Color::opposite);
+    assert!(check(&wrong_winner).unwrap_err().starts_with("the record gives the winner"));
+    let mut unfinished = record.clone();
+    unfinished.turns.pop();
+    unfinished.winner = None;
+    assert_eq!(check(&unfinished), Err("the game is not finished".to_string()));
+    // The first roll is kept but the move is replaced with one for the other player's dice.
+    let mut illegal = record.clone();
+    illegal.turns[0].mov = Move { submoves: vec!(Submove { from: 1, die: 6 }; 4) };
+    assert!(check(&illegal).unwrap_err().contains("illegal move"));
+}
@@ -0,0 +1,96 @@
+extern crate rustgammon;
+
+use std::sync::Arc;
+
+use rustgammon::bearoff::*;
+use rustgammon::heuristicplayer::*;
+use rustgammon::rustgammon::*;
+
+#[test]
+fn test_parse_reads_weights_and_comments() {
+    let heuristic = Heuristic::parse("# comment\npip_count: 2.5\n\nanchors: 0 # none\n").unwrap();
+    assert_eq!(heuristic.pip_count, 2.5);
+    assert_eq!(heuristic.anchors, 0.0);
+    let default: Heuristic = Default::default();
+    assert_eq!(heuristic.prime_length, default.prime_length);
+}
+
+#[test]
+fn test_parse_rejects_unknown_weight() {
+    assert!(Heuristic::parse("pips: 1.0").is_err());
+    assert!(Heuristic::parse("pip_count 1.0").is_err());
+    assert!(Heuristic::parse("pip_count: one").is_err());
+}
+
+#[test]
+fn test_default_config_matches_default_weights() {
+    let heuristic = Heuristic::load(DEFAULT_CONFIG_PATH).unwrap();
+    let default: Heuristic = Default::default();
+    assert_eq!(heuristic, default);
+}
+
+#[test]
+fn test_heuristic_player_makes_five_point_with_opening_three_one() {
+    let mut game: Backgammon = Default::default();
+    game.init();
+    let player = HeuristicPlayer::new(Color::Red);
+    let mov = player.make_move(&game, (3, 1));
+    game.do_move(Color::Red, &mov);
+    assert_eq!(game.get_board(Color::Red, 20), 2);
+    assert_eq!(game.get_board(Color::Red, 17), 2);
+    assert_eq!(game.get_board(Color::Red, 19), 4);
+}
+
+#[test]
+fn test_heuristic_player_hits_blot() {
+    let mut game: Backgammon = Default::default();
+    game.red_board.set(12, 2);
+    game.red_board.set(19, 2);
+    game.white_board.set(game.get_opposite_pos(16), 1);
+    game.white_board.set(1, 2);
+    let player = HeuristicPlayer::new(Color::Red);
+    let mov = player.make_move(&game, (4, 2));
+    game.do_move(Color::Red, &mov);
+    assert_eq!(game.get_board(Color::White, BAR_POS), 1);
+}
+
+#[test]
+fn test_evaluate_penalises_direct_shots() {
+    let heuristic: Heuristic = Default::default();
+    let mut safe: Backgammon = Default::default();
+    safe.red_board.set(10, 2);
+    safe.white_board.set(10, 1);
+    let mut exposed = safe;
+    exposed.red_board.set(10, 1);
+    exposed.red_board.set(11, 1);
+    assert!(heuristic.evaluate(&exposed, Color::Red) < heuristic.evaluate(&safe, Color::Red));
+}
+
+#[test]
+fn test_heuristic_player_uses_bearoff_database() {
+    // Red has one checker on each of its 1, 2 and 3 points and White two checkers left.
+    let mut game: Backgammon = Default::default();
+    for &pos in &[22, 23, 24] {
+        game.red_board.set(pos, 1);
+    }
+    game.red_board.set(BEARING_OFF_POS, 12);
+    game.white_board.set(20, 1);
+    game.white_board.set(21, 1);
+    game.white_board.set(BEARING_OFF_POS, 13);
+    let database = TwoSidedBearoff::generate(3);
+    let best = game.list_moves(Color::Red, (2, 1)).iter().map(|mov| {
+        let mut next = game;
+        next.do_move(Color::Red, mov);
+        database.probabilities(&next, Color::Red).unwrap().equity()
+    }).fold(f64::NEG_INFINITY, f64::max);
+    let equity = |player: &HeuristicPlayer| {
+        let mut next = game;
+        next.do_move(Color::Red, &player.make_move(&game, (2, 1)));
+        database.probabilities(&next, Color::Red).unwrap().equity()
+    };
+    // Without the databases, as by default, the heuristic misplays the bearoff.
+    assert!(equity(&HeuristicPlayer::new(Color::Red)) < best - 0.5);
+    let databases = BearoffDatabases { one_sided: None, two_sided: Some(TwoSidedBearoff::generate(3)) };
+    let player = HeuristicPlayer { bearoff: Arc::new(databases), ..HeuristicPlayer::new(Color::Red) };
+    assert_eq!(equity(&player), best);
+}
@@ -0,0 +1,87 @@
+extern crate rustgammon;
+
+mod common;
+
+use rustgammon::gamerecord::*;
+use rustgammon::luck::*;
+use rustgammon::perft::distinct_rolls;
+use rustgammon::rustgammon::*;
+
+use common::PipLeadEvaluator;
+
+// A race where both players have all their checkers on their bar points.
+fn race_position() -> Backgammon {
+    let mut game: Backgammon = Default::default();
+    game.set_board(Color::Red, 18, 15);
+    game.set_board(Color::White, 18, 15);
+    return game;
+}
+
+#[test]
+fn test_luck_averages_to_zero() {
+    let game = race_position();
+    let average = average_equity(&game, Color::Red, &PipLeadEvaluator);
+    let mut total = 0.0;
+    for (roll, ways) in distinct_rolls() {
+        total += ways as f64 * (roll_equity(&game, Color::Red, roll, &PipLeadEvaluator) - average);
+    }
+    assert!(total.abs() < 1e-9);
+    // Every roll moves its full pips, so the average is the mean roll of 49/6 pips.
+    assert!((average - 49.0 / 600.0).abs() < 1e-9);
+    assert!(average < roll_equity(&game, Color::Red, (6, 6), &PipLeadEvaluator));
+    assert!(roll_equity(&game, Color::Red, (2, 1), &PipLeadEvaluator) < average);
+}
+
+#[test]
+fn test_winning_rolls_are_worth_the_points() {
+    let mut game: Backgammon = Default::default();
+    game.set_board(Color::Red, 24, 1);
+    game.set_board(Color::Red, BEARING_OFF_POS, 14);
+    game.set_board(Color::White, 24, 14);
+    game.set_board(Color::White, BEARING_OFF_POS, 1);
+    for (roll, _) in distinct_rolls() {
+        assert_eq!(roll_equity(&game, Color::Red, roll, &PipLeadEvaluator), 1.0);
+    }
+    assert_eq!(average_equity(&game, Color::Red, &PipLeadEvaluator), 1.0);
+    // White is backgammoned with no checkers off and checkers in Red's home board.
+    game.set_board(Color::White, 24, 0);
+    game.set_board(Color::White, BEARING_OFF_POS, 0);
+    game.set_board(Color::White, 1, 15);
+    assert_eq!(roll_equity(&game, Color::Red, (1, 1), &PipLeadEvaluator), 3.0);
+}
+
+#[test]
+fn test_luck_adjusted_result() {
+    let mut record = GameRecord::new(&race_position());
+    record.turns.push(Turn {
+        color: Color::Red,
+        roll: (6, 6),
+        mov: Move { submoves: vec!(Submove { from: 18, die: 6 }; 4) },
+    });
+    record.doubles.push(Double { turn: 1, color: Color::White, taken: true });
+    record.turns.push(Turn {
+        color: Color::White,
+        roll: (2, 1),
+        mov: Move { submoves: vec!(Submove { from: 18, die: 2 }, Submove { from: 18, die: 1 }) },
+    });
+    let analysis = analyze_luck(&record, &PipLeadEvaluator);
+    assert_eq!(analysis.rolls.len(), 2);
+    let (red, white) = (&analysis.rolls[0], &analysis.rolls[1]);
+    assert_eq!((red.cube_value, white.cube_value), (1, 2));
+    assert!((red.luck - (red.equity - red.average)).abs() < 1e-9);
+    assert!((white.luck - 2.0 * (white.equity - white.average)).abs() < 1e-9);
+    assert!(0.0 < analysis.luck(Color::Red) && analysis.luck(Color::White) < 0.0);
+    assert_eq!(analysis.net_luck(Color::White), -analysis.net_luck(Color::Red));
+    assert_eq!(analysis.result, None);
+    assert_eq!(analysis.luck_adjusted_result(Color::Red), None);
+
+    // Red wins the doubled cube when White passes a redouble.
+    record.doubles.push(Double { turn: 2, color: Color::Red, taken: false });
+    record.winner = Some(Color::Red);
+    let analysis = analyze_luck(&record, &PipLeadEvaluator);
+    assert_eq!(analysis.result(Color::Red), Some(2.0));
+    assert_eq!(analysis.result(Color::White), Some(-2.0));
+    let adjusted = analysis.luck_adjusted_result(Color::Red).unwrap();
+    assert!((adjusted - (2.0 - analysis.net_luck(Color::Red))).abs() < 1e-9);
+    assert!(adjusted < 2.0);
+}
@@ -0,0 +1,94 @@
+extern crate rand;
+extern crate rustgammon;
+
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use rand::SeedableRng;
+use rand::XorShiftRng;
+
+use rustgammon::bearoff::*;
+use rustgammon::mctsplayer::*;
+use rustgammon::rustgammon::*;
+
+#[test]
+fn test_plays_legal_moves() {
+    let mut game: Backgammon = Default::default();
+    game.init();
+    let player = MctsPlayer::new(Color::White, Budget::Iterations(50));
+    for roll in &[(6, 5), (2, 2), (4, 1)] {
+        let mov = player.make_move(&game, *roll);
+        assert!(game.can_do_move(Color::White, *roll, &mov));
+    }
+}
+
+#[test]
+fn test_bears_off_to_win() {
+    // Bearing off both checkers wins before White bears off its last checker.
+    let mut game: Backgammon = Default::default();
+    game.red_board.set(23, 1);
+    game.red_board.set(24, 1);
+    game.red_board.set(BEARING_OFF_POS, 13);
+    game.white_board.set(24, 1);
+    game.white_board.set(BEARING_OFF_POS, 14);
+    let player = MctsPlayer::new(Color::Red, Budget::Iterations(200));
+    let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    let candidates = player.search(&game, (2, 1), &mut rng);
+    assert_eq!(candidates.len(), 2);
+    let total: u32 = candidates.iter().map(|&(_, visits)| visits).sum();
+    assert_eq!(total, 200);
+    let mov = player.make_move(&game, (2, 1));
+    let mut next = game;
+    next.do_move(Color::Red, &mov);
+    assert_eq!(next.winner(), Some(Color::Red));
+}
+
+#[test]
+fn test_forced_move_needs_no_search() {
+    let mut game: Backgammon = Default::default();
+    game.red_board.set(24, 1);
+    game.red_board.set(BEARING_OFF_POS, 14);
+    game.white_board.set(1, 15);
+    let player = MctsPlayer::new(Color::Red, Budget::Iterations(1000000));
+    let candidates = player.search(&game, (6, 5), &mut XorShiftRng::from_seed([1, 2, 3, 4]));
+    assert_eq!(candidates.len(), 1);
+}
+
+#[test]
+fn test_time_budget() {
+    let mut game: Backgammon = Default::default();
+    game.init();
+    let player = MctsPlayer::new(Color::Red, Budget::Time(Duration::from_millis(100)));
+    let start = Instant::now();
+    let mov = player.make_move(&game, (3, 1));
+    assert!(start.elapsed() < Duration::from_secs(2));
+    assert!(game.can_do_move(Color::Red, (3, 1), &mov));
+}
+
+#[test]
+fn test_scores_bearoff_leaves_with_the_database() {
+    // Red has one checker on each of its 1, 2 and 3 points and White two checkers left.
+    let mut game: Backgammon = Default::default();
+    for &pos in &[22, 23, 24] {
+        game.red_board.set(pos, 1);
+    }
+    game.red_board.set(BEARING_OFF_POS, 12);
+    game.white_board.set(20, 1);
+    game.white_board.set(21, 1);
+    game.white_board.set(BEARING_OFF_POS, 13);
+    let bearoff = Arc::new(BearoffDatabases { one_sided: None,
+                                              two_sided: Some(TwoSidedBearoff::generate(3)) });
+    let equity = |mov: &Move| {
+        let mut next = game;
+        next.do_move(Color::Red, mov);
+        bearoff.probabilities(&next, Color::Red).unwrap().equity()
+    };
+    let best = game.list_moves(Color::Red, (2, 1)).iter().map(&equity)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let player = MctsPlayer { bearoff: bearoff.clone(),
+                              ..MctsPlayer::new(Color::Red, Budget::Iterations(100)) };
+    let candidates = player.search(&game, (2, 1), &mut XorShiftRng::from_seed([1, 2, 3, 4]));
+    let (mov, _) = candidates.into_iter().max_by_key(|&(_, visits)| visits).unwrap();
+    assert_eq!(equity(&mov), best);
+}
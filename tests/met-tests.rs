@@ -0,0 +1,161 @@
+extern crate rustgammon;
+
+use rustgammon::met::*;
+
+#[test]
+fn test_generated_table_is_symmetric() {
+    let table = MatchEquityTable::generate(11, DEFAULT_GAMMON_RATE);
+    assert_eq!(table.max_away(), 11);
+    for away in 1..12 {
+        for opponent_away in 1..12 {
+            let sum = table.equity(away, opponent_away, false) +
+                table.equity(opponent_away, away, false);
+            assert!((sum - 1.0).abs() < 1e-9, "{}-away {}-away", away, opponent_away);
+        }
+        assert!((table.equity(away, away, false) - 0.5).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn test_crawford_and_post_crawford() {
+    let table = MatchEquityTable::generate(7, 0.2);
+    // The trailer at 2-away wins the Crawford game and then the next game, or a gammon.
+    assert!((table.equity(2, 1, false) - 0.3).abs() < 1e-9);
+    assert!((table.equity(1, 2, false) - 0.7).abs() < 1e-9);
+    assert!((table.equity(2, 1, true) - 0.5).abs() < 1e-9);
+    for away in 2..7 {
+        assert!(table.equity(away + 1, 1, true) <= table.equity(away, 1, true));
+        assert!(table.equity(away + 1, 1, false) < table.equity(away, 1, false));
+        assert!(table.equity(away, 1, false) < table.equity(away, 2, false));
+    }
+}
+
+#[test]
+fn test_equity_after_game() {
+    let table = MatchEquityTable::generate(7, 0.2);
+    let score = MatchScore { away: 3, opponent_away: 5, crawford: false };
+    assert_eq!(table.equity_after(&score, 4), 1.0);
+    assert_eq!(table.equity_after(&score, -8), 0.0);
+    assert_eq!(table.equity_after(&score, 2), table.equity(1, 5, false));
+    assert_eq!(table.equity_after(&score, -1), table.equity(3, 4, false));
+    let crawford = MatchScore { away: 1, opponent_away: 5, crawford: true };
+    assert_eq!(table.equity_after(&crawford, -2), table.equity(1, 3, true));
+}
+
+#[test]
+fn test_builtin_tables() {
+    for name in BUILTIN_TABLES.iter() {
+        let table = MatchEquityTable::builtin(name).unwrap();
+        assert_eq!(table.name, *name);
+        assert_eq!(table.max_away(), DEFAULT_MAX_AWAY);
+        for away in 1..26 {
+            for opponent_away in 1..26 {
+                let sum = table.equity(away, opponent_away, false) +
+                    table.equity(opponent_away, away, false);
+                assert!((sum - 1.0).abs() < 1e-9, "{} {}-away {}-away", name, away, opponent_away);
+                if away < opponent_away {
+                    assert!(0.5 < table.equity(away, opponent_away, false));
+                }
+            }
+        }
+    }
+    assert!(MatchEquityTable::builtin(DEFAULT_TABLE).is_none());
+    let live_cube = MatchEquityTable::builtin("live-cube").unwrap();
+    // The cube helps the trailer.
+    let cubeless = MatchEquityTable::builtin("cubeless").unwrap();
+    assert!(cubeless.equity(5, 3, false) < live_cube.equity(5, 3, false));
+    assert!((live_cube.equity(2, 3, false) - 0.6).abs() < 0.02);
+}
+
+#[test]
+fn test_published_tables() {
+    assert_eq!(MatchEquityTable::named("cubeless").unwrap().name, "cubeless");
+    assert!(MatchEquityTable::named("kazaross").is_err());
+    let default: MatchEquityTable = Default::default();
+    for &(name, path) in PUBLISHED_TABLES.iter() {
+        // The files of the published tables are installed separately.
+        let table = match MatchEquityTable::named(name) {
+            Ok(table) => table,
+            Err(e) => {
+                assert!(e.starts_with(path), "{}", e);
+                assert!(e.ends_with("(copy it from the met directory of GNU Backgammon)"), "{}", e);
+                continue;
+            },
+        };
+        assert_eq!(table.name, name);
+        assert_eq!(table.max_away(), DEFAULT_MAX_AWAY);
+        for away in 1..25 {
+            assert!((table.equity(away, away, false) - 0.5).abs() < 1e-3);
+            assert!(table.equity(away + 1, away, false) < 0.5);
+        }
+        if name == DEFAULT_TABLE {
+            assert_eq!(default, table);
+        }
+    }
+    if MatchEquityTable::named(DEFAULT_TABLE).is_err() {
+        assert_eq!(default.name, "live-cube");
+    }
+}
+
+#[test]
+fn test_take_points_and_gammon_values() {
+    let table: MatchEquityTable = Default::default();
+    let score = MatchScore { away: 2, opponent_away: 2, crawford: false };
+    // Passing leaves 2-away against 1-away, and taking plays for the match.
+    assert!((table.take_point(&score, 1) - table.equity(2, 1, false)).abs() < 1e-9);
+    let (win_gammon, lose_gammon) = table.gammon_values(&score, 1);
+    assert!((win_gammon - lose_gammon).abs() < 1e-9);
+    assert!(1.0 < win_gammon);
+    // A long match is close to money, where a dead cube has a take point of 25%.
+    let long = MatchScore { away: 25, opponent_away: 25, crawford: false };
+    let take_point = table.take_point(&long, 1);
+    assert!(0.2 < take_point && take_point < 0.3, "{}", take_point);
+    let (win_gammon, lose_gammon) = table.gammon_values(&long, 1);
+    assert!((win_gammon - 1.0).abs() < 0.1 && (lose_gammon - 1.0).abs() < 0.1);
+    // Gammons are worth nothing to the leader in the Crawford game.
+    let crawford = MatchScore { away: 1, opponent_away: 3, crawford: true };
+    assert_eq!(table.gammon_values(&crawford, 1).0, 0.0);
+}
+
+#[test]
+fn test_xml_round_trip() {
+    let table = MatchEquityTable::generate_live_cube(5, 0.25);
+    let mut xml = Vec::new();
+    table.write_xml(&mut xml).unwrap();
+    let parsed = MatchEquityTable::parse_xml(&String::from_utf8(xml).unwrap()).unwrap();
+    assert_eq!(parsed.name, table.name);
+    assert_eq!(parsed.max_away(), 5);
+    for away in 1..6 {
+        for opponent_away in 1..6 {
+            for &post_crawford in [false, true].iter() {
+                assert!((parsed.equity(away, opponent_away, post_crawford) -
+                         table.equity(away, opponent_away, post_crawford)).abs() < 1e-6);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_parse_gnubg_xml() {
+    let xml = "<?xml version=\"1.0\" ?>
+<match-equity-table>
+  <info><name>Test</name><length>2</length></info>
+  <pre-crawford-table type=\"explicit\">
+    <row><me>0.5</me><me>0.7</me></row>
+    <row><me>0.3</me><me>0.5</me></row>
+  </pre-crawford-table>
+  <post-crawford-table player=\"0\" type=\"explicit\">
+    <row><me>0.5</me><me>0.49</me><me>0.3</me></row>
+  </post-crawford-table>
+  <post-crawford-table player=\"both\" type=\"explicit\">
+    <row><me>0.5</me><me>0.48</me><me>0.31</me></row>
+  </post-crawford-table>
+</match-equity-table>";
+    let table = MatchEquityTable::parse_xml(xml).unwrap();
+    assert_eq!(table.name, "Test");
+    assert_eq!(table.equity(1, 2, false), 0.7);
+    assert_eq!(table.post_crawford, vec!(0.5, 0.48));
+    assert!(MatchEquityTable::parse_xml(&xml.replace("<me>0.3</me><me>0.5</me>", "<me>0.3</me>")).is_err());
+    assert!(MatchEquityTable::parse_xml(&xml.replace("type=\"explicit\"", "type=\"formula\"")).is_err());
+    assert!(MatchEquityTable::parse_xml("<match-equity-table/>").is_err());
+}
@@ -0,0 +1,102 @@
+extern crate rand;
+extern crate rustgammon;
+
+use rand::SeedableRng;
+use rand::XorShiftRng;
+
+use rustgammon::evaluator::*;
+use rustgammon::neuralnet::*;
+use rustgammon::rustgammon::*;
+
+fn seeded_net(hidden: usize) -> NeuralNet {
+    let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    return NeuralNet::random(hidden, &mut rng);
+}
+
+#[test]
+fn test_encode_opening_position() {
+    let mut game: Backgammon = Default::default();
+    game.init();
+    let inputs = encode(&game, Color::Red, true);
+    assert_eq!(inputs.len(), NUM_INPUTS);
+    // Two checkers on position 1.
+    assert_eq!(&inputs[0..4], &[1.0, 1.0, 0.0, 0.0]);
+    // Five checkers on position 12.
+    assert_eq!(&inputs[44..48], &[1.0, 1.0, 1.0, 1.0]);
+    // Nothing on the bar or borne off.
+    assert_eq!(&inputs[96..98], &[0.0, 0.0]);
+    // The side on roll.
+    assert_eq!(&inputs[NUM_INPUTS - 2..], &[1.0, 0.0]);
+    assert_eq!(&encode(&game, Color::Red, false)[NUM_INPUTS - 2..], &[0.0, 1.0]);
+    // The opening position is symmetric.
+    assert_eq!(encode(&game, Color::White, true), inputs);
+}
+
+#[test]
+fn test_encode_bar_and_off() {
+    let mut game: Backgammon = Default::default();
+    game.white_board.set(BAR_POS, 2);
+    game.white_board.set(BEARING_OFF_POS, 3);
+    let inputs = encode(&game, Color::Red, true);
+    assert_eq!(&inputs[98 + 96..98 + 98], &[1.0, 0.2]);
+}
+
+#[test]
+fn test_outputs_are_consistent_probabilities() {
+    let net = seeded_net(10);
+    let mut game: Backgammon = Default::default();
+    game.init();
+    let p = net.probabilities(&game, Color::Red);
+    assert!(0.0 <= p.win && p.win <= 1.0);
+    assert!(p.win_backgammon <= p.win_gammon && p.win_gammon <= p.win);
+    assert!(p.lose_backgammon <= p.lose_gammon && p.lose_gammon <= 1.0 - p.win);
+    assert_eq!(net.evaluate(&game, Color::Red), p.equity());
+}
+
+#[test]
+fn test_probabilities_equity_and_flip() {
+    let p = Probabilities {
+        win: 0.6,
+        win_gammon: 0.2,
+        win_backgammon: 0.05,
+        lose_gammon: 0.1,
+        lose_backgammon: 0.0,
+    };
+    assert!((p.equity() - 0.35).abs() < 1e-12);
+    assert!((p.flip().equity() + p.equity()).abs() < 1e-12);
+    assert_eq!(p.flip().flip(), p);
+}
+
+#[test]
+fn test_weights_round_trip() {
+    let net = seeded_net(7);
+    let mut text = Vec::new();
+    net.write(&mut text).unwrap();
+    let parsed = NeuralNet::parse(&String::from_utf8(text).unwrap()).unwrap();
+    assert_eq!(parsed, net);
+}
+
+#[test]
+fn test_parse_rejects_bad_weights() {
+    let net = seeded_net(3);
+    let mut text = Vec::new();
+    net.write(&mut text).unwrap();
+    let text = String::from_utf8(text).unwrap();
+    assert!(NeuralNet::parse("").is_err());
+    assert!(NeuralNet::parse(&text.replace("rustgammon-neuralnet", "other")).is_err());
+    // Missing the last weight.
+    let truncated = &text.trim_end()[..text.trim_end().rfind(' ').unwrap()];
+    assert!(NeuralNet::parse(truncated).is_err());
+}
+
+#[test]
+fn test_outputs_round_trip() {
+    let probabilities = Probabilities {
+        win: 0.6,
+        win_gammon: 0.2,
+        win_backgammon: 0.05,
+        lose_gammon: 0.1,
+        lose_backgammon: 0.01,
+    };
+    assert_eq!(to_probabilities(&to_outputs(&probabilities)), probabilities);
+}
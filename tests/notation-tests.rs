@@ -0,0 +1,50 @@
+extern crate rustgammon;
+
+use rustgammon::notation::*;
+use rustgammon::rustgammon::*;
+
+#[test]
+fn test_opening_moves() {
+    let mut game: Backgammon = Default::default();
+    game.init();
+    // Submoves are a stack, so the last one is played first.
+    let mov = Move { submoves: vec!(Submove { from: 19, die: 1 }, Submove { from: 17, die: 3 }) };
+    assert_eq!(format_move(&game, Color::Red, &mov), "8/5 6/5");
+    let mov = Move { submoves: vec!(Submove { from: 17, die: 3 }, Submove { from: 19, die: 1 }) };
+    assert_eq!(format_move(&game, Color::White, &mov), "19/20 17/20");
+}
+
+#[test]
+fn test_hits_bar_and_off() {
+    let mut game: Backgammon = Default::default();
+    game.red_board.set(BAR_POS, 1);
+    game.white_board.set(22, 1);
+    let mov = Move { submoves: vec!(Submove { from: 3, die: 2 }, Submove { from: BAR_POS, die: 3 }) };
+    assert_eq!(format_move(&game, Color::Red, &mov), "bar/22* 22/20");
+
+    let mut game: Backgammon = Default::default();
+    game.red_board.set(22, 2);
+    let mov = Move { submoves: vec!(Submove { from: 22, die: 6 }, Submove { from: 22, die: 3 }) };
+    assert_eq!(format_move(&game, Color::Red, &mov), "3/off 3/off");
+    assert_eq!(format_move(&game, Color::Red, &Move { submoves: Vec::new() }), "cannot move");
+}
+
+#[test]
+fn test_point_names() {
+    assert_eq!(point_name(Color::Red, 1), "24");
+    assert_eq!(point_name(Color::White, 1), "1");
+    assert_eq!(point_name(Color::White, BAR_POS), "bar");
+    assert_eq!(point_name(Color::White, BEARING_OFF_POS), "off");
+}
+
+#[test]
+fn test_point_names_from_either_perspective() {
+    assert_eq!(point_name_from(Color::White, Color::White, 1), "24");
+    assert_eq!(point_name_from(Color::White, Color::Red, 1), "1");
+    assert_eq!(point_number(Color::Red, Color::Red, 19), 6);
+    assert_eq!(point_position(Color::White, Color::White, 6), 19);
+    let mut game: Backgammon = Default::default();
+    game.init();
+    let mov = Move { submoves: vec!(Submove { from: 17, die: 3 }, Submove { from: 19, die: 1 }) };
+    assert_eq!(format_move_from(Color::White, &game, Color::White, &mov), "6/5 8/5");
+}
@@ -0,0 +1,136 @@
+extern crate rustgammon;
+
+use std::collections::HashSet;
+
+use rustgammon::fairdice::FairDice;
+use rustgammon::packed::*;
+use rustgammon::randomplayer::RandomPlayer;
+use rustgammon::rustgammon::*;
+
+// Positions from a few random games.
+fn sample_positions() -> Vec<(Backgammon, Color)> {
+    let mut positions = Vec::new();
+    for seed in 0..3u8 {
+        let mut game: Backgammon = Default::default();
+        game.init();
+        let start = game;
+        let first = RandomPlayer { color: Color::Red };
+        let second = RandomPlayer { color: Color::White };
+        let mut dice = FairDice::from_secrets(&[seed], b"packed");
+        let record = game.run_with_dice(&first, &second, &mut dice);
+        let mut replay = start;
+        for turn in &record.turns {
+            positions.push((replay, turn.color));
+            replay.do_move(turn.color, &turn.mov);
+        }
+    }
+    return positions;
+}
+
+fn all_rolls() -> Vec<DiceRoll> {
+    let mut rolls = Vec::new();
+    for a in 1..7 {
+        for b in a..7 {
+            rolls.push((a, b));
+        }
+    }
+    return rolls;
+}
+
+#[test]
+fn test_packed_position_round_trip() {
+    let mut game: Backgammon = Default::default();
+    game.init();
+    game.red_board.set(BEARING_OFF_POS, 15);
+    let packed = PackedPosition::new(&game);
+    let unpacked = packed.to_backgammon();
+    for pos in 0..BOARD_SIZE {
+        assert_eq!(unpacked.get_board(Color::Red, pos), game.get_board(Color::Red, pos));
+        assert_eq!(unpacked.get_board(Color::White, pos), game.get_board(Color::White, pos));
+        assert_eq!(packed.get(Color::White, pos), game.get_board(Color::White, pos));
+    }
+}
+
+#[test]
+fn test_generate_moves_matches_list_moves() {
+    let mut buffer = MoveBuffer::new();
+    for (game, color) in sample_positions().into_iter().step_by(5) {
+        for roll in all_rolls() {
+            let mut expected = HashSet::new();
+            for mov in &game.list_moves(color, roll) {
+                let mut next = game;
+                next.do_move(color, mov);
+                expected.insert(PackedPosition::new(&next));
+            }
+            PackedPosition::new(&game).generate_moves(color, roll, &mut buffer);
+            let mut generated = HashSet::new();
+            for packed_move in buffer.moves() {
+                assert!(generated.insert(packed_move.position), "duplicate position");
+            }
+            assert_eq!(generated, expected);
+        }
+    }
+}
+
+#[test]
+fn test_generated_moves_are_legal_and_reach_their_position() {
+    let mut buffer = MoveBuffer::new();
+    for (game, color) in sample_positions().into_iter().step_by(11) {
+        for roll in all_rolls() {
+            PackedPosition::new(&game).generate_moves(color, roll, &mut buffer);
+            for packed_move in buffer.moves() {
+                let mov = packed_move.to_move();
+                assert!(game.can_do_move(color, roll, &mov));
+                let mut next = game;
+                next.do_move(color, &mov);
+                assert!(PackedPosition::new(&next) == packed_move.position);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_generate_moves_plays_higher_die() {
+    let mut game: Backgammon = Default::default();
+    game.red_board.set(24, 1);
+    let mut buffer = MoveBuffer::new();
+    PackedPosition::new(&game).generate_moves(Color::Red, (1, 2), &mut buffer);
+    assert_eq!(buffer.len(), 1);
+    let mov = buffer.moves()[0].to_move();
+    assert_eq!(mov.submoves, vec!(Submove { from: 24, die: 2 }));
+}
+
+#[test]
+fn test_generate_moves_finds_nothing_when_blocked() {
+    let mut game: Backgammon = Default::default();
+    game.red_board.set(BAR_POS, 1);
+    for pos in 19..25 {
+        game.white_board.set(pos, 2);
+    }
+    let mut buffer = MoveBuffer::new();
+    PackedPosition::new(&game).generate_moves(Color::Red, (6, 6), &mut buffer);
+    assert!(buffer.is_empty());
+}
+
+#[test]
+fn test_move_buffer_bound() {
+    // 18 choose 4 ways for four dice to fall among 15 checkers.
+    assert_eq!(MAX_DOUBLE_MOVES, 18 * 17 * 16 * 15 / 24);
+    // Spread checkers one to a point so that most of them can move.
+    let mut game: Backgammon = Default::default();
+    for pos in 1..16 {
+        game.red_board.set(pos, 1);
+    }
+    let mut buffer: MoveBuffer = Default::default();
+    for roll in all_rolls() {
+        PackedPosition::new(&game).generate_moves(Color::Red, roll, &mut buffer);
+        let bound = if roll.0 == roll.1 { MAX_DOUBLE_MOVES } else { 15 * 15 + 15 };
+        assert!(buffer.len() <= bound);
+    }
+    for (game, color) in sample_positions() {
+        for roll in all_rolls() {
+            PackedPosition::new(&game).generate_moves(color, roll, &mut buffer);
+            assert!(buffer.len() <= MAX_DOUBLE_MOVES);
+        }
+    }
+}
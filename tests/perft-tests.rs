@@ -0,0 +1,69 @@
+extern crate rustgammon;
+
+use rustgammon::packed::*;
+use rustgammon::perft::*;
+
+// Distinct positions for each roll in the order of distinct_rolls: 1-1, 1-2, ..., 6-6.
+const EXPECTED_COUNTS: [(&str, [usize; 21]); 4] = [
+    ("opening", [42, 15, 16, 14, 8, 10, 75, 17, 18, 8, 14, 73, 17, 9, 14, 52, 9, 14, 4, 7, 11]),
+    ("middle game",
+     [167, 41, 27, 37, 29, 20, 221, 27, 40, 33, 19, 36, 25, 19, 13, 107, 29, 18, 45, 14, 12]),
+    ("on the bar", [0, 1, 0, 0, 0, 0, 17, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]),
+    ("bearing off", [96, 26, 21, 16, 11, 6, 58, 18, 14, 10, 5, 25, 12, 8, 4, 8, 6, 3, 2, 2, 1]),
+];
+
+const EXPECTED_PERFT: [(u64, u64); 4] = [(447, 202782), (979, 863781), (37, 14241), (352, 120384)];
+
+#[test]
+fn test_list_moves_counts_for_reference_positions() {
+    let positions = reference_positions();
+    assert_eq!(positions.len(), EXPECTED_COUNTS.len());
+    for (reference, &(name, counts)) in positions.iter().zip(EXPECTED_COUNTS.iter()) {
+        assert_eq!(reference.name, name);
+        for (&(roll, _), expected) in distinct_rolls().iter().zip(counts.iter()) {
+            assert_eq!(count_positions(&reference.game, reference.color, roll), *expected,
+                       "{} with {}-{}", name, roll.0, roll.1);
+        }
+    }
+}
+
+#[test]
+fn test_perft_for_reference_positions() {
+    for (reference, &(depth_one, depth_two)) in reference_positions().iter()
+        .zip(EXPECTED_PERFT.iter()) {
+        let position = PackedPosition::new(&reference.game);
+        assert_eq!(perft(&position, reference.color, 1), depth_one, "{}", reference.name);
+        assert_eq!(perft(&position, reference.color, 2), depth_two, "{}", reference.name);
+    }
+}
+
+#[test]
+fn test_perft_depth_zero_is_one() {
+    let position = PackedPosition::new(&reference_positions()[0].game);
+    assert_eq!(perft(&position, reference_positions()[0].color, 0), 1);
+}
+
+#[test]
+fn test_distinct_rolls_cover_all_36() {
+    let rolls = distinct_rolls();
+    assert_eq!(rolls.len(), 21);
+    assert_eq!(rolls.iter().map(|&(_, ways)| ways).sum::<u32>(), 36);
+}
+
+#[test]
+fn test_bench_counts_calls() {
+    let positions = reference_positions();
+    let list = bench_list_moves(&positions[..1], 1);
+    let generated = bench_generate_moves(&positions[..1], 1);
+    assert_eq!(list.calls, 21);
+    assert_eq!(generated.calls, 21);
+    assert_eq!(generated.moves, 447);
+}
+
+#[test]
+fn test_roll_index_matches_distinct_rolls() {
+    for (idx, &((a, b), _)) in distinct_rolls().iter().enumerate() {
+        assert_eq!(roll_index((a, b)), idx);
+        assert_eq!(roll_index((b, a)), idx);
+    }
+}
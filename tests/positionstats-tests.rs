@@ -0,0 +1,53 @@
+extern crate rustgammon;
+
+use rustgammon::positionstats::*;
+use rustgammon::rustgammon::*;
+
+#[test]
+fn test_pip_count_for_initial_position() {
+    let mut game: Backgammon = Default::default();
+    game.init();
+    assert_eq!(game.pip_count(Color::Red), 167);
+    assert_eq!(game.pip_count(Color::White), 167);
+}
+
+#[test]
+fn test_pip_count_counts_bar_as_twenty_five() {
+    let mut game: Backgammon = Default::default();
+    game.red_board.set(BAR_POS, 1);
+    game.red_board.set(BEARING_OFF_POS, 14);
+    assert_eq!(game.pip_count(Color::Red), 25);
+}
+
+#[test]
+fn test_position_stats_for_initial_position() {
+    let mut game: Backgammon = Default::default();
+    game.init();
+    let stats = PositionStats::new(&game, Color::Red);
+    assert_eq!(stats.pip_count, 167);
+    assert_eq!(stats.off, 0);
+    assert_eq!(stats.bar, 0);
+    assert_eq!(stats.blots, 0);
+    assert_eq!(stats.made_points, 4);
+    assert_eq!(stats.prime_length, 1);
+    assert_eq!(stats.home_board_points, 1);
+    // The 4 and 5 points are empty.
+    assert_eq!(stats.keith_adjustment, 2);
+}
+
+#[test]
+fn test_position_stats_counts_blots_and_primes() {
+    let mut game: Backgammon = Default::default();
+    for pos in 17..22 {
+        game.white_board.set(pos, 2);
+    }
+    game.white_board.set(1, 1);
+    game.white_board.set(24, 4);
+    let stats = PositionStats::new(&game, Color::White);
+    assert_eq!(stats.blots, 1);
+    assert_eq!(stats.made_points, 6);
+    assert_eq!(stats.prime_length, 5);
+    assert_eq!(stats.home_board_points, 4);
+    // Three extra checkers on the 1 point.
+    assert_eq!(stats.keith_adjustment, 6);
+}
@@ -0,0 +1,137 @@
+extern crate rustgammon;
+
+use rustgammon::bearoff::OneSidedBearoff;
+use rustgammon::evaluator::ProbabilityEvaluator;
+use rustgammon::race::*;
+use rustgammon::rustgammon::*;
+
+// Checkers on the home points of each player, where index 0 is the one point.
+fn home_position(red: &[Checker; 6], white: &[Checker; 6]) -> Backgammon {
+    let mut game: Backgammon = Default::default();
+    for point in 0..6 {
+        game.set_board(Color::Red, BEARING_OFF_POS - point - 1, red[point]);
+        game.set_board(Color::White, BEARING_OFF_POS - point - 1, white[point]);
+    }
+    return game;
+}
+
+#[test]
+fn test_is_race() {
+    let mut game: Backgammon = Default::default();
+    game.init();
+    assert!(!game.is_race());
+
+    let mut game: Backgammon = Default::default();
+    // Red on its 10 point has yet to pass White on its 9 point, which is Red's 16 point.
+    game.set_board(Color::Red, 15, 15);
+    game.set_board(Color::White, 9, 15);
+    assert!(!game.is_race());
+    // White on its 11 point is Red's 14 point.
+    game.set_board(Color::White, 9, 0);
+    game.set_board(Color::White, 11, 15);
+    assert!(game.is_race());
+    game.set_board(Color::Red, 15, 14);
+    game.set_board(Color::Red, BAR_POS, 1);
+    assert!(!game.is_race());
+}
+
+#[test]
+fn test_no_assessment_with_contact() {
+    let mut game: Backgammon = Default::default();
+    game.init();
+    assert!(assess(&game, Color::Red, RaceFormula::Keith, true).is_none());
+    assert!(assess_all(&game, Color::Red, true).is_empty());
+}
+
+#[test]
+fn test_even_race_is_no_double() {
+    let game = home_position(&[3, 3, 3, 2, 2, 2], &[3, 3, 3, 2, 2, 2]);
+    let keith = assess(&game, Color::Red, RaceFormula::Keith, true).unwrap();
+    // 48 pips, with 4 for the one point and 2 for the two point.
+    assert_eq!(keith.count, 54.0);
+    assert_eq!(keith.opponent_count, 54.0);
+    let thorp = assess(&game, Color::Red, RaceFormula::Thorp, true).unwrap();
+    // 48 pips, 30 for the checkers, 3 for the one point, less 6 for the points.
+    assert_eq!(thorp.count, 75.0);
+    for assessment in assess_all(&game, Color::Red, true) {
+        assert_eq!(assessment.action, CubeAction::NoDouble, "{}", assessment);
+        assert!(0.5 < assessment.win_probability && assessment.win_probability < 0.75,
+                "{}", assessment);
+    }
+}
+
+#[test]
+fn test_large_lead_is_double_pass() {
+    let game = home_position(&[3, 3, 3, 3, 3, 0], &[0, 0, 0, 0, 0, 15]);
+    for assessment in assess_all(&game, Color::Red, true) {
+        assert_eq!(assessment.action, CubeAction::DoublePass, "{}", assessment);
+        assert!(0.95 < assessment.win_probability, "{}", assessment);
+    }
+    for assessment in assess_all(&game, Color::White, true) {
+        assert_eq!(assessment.action, CubeAction::NoDouble, "{}", assessment);
+        assert!(assessment.win_probability < 0.05, "{}", assessment);
+    }
+}
+
+#[test]
+fn test_redouble_needs_a_larger_lead() {
+    // A race of 115 pips against 129 pips.
+    let mut game: Backgammon = Default::default();
+    game.set_board(Color::Red, 14, 5);
+    game.set_board(Color::Red, 19, 10);
+    game.set_board(Color::White, 13, 5);
+    game.set_board(Color::White, 18, 9);
+    game.set_board(Color::White, 19, 1);
+    for formula in FORMULAS.iter() {
+        let initial = assess(&game, Color::Red, *formula, true).unwrap();
+        let redouble = assess(&game, Color::Red, *formula, false).unwrap();
+        assert_eq!(initial.win_probability, redouble.win_probability);
+        if initial.action == CubeAction::NoDouble {
+            assert_eq!(redouble.action, CubeAction::NoDouble, "{}", redouble);
+        }
+    }
+    assert_eq!(assess(&game, Color::Red, RaceFormula::Keith, true).unwrap().action,
+               CubeAction::DoubleTake);
+}
+
+#[test]
+fn test_win_probability_is_close_to_bearoff_database() {
+    let database = OneSidedBearoff::generate(8);
+    let positions = [
+        ([0, 1, 2, 1, 1, 1], [1, 1, 1, 1, 1, 1]),
+        ([0, 0, 0, 2, 2, 2], [1, 1, 1, 1, 2, 0]),
+        ([0, 1, 1, 2, 2, 2], [1, 1, 1, 2, 1, 2]),
+        ([0, 0, 2, 2, 2, 2], [0, 0, 0, 2, 3, 3]),
+    ];
+    for &(red, white) in positions.iter() {
+        let game = home_position(&red, &white);
+        // The database gives the probabilities of White, who just moved.
+        let exact = 1.0 - database.probabilities(&game, Color::White).unwrap().win;
+        for assessment in assess_all(&game, Color::Red, true) {
+            assert!((assessment.win_probability - exact).abs() < 0.1,
+                    "{} against {:.3}", assessment, exact);
+        }
+    }
+}
+
+#[test]
+fn test_win_probability() {
+    assert_eq!(win_probability(0.0, 0.0), 1.0);
+    assert!(0.55 < win_probability(100.0, 100.0));
+    assert!(win_probability(100.0, 100.0) < 0.6);
+    assert!(win_probability(110.0, 100.0) < win_probability(100.0, 100.0));
+    assert!((win_probability(100.0, 100.0 - 49.0 / 12.0) - 0.5).abs() < 1e-6);
+}
+
+#[test]
+fn test_race_evaluator() {
+    // Red needs 20 pips and White 30.
+    let game = home_position(&[0, 0, 0, 5, 0, 0], &[0, 0, 0, 0, 0, 5]);
+    let probabilities = RaceEvaluator.probabilities(&game, Color::Red);
+    assert!(0.5 < probabilities.win && probabilities.win < 1.0);
+    assert_eq!(probabilities.win_gammon, 0.0);
+    assert!(RaceEvaluator.probabilities(&game, Color::White).win < 0.5);
+    let mut finished = game;
+    finished.set_board(Color::Red, BEARING_OFF_POS, 15);
+    assert_eq!(RaceEvaluator.probabilities(&finished, Color::Red).win, 1.0);
+}
@@ -0,0 +1,75 @@
+extern crate rustgammon;
+
+mod common;
+
+use rustgammon::analysis::Analyzer;
+use rustgammon::gamerecord::*;
+use rustgammon::luck::analyze_luck;
+use rustgammon::report::*;
+use rustgammon::rustgammon::*;
+
+use common::PipEvaluator;
+
+// Red runs from the opening with 6-5, then doubles and White passes.
+fn opening_record() -> GameRecord {
+    let mut game: Backgammon = Default::default();
+    game.init();
+    let mut record = GameRecord::new(&game);
+    record.turns.push(Turn {
+        color: Color::Red,
+        roll: (6, 5),
+        mov: Move { submoves: vec!(Submove { from: 7, die: 5 }, Submove { from: 1, die: 6 }) },
+    });
+    record.turns.push(Turn {
+        color: Color::White,
+        roll: (2, 1),
+        mov: Move { submoves: vec!(Submove { from: 12, die: 2 }, Submove { from: 17, die: 1 }) },
+    });
+    record.doubles.push(Double { turn: 2, color: Color::Red, taken: false });
+    record.winner = Some(Color::Red);
+    return record;
+}
+
+fn report_from(title: &str, perspective: Option<Color>) -> String {
+    let record = opening_record();
+    let analysis = Analyzer::new(PipEvaluator).analyze(&record);
+    let luck = analyze_luck(&record, &PipEvaluator);
+    let mut out = Vec::new();
+    write_report(&mut out, title, &analysis, &luck, perspective).unwrap();
+    return String::from_utf8(out).unwrap();
+}
+
+fn report_text(title: &str) -> String {
+    return report_from(title, None);
+}
+
+#[test]
+fn test_report_is_a_single_page() {
+    let text = report_text("Red v White");
+    assert!(text.starts_with("<!DOCTYPE html>"));
+    assert!(text.trim_end().ends_with("</body></html>"));
+    assert!(!text.contains(" src="));
+    assert!(!text.contains("<link"));
+    // A board before each of the two moves and before the double after the last move.
+    assert_eq!(text.matches("<svg").count(), 3);
+    assert_eq!(text.matches("</svg>").count(), 3);
+    assert!(text.contains("Turn 1: red rolls 6-5"));
+    assert!(text.contains("24/18 18/13"));
+    assert!(text.contains("double, pass"));
+    assert!(text.contains("Luck-adjusted result"));
+}
+
+#[test]
+fn test_report_escapes_the_title() {
+    let text = report_text("Red <b>& White</b>");
+    assert!(text.contains("<h1>Red &lt;b&gt;&amp; White&lt;/b&gt;</h1>"));
+    assert_eq!(escape("\"a\" < b"), "&quot;a&quot; &lt; b");
+}
+
+#[test]
+fn test_report_from_a_perspective() {
+    // Red's opening move, numbered from White's side.
+    let text = report_from("Red v White", Some(Color::White));
+    assert!(text.contains("1/7 7/12"));
+    assert!(!text.contains("24/18 18/13"));
+}
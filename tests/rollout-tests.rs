@@ -0,0 +1,84 @@
+extern crate rand;
+extern crate rustgammon;
+
+use rand::SeedableRng;
+use rand::XorShiftRng;
+
+use rustgammon::evaluator::ProbabilityEvaluator;
+use rustgammon::neuralnet::*;
+use rustgammon::rollout::*;
+use rustgammon::rustgammon::*;
+
+fn seeded_net() -> NeuralNet {
+    let mut rng = XorShiftRng::from_seed([9, 10, 11, 12]);
+    return NeuralNet::random(5, &mut rng);
+}
+
+fn config(games: usize, truncation: Option<usize>) -> RolloutConfig {
+    return RolloutConfig { games: games, truncation: truncation, threads: 1, ..Default::default() };
+}
+
+#[test]
+fn test_certain_backgammon() {
+    // Red bears off next turn while all of White's checkers are in Red's home board.
+    let mut game: Backgammon = Default::default();
+    game.red_board.set(24, 1);
+    game.red_board.set(BEARING_OFF_POS, 14);
+    game.white_board.set(1, 15);
+    let result = rollout(&game, Color::Red, &seeded_net(), &config(20, None));
+    assert_eq!(result.games, 20);
+    assert_eq!(result.probabilities.win, 1.0);
+    assert_eq!(result.probabilities.win_backgammon, 1.0);
+    assert_eq!(result.equity, 3.0);
+    assert_eq!(result.standard_error, 0.0);
+}
+
+#[test]
+fn test_truncation_at_zero_plies_is_the_evaluation() {
+    let mut game: Backgammon = Default::default();
+    game.init();
+    let net = seeded_net();
+    let result = rollout(&game, Color::White, &net, &config(3, Some(0)));
+    let expected = net.probabilities(&game, Color::White);
+    assert!((result.equity - expected.equity()).abs() < 1e-12);
+    assert!((result.probabilities.win - expected.win).abs() < 1e-12);
+}
+
+#[test]
+fn test_result_does_not_depend_on_threads() {
+    let mut game: Backgammon = Default::default();
+    game.init();
+    let net = seeded_net();
+    let single = rollout(&game, Color::Red, &net, &config(12, Some(6)));
+    let threaded = rollout(&game, Color::Red, &net,
+                           &RolloutConfig { threads: 3, ..config(12, Some(6)) });
+    assert_eq!(single.games, threaded.games);
+    assert!((single.equity - threaded.equity).abs() < 1e-9);
+    assert!((single.standard_error - threaded.standard_error).abs() < 1e-9);
+}
+
+#[test]
+fn test_candidates_share_dice() {
+    let mut game: Backgammon = Default::default();
+    game.init();
+    let net = seeded_net();
+    let mov = Move { submoves: vec!(Submove { from: 17, die: 3 }, Submove { from: 19, die: 1 }) };
+    let moves = vec!(mov.clone(), mov);
+    let results = rollout_moves(&game, Color::Red, &moves, &net, &config(8, Some(8)));
+    assert_eq!(results[0], results[1]);
+    let independent = RolloutConfig { duplicate_dice: false, ..config(8, Some(8)) };
+    let results = rollout_moves(&game, Color::Red, &moves, &net, &independent);
+    assert!(results[0] != results[1]);
+}
+
+#[test]
+fn test_game_result() {
+    let mut game: Backgammon = Default::default();
+    game.red_board.set(BEARING_OFF_POS, 15);
+    game.white_board.set(20, 14);
+    game.white_board.set(BEARING_OFF_POS, 1);
+    let result = game_result(&game, Color::Red, Color::White);
+    assert_eq!(result.win, 0.0);
+    assert_eq!(result.lose_gammon, 0.0);
+    assert_eq!(result.equity(), -1.0);
+}
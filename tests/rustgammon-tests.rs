@@ -233,6 +233,50 @@ fn test_can_do_move_false_for_illegal_move() {
     assert!(!game.can_do_move(Color::Red, dice_roll, &illegal_move));
 }
 
+#[test]
+fn test_can_do_move_passes_only_without_legal_moves() {
+    let mut game: Backgammon = Default::default();
+    game.red_board.set(0, 1);
+    // White closes its home board, so red cannot enter from the bar.
+    for pos in 19..25 {
+        game.white_board.set(pos, 2);
+    }
+    let pass = Move { submoves: Vec::new() };
+    assert!(game.can_do_move(Color::Red, (6, 5), &pass));
+    assert!(!game.can_do_move(Color::White, (6, 5), &pass));
+}
+
+// A computer player that never moves, even when it must.
+struct StuckPlayer {
+    color: Color,
+}
+
+impl Player for StuckPlayer {
+    fn get_color(&self) -> Color {
+        return self.color;
+    }
+
+    fn make_move(&self, _: &Backgammon, _: DiceRoll) -> Move {
+        return Move { submoves: Vec::new() };
+    }
+}
+
+#[test]
+fn test_legal_move_of_replaces_illegal_moves() {
+    let mut game: Backgammon = Default::default();
+    game.init();
+    let red = StuckPlayer { color: Color::Red };
+    let mov = game.legal_move_of(&red, (3, 1));
+    assert!(!mov.submoves.is_empty());
+    assert!(game.can_do_move(Color::Red, (3, 1), &mov));
+    // Passing is legal when red cannot enter from the bar.
+    game.red_board.set(0, 1);
+    for pos in 19..25 {
+        game.white_board.set(pos, 2);
+    }
+    assert!(game.legal_move_of(&red, (6, 5)).submoves.is_empty());
+}
+
 #[test]
 fn test_do_move_does_move() {
     let mut game: Backgammon = Default::default();
@@ -291,3 +335,27 @@ fn test_do_submove_does_not_hit_blot_when_bearing_off() {
     assert_eq!(game.get_board(Color::White, 0), 1);
 }
 
+
+#[test]
+fn test_can_do_submove_false_for_bearing_off_with_higher_checker_beyond_die() {
+    let mut game: Backgammon = Default::default();
+    game.red_board.set(BEARING_OFF_POS - 1, 1);
+    game.red_board.set(BEARING_OFF_POS - 4, 1);
+    // The 3 can move the checker on the 4 point, so it may not bear off from the 1 point.
+    let submove = Submove { from: BEARING_OFF_POS - 1, die: 3 };
+    assert!(!game.can_do_submove(Color::Red, &submove));
+}
+
+#[test]
+fn test_list_moves_plays_all_four_dice_of_double_when_possible() {
+    let mut game: Backgammon = Default::default();
+    game.red_board.set(1, 1);
+    game.red_board.set(2, 1);
+    // Block the 1 point checker after two sixes.
+    game.white_board.set(game.get_opposite_pos(19), 2);
+    let moves = game.list_moves(Color::Red, (6, 6));
+    assert!(!moves.is_empty());
+    for mov in &moves {
+        assert_eq!(mov.submoves.len(), 4);
+    }
+}
@@ -0,0 +1,129 @@
+extern crate rustgammon;
+
+use std::time::Duration;
+use std::time::Instant;
+
+use rustgammon::evaluator::Evaluator;
+use rustgammon::heuristicplayer::*;
+use rustgammon::searchplayer::*;
+use rustgammon::rustgammon::*;
+
+// Prefer the lower pip count only.
+struct PipEvaluator;
+
+impl Evaluator for PipEvaluator {
+    fn evaluate(&self, game: &Backgammon, color: Color) -> f64 {
+        return game.pip_count(color.opposite()) as f64 - game.pip_count(color) as f64;
+    }
+}
+
+fn race_position() -> Backgammon {
+    let mut game: Backgammon = Default::default();
+    game.red_board.set(20, 2);
+    game.red_board.set(23, 1);
+    game.red_board.set(BEARING_OFF_POS, 12);
+    game.white_board.set(21, 3);
+    game.white_board.set(BEARING_OFF_POS, 12);
+    return game;
+}
+
+#[test]
+fn test_zero_plies_plays_best_static_move() {
+    let mut game: Backgammon = Default::default();
+    game.init();
+    let search = SearchPlayer::new(Color::Red, Heuristic::default(), 0);
+    let heuristic = HeuristicPlayer::new(Color::Red);
+    let mut searched = game;
+    searched.do_move(Color::Red, &search.make_move(&game, (3, 1)));
+    let mut static_game = game;
+    static_game.do_move(Color::Red, &heuristic.make_move(&game, (3, 1)));
+    for pos in 0..BOARD_SIZE {
+        assert_eq!(searched.get_board(Color::Red, pos), static_game.get_board(Color::Red, pos));
+    }
+}
+
+#[test]
+fn test_one_ply_search_plays_legal_moves() {
+    let mut game: Backgammon = Default::default();
+    game.init();
+    let search = SearchPlayer::new(Color::White, Heuristic::default(), 1);
+    for roll in &[(6, 5), (2, 2), (4, 1)] {
+        let mov = search.make_move(&game, *roll);
+        assert!(game.can_do_move(Color::White, *roll, &mov));
+    }
+}
+
+#[test]
+fn test_search_uses_pluggable_evaluator() {
+    let game = race_position();
+    let search = SearchPlayer::new(Color::Red, PipEvaluator, 1);
+    let mov = search.make_move(&game, (5, 4));
+    assert!(game.can_do_move(Color::Red, (5, 4), &mov));
+    let mut next = game;
+    next.do_move(Color::Red, &mov);
+    assert_eq!(next.pip_count(Color::Red), game.pip_count(Color::Red) - 9);
+}
+
+#[test]
+fn test_two_ply_search_bears_off() {
+    let game = race_position();
+    let mut search = SearchPlayer::new(Color::Red, Heuristic::default(), 2);
+    search.candidates = 3;
+    search.inner_candidates = 1;
+    let mov = search.make_move(&game, (6, 5));
+    let mut next = game;
+    next.do_move(Color::Red, &mov);
+    assert_eq!(next.get_board(Color::Red, BEARING_OFF_POS), 14);
+}
+
+#[test]
+fn test_search_returns_empty_move_without_legal_moves() {
+    let mut game: Backgammon = Default::default();
+    game.red_board.set(BAR_POS, 1);
+    for pos in 19..25 {
+        game.white_board.set(pos, 2);
+    }
+    let search = SearchPlayer::new(Color::Red, Heuristic::default(), 1);
+    assert!(search.make_move(&game, (3, 3)).submoves.is_empty());
+}
+
+// Prefers keeping checkers on the board, unless the game is over.
+struct StallingEvaluator;
+
+impl Evaluator for StallingEvaluator {
+    fn evaluate(&self, game: &Backgammon, color: Color) -> f64 {
+        return 15.0 - game.get_board(color, BEARING_OFF_POS) as f64;
+    }
+
+    fn evaluate_finished(&self, _: &Backgammon, winner: Color, color: Color) -> f64 {
+        return if winner == color { 100.0 } else { -100.0 };
+    }
+}
+
+#[test]
+fn test_search_scores_finished_games() {
+    let mut game: Backgammon = Default::default();
+    game.red_board.set(23, 1);
+    game.red_board.set(24, 1);
+    game.red_board.set(BEARING_OFF_POS, 13);
+    game.white_board.set(24, 1);
+    game.white_board.set(BEARING_OFF_POS, 14);
+    let search = SearchPlayer::new(Color::Red, StallingEvaluator, 1);
+    let mut next = game;
+    next.do_move(Color::Red, &search.make_move(&game, (2, 1)));
+    assert_eq!(next.winner(), Some(Color::Red));
+}
+
+#[test]
+fn test_two_ply_opening_move_takes_under_a_second() {
+    let mut game: Backgammon = Default::default();
+    game.init();
+    let search = SearchPlayer::new(Color::Red, Heuristic::default(), 2);
+    let start = Instant::now();
+    let mov = search.make_move(&game, (3, 1));
+    let elapsed = start.elapsed();
+    assert!(game.can_do_move(Color::Red, (3, 1), &mov));
+    // Unoptimised builds are several times slower.
+    let limit = if cfg!(debug_assertions) { 10 } else { 1 };
+    assert!(elapsed < Duration::from_secs(limit), "took {:?}", elapsed);
+}
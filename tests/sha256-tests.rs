@@ -0,0 +1,37 @@
+extern crate rustgammon;
+
+use rustgammon::sha256::*;
+
+#[test]
+fn test_sha256_empty() {
+    assert_eq!(to_hex(&sha256(b"")),
+               "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+}
+
+#[test]
+fn test_sha256_abc() {
+    assert_eq!(to_hex(&sha256(b"abc")),
+               "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+}
+
+#[test]
+fn test_sha256_two_blocks() {
+    let message = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+    assert_eq!(to_hex(&sha256(message)),
+               "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1");
+}
+
+#[test]
+fn test_sha256_million_a() {
+    let message = vec![b'a'; 1000000];
+    assert_eq!(to_hex(&sha256(&message)),
+               "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0");
+}
+
+#[test]
+fn test_from_hex_reverses_to_hex() {
+    let digest = sha256(b"rustgammon");
+    assert_eq!(from_hex(&to_hex(&digest)).unwrap(), digest.to_vec());
+    assert!(from_hex("abc").is_none());
+    assert!(from_hex("zz").is_none());
+}
@@ -0,0 +1,111 @@
+extern crate rustgammon;
+
+use rustgammon::cube::Cube;
+use rustgammon::notation::Orientation;
+use rustgammon::rustgammon::*;
+use rustgammon::svg::*;
+
+// The value of the first occurrence of the attribute in the text.
+fn attribute(text: &str, name: &str) -> u32 {
+    let start = text.find(&format!(" {}=\"", name)).unwrap() + name.len() + 3;
+    let end = start + text[start..].find('"').unwrap();
+    return text[start..end].parse().unwrap();
+}
+
+fn render(game: &Backgammon) -> String {
+    let renderer: SvgRenderer = Default::default();
+    return renderer.render(&Diagram::new(game));
+}
+
+#[test]
+fn test_initial_position() {
+    let mut game: Backgammon = Default::default();
+    game.init();
+    let svg = render(&game);
+    assert!(svg.starts_with("<svg "));
+    assert!(svg.trim_end().ends_with("</svg>"));
+    assert_eq!(svg.matches("<circle").count(), 30);
+    assert_eq!(svg.matches("<polygon").count(), 24);
+    for point in 1..25 {
+        assert!(svg.contains(&format!(">{}</text>", point)), "point {}", point);
+    }
+    assert!(svg.contains(">red pips 167</text>"));
+    assert!(svg.contains(">white pips 167</text>"));
+}
+
+#[test]
+fn test_tall_stack_shows_the_count() {
+    let mut game: Backgammon = Default::default();
+    game.set_board(Color::Red, 24, 12);
+    game.set_board(Color::Red, BAR_POS, 1);
+    game.set_board(Color::Red, BEARING_OFF_POS, 2);
+    game.set_board(Color::White, 24, 15);
+    let svg = render(&game);
+    // Five checkers on each point and one on the bar.
+    assert_eq!(svg.matches("<circle").count(), 11);
+    // The counts are written in the colour of the other player's checkers.
+    assert!(svg.contains(&format!("fill=\"{}\">12</text>", CLASSIC.white)));
+    assert!(svg.contains(&format!("fill=\"{}\">15</text>", CLASSIC.red)));
+    // Two borne off checkers, the board and the bar.
+    assert_eq!(svg.matches("<rect").count(), 2 + 3);
+}
+
+#[test]
+fn test_orientation_mirrors_the_board() {
+    let mut game: Backgammon = Default::default();
+    // Red's one point.
+    game.set_board(Color::Red, 24, 1);
+    let mut renderer: SvgRenderer = Default::default();
+    let right = attribute(&renderer.render(&Diagram::new(&game)), "cx");
+    renderer.orientation = Orientation::HomeLeft;
+    let left = attribute(&renderer.render(&Diagram::new(&game)), "cx");
+    assert!(left < right);
+    assert_eq!(left + right, renderer.width());
+}
+
+#[test]
+fn test_dice_and_cube() {
+    let mut game: Backgammon = Default::default();
+    game.init();
+    let mut diagram = Diagram::new(&game);
+    diagram.dice = Some((Color::Red, (6, 1)));
+    diagram.cube = Some(Default::default());
+    let renderer: SvgRenderer = Default::default();
+    let svg = renderer.render(&diagram);
+    assert_eq!(svg.matches("<circle").count(), 30 + 7);
+    assert!(svg.contains(">64</text>"));
+    diagram.cube = Some(Cube { value: 4, owner: Some(Color::White) });
+    assert!(renderer.render(&diagram).contains(">4</text>"));
+}
+
+#[test]
+fn test_themes() {
+    assert_eq!(Theme::by_name("felt"), Some(FELT));
+    assert_eq!(Theme::by_name("neon"), None);
+    let mut game: Backgammon = Default::default();
+    game.init();
+    for theme in THEMES.iter() {
+        let renderer = SvgRenderer { theme: *theme, ..Default::default() };
+        let svg = renderer.render(&Diagram::new(&game));
+        assert!(svg.contains(&format!("fill=\"{}\"", theme.red)), "{}", theme.name);
+        assert!(svg.contains(&format!("fill=\"{}\"", theme.board)), "{}", theme.name);
+    }
+}
+
+#[test]
+fn test_perspective_turns_the_board() {
+    let mut game: Backgammon = Default::default();
+    // Red's one point, which is White's twenty four point.
+    game.set_board(Color::Red, 24, 1);
+    let mut renderer: SvgRenderer = Default::default();
+    let red_view = renderer.render(&Diagram::new(&game));
+    renderer.perspective = Color::White;
+    let white_view = renderer.render(&Diagram::new(&game));
+    // Both home boards are on the right, so the checker moves from the bottom right to the
+    // top right.
+    assert_eq!(attribute(&white_view, "cx"), attribute(&red_view, "cx"));
+    assert!(attribute(&white_view, "cy") < attribute(&red_view, "cy"));
+    // The pip count of the player seen from is written last, at the bottom.
+    assert!(red_view.find(">white pips").unwrap() < red_view.find(">red pips").unwrap());
+    assert!(white_view.find(">red pips").unwrap() < white_view.find(">white pips").unwrap());
+}
@@ -0,0 +1,140 @@
+extern crate rustgammon;
+
+use rustgammon::notation::Orientation;
+use rustgammon::rustgammon::*;
+use rustgammon::textboard::*;
+
+const INITIAL_CLASSIC: &str = "\
++13-14-15-16-17-18-+---+19-20-21-22-23-24-+
+| X           O    |   | O              X |  white off: 0
+| X           O    |   | O              X |
+| X           O    |   | O                |
+| X                |   | O                |
+| X                |   | O                |
+|                  |BAR|                  |
+| O                |   | X                |
+| O                |   | X                |
+| O           X    |   | X                |
+| O           X    |   | X              O |
+| O           X    |   | X              O |  red off: 0
++12-11-10--9--8--7-+---+-6--5--4--3--2--1-+
+red X: 167 pips, white O: 167 pips
+";
+
+fn initial_position() -> Backgammon {
+    let mut game: Backgammon = Default::default();
+    game.init();
+    return game;
+}
+
+// The text without ANSI colour escapes.
+fn strip_escapes(text: &str) -> String {
+    let mut stripped = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|&c| c == 'm');
+        } else {
+            stripped.push(c);
+        }
+    }
+    return stripped;
+}
+
+#[test]
+fn test_compact_style_is_the_display() {
+    let game = initial_position();
+    let text = game.to_string();
+    assert_eq!(text, game.view(BoardStyle::Compact).to_string());
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[0], " 13 14 15 16 17 18    19 20 21 22 23 24");
+    assert_eq!(lines[1], " R5 .. .. .. W3 .. || W5 .. .. .. .. R2\tRed bar: 0\tRed off: 0");
+    assert_eq!(lines[2], " W5 .. .. .. R3 .. || R5 .. .. .. .. W2\tWhite bar: 0\tWhite off: 0");
+    assert_eq!(lines[3], " 12 11 10  9  8  7     6  5  4  3  2  1");
+    assert!(lines[4].starts_with("red pips: 167"));
+}
+
+#[test]
+fn test_classic_style() {
+    assert_eq!(initial_position().view(BoardStyle::Classic).to_string(), INITIAL_CLASSIC);
+}
+
+#[test]
+fn test_tall_stacks_and_the_bar() {
+    let mut game: Backgammon = Default::default();
+    game.set_board(Color::Red, 19, 12);
+    game.set_board(Color::Red, BAR_POS, 3);
+    game.set_board(Color::White, BAR_POS, 1);
+    game.set_board(Color::White, BEARING_OFF_POS, 14);
+    let text = game.view(BoardStyle::Classic).to_string();
+    let lines: Vec<&str> = text.lines().collect();
+    // The first row of the bottom half, with White's checker on the bar and the count of
+    // Red's six point, which is the first point of the right half.
+    assert_eq!(&lines[7][19..30], "| O |12    ");
+    assert_eq!(&lines[11][19..30], "|   | X    ");
+    // Red's checkers on the bar stack upwards from it.
+    assert_eq!(&lines[5][19..24], "| X |");
+    assert_eq!(&lines[3][19..24], "| X |");
+    assert_eq!(&lines[2][19..24], "|   |");
+    assert!(lines[1].ends_with("white off: 14"));
+}
+
+#[test]
+fn test_unicode_style_has_the_classic_layout() {
+    let game = initial_position();
+    let text = game.view(BoardStyle::Unicode).to_string();
+    assert!(text.contains("\x1b[1;31m"));
+    assert!(text.starts_with("\u{250c}13\u{2500}14"));
+    let stripped = strip_escapes(&text);
+    let classic = INITIAL_CLASSIC.replace('X', "\u{25cf}").replace('O', "\u{25cb}");
+    for (line, classic_line) in stripped.lines().zip(classic.lines()).skip(1).take(11) {
+        assert_eq!(line.chars().count(), classic_line.chars().count(), "{}", line);
+    }
+}
+
+#[test]
+fn test_write_to_any_writer() {
+    let game = initial_position();
+    for style in STYLES.iter() {
+        let mut out = Vec::new();
+        game.write(&mut out, *style).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), game.view(*style).to_string());
+        assert_eq!(style.name().parse::<BoardStyle>(), Ok(*style));
+    }
+    assert!("fancy".parse::<BoardStyle>().is_err());
+}
+
+#[test]
+fn test_move_display() {
+    let mov = Move { submoves: vec!(Submove { from: 17, die: 3 }, Submove { from: 19, die: 1 }) };
+    assert_eq!(mov.to_string(), "from: 17, die: 3, from: 19, die: 1");
+}
+
+#[test]
+fn test_board_from_white() {
+    let game = initial_position();
+    let text = game.view_from(BoardStyle::Classic, Color::White, Orientation::HomeRight).to_string();
+    // The opening position is symmetric, so White sees Red's view with the checkers swapped.
+    let expected = INITIAL_CLASSIC.replace('X', "x").replace('O', "X").replace('x', "O")
+        .replace("white off", "w off").replace("red off", "white off").replace("w off", "red off")
+        .replace("red O: 167 pips, white X", "red X: 167 pips, white O");
+    assert_eq!(text, expected);
+}
+
+#[test]
+fn test_home_board_on_the_left() {
+    let mut game: Backgammon = Default::default();
+    game.set_board(Color::Red, 24, 2);
+    game.set_board(Color::White, BAR_POS, 1);
+    let text = game.view_from(BoardStyle::Compact, Color::Red, Orientation::HomeLeft).to_string();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[0], " 24 23 22 21 20 19    18 17 16 15 14 13");
+    assert_eq!(lines[2], " R2 .. .. .. .. .. || .. .. .. .. .. ..\tWhite bar: 1\tWhite off: 0");
+    assert_eq!(lines[3], "  1  2  3  4  5  6     7  8  9 10 11 12");
+    let text = game.view_from(BoardStyle::Classic, Color::White, Orientation::HomeLeft).to_string();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[0], "+24-23-22-21-20-19-+---+18-17-16-15-14-13-+");
+    // White's checker on the bar is above it and Red's checkers are on White's 24 point.
+    assert_eq!(&lines[5][19..24], "| O |");
+    assert_eq!(&lines[1][..5], "| X  ");
+}
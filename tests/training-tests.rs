@@ -0,0 +1,119 @@
+extern crate rand;
+extern crate rustgammon;
+
+use std::env;
+
+use rand::SeedableRng;
+use rand::XorShiftRng;
+
+use rustgammon::evaluator::ProbabilityEvaluator;
+use rustgammon::fairdice::FairDice;
+use rustgammon::gamerecord::*;
+use rustgammon::neuralnet::*;
+use rustgammon::randomplayer::RandomPlayer;
+use rustgammon::rustgammon::*;
+use rustgammon::training::*;
+
+fn seeded_net(hidden: usize) -> NeuralNet {
+    let mut rng = XorShiftRng::from_seed([5, 6, 7, 8]);
+    return NeuralNet::random(hidden, &mut rng);
+}
+
+// Red bears off the last checker with any roll.
+fn last_roll_record() -> GameRecord {
+    let mut game: Backgammon = Default::default();
+    game.red_board.set(24, 1);
+    game.red_board.set(BEARING_OFF_POS, 14);
+    game.white_board.set(24, 15);
+    let mut record = GameRecord::new(&game);
+    record.turns.push(Turn {
+        color: Color::Red,
+        roll: (6, 5),
+        mov: Move { submoves: vec!(Submove { from: 24, die: 6 }) },
+    });
+    record.winner = Some(Color::Red);
+    return record;
+}
+
+#[test]
+fn test_points_won() {
+    let mut game: Backgammon = Default::default();
+    game.red_board.set(BEARING_OFF_POS, 15);
+    game.white_board.set(20, 15);
+    assert_eq!(game.winner(), Some(Color::Red));
+    assert_eq!(game.points_won(Color::Red), 2);
+    game.white_board.set(20, 14);
+    game.white_board.set(3, 1);
+    assert_eq!(game.points_won(Color::Red), 3);
+    game.white_board.set(3, 0);
+    game.white_board.set(BEARING_OFF_POS, 1);
+    assert_eq!(game.points_won(Color::Red), 1);
+}
+
+#[test]
+fn test_learn_moves_towards_the_result() {
+    let record = last_roll_record();
+    let mut trainer = TdTrainer::new(seeded_net(5), 0.1, 0.7);
+    let before_red = trainer.net.evaluate_for(&record.start, Color::Red, true);
+    let before_white = trainer.net.evaluate_for(&record.start, Color::White, false);
+    for _ in 0..20 {
+        trainer.learn(&record, Color::Red);
+        trainer.learn(&record, Color::White);
+    }
+    let after_red = trainer.net.evaluate_for(&record.start, Color::Red, true);
+    let after_white = trainer.net.evaluate_for(&record.start, Color::White, false);
+    assert!(before_red.win < after_red.win);
+    // White loses a gammon.
+    assert!(before_white.lose_gammon < after_white.lose_gammon);
+    assert!(after_white.win < before_white.win);
+}
+
+#[test]
+fn test_train_game_plays_to_the_end() {
+    let mut trainer = TdTrainer::new(seeded_net(5), 0.1, 0.7);
+    let before = trainer.net.clone();
+    let record = trainer.train_game(&mut FairDice::new([1; 32]));
+    assert!(record.winner.is_some());
+    let mut game = record.start;
+    for turn in &record.turns {
+        game.do_move(turn.color, &turn.mov);
+    }
+    assert_eq!(game.winner(), record.winner);
+    assert!(trainer.net != before);
+}
+
+#[test]
+fn test_play_match_counts_games_and_points() {
+    let net = seeded_net(5);
+    let player = rustgammon::searchplayer::SearchPlayer::new(Color::Red, &net, 0);
+    let opponent = RandomPlayer { color: Color::White };
+    let result = play_match(&player, &opponent, 4, &mut FairDice::new([2; 32]));
+    assert_eq!(result.games, 4);
+    assert!(result.wins <= result.points_won);
+    assert!(result.losses() <= result.points_lost);
+    assert!(result.points_won + result.points_lost <= 12);
+}
+
+#[test]
+fn test_checkpoint_can_be_loaded() {
+    let net = seeded_net(3);
+    let path = env::temp_dir().join("rustgammon-training-test.txt");
+    let path = path.to_str().unwrap();
+    checkpoint(&net, path).unwrap();
+    let loaded = NeuralNet::load(path).unwrap();
+    assert_eq!(loaded, net);
+    let mut game: Backgammon = Default::default();
+    game.init();
+    assert_eq!(loaded.probabilities(&game, Color::Red), net.probabilities(&game, Color::Red));
+}
+
+#[test]
+fn test_default_weights_load() {
+    let net = NeuralNet::load(DEFAULT_WEIGHTS_PATH).unwrap();
+    assert_eq!(net.hidden, DEFAULT_HIDDEN);
+    let mut game: Backgammon = Default::default();
+    game.init();
+    // The side on roll in the opening position is the favourite.
+    let p = net.evaluate_for(&game, Color::Red, true);
+    assert!(0.5 < p.win && p.win < 0.6);
+}
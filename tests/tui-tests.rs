@@ -0,0 +1,122 @@
+extern crate rustgammon;
+
+use rustgammon::notation::Orientation;
+use rustgammon::rustgammon::*;
+use rustgammon::tui::*;
+
+fn initial_position() -> Backgammon {
+    let mut game: Backgammon = Default::default();
+    game.init();
+    return game;
+}
+
+// Click on the top or bottom cell of the column of a slot.
+fn click(tui: &mut Tui, slot: Slot) -> Outcome {
+    let x = tui.slot_x(slot) + 1;
+    let y = match slot {
+        Slot::Point(point) if 13 <= point => 1,
+        _ => 11,
+    };
+    return tui.handle(Input::Click(x, y));
+}
+
+#[test]
+fn test_initial_board() {
+    let tui = Tui::new(&initial_position(), Color::Red, Orientation::HomeRight);
+    let canvas = tui.draw();
+    assert_eq!(canvas.line(0), "+13-14-15-16-17-18-+---+19-20-21-22-23-24-+---+  Moves");
+    assert_eq!(canvas.line(1), "| X           O    |   | O              X |   |");
+    assert_eq!(canvas.line(6), "|                  |BAR|                  |   |");
+    assert_eq!(canvas.line(11), "| O           X    |   | X              O |   |");
+    assert_eq!(canvas.line(12), "+12-11-10--9--8--7-+---+-6--5--4--3--2--1-+---+");
+    assert_eq!(canvas.line(13), "red X: 167 pips, 0 off   white O: 167 pips, 0 off");
+    assert_eq!(canvas.cell(2, 1).ink, Ink::Red);
+    assert_eq!(canvas.cell(14, 1).ink, Ink::White);
+}
+
+#[test]
+fn test_board_from_white_with_home_on_the_left() {
+    let tui = Tui::new(&initial_position(), Color::White, Orientation::HomeLeft);
+    let canvas = tui.draw();
+    assert_eq!(canvas.line(0), "+---+24-23-22-21-20-19-+---+18-17-16-15-14-13-+  Moves");
+    // White's checkers on its own six point are at the bottom, left of the bar.
+    assert_eq!(canvas.line(11), "|   | X              O |   |    O           X |");
+    assert_eq!(tui.slot_at(8, 11), Some(Slot::Point(2)));
+    assert_eq!(tui.slot_at(1, 11), Some(Slot::Off));
+    assert_eq!(tui.slot_at(1, 1), None);
+}
+
+#[test]
+fn test_legal_submoves_follow_the_rules() {
+    // Only one die can be played, so it must be the higher one.
+    let mut game: Backgammon = Default::default();
+    game.set_board(Color::Red, 1, 1);
+    game.set_board(Color::White, 13, 2);
+    let turn = TurnInput::new(&game, Color::Red, (6, 5));
+    assert_eq!(turn.legal_submoves(), vec!(Submove { from: 1, die: 6 }));
+
+    let mut turn = TurnInput::new(&initial_position(), Color::Red, (3, 1));
+    assert_eq!(turn.destinations(17), vec!(18, 20));
+    turn.play(Submove { from: 17, die: 3 });
+    assert_eq!(turn.used_dice(), vec!(true, false));
+    assert!(!turn.is_complete());
+    assert!(turn.undo());
+    assert!(turn.played.is_empty());
+    assert!(!turn.undo());
+}
+
+#[test]
+fn test_play_a_move_with_the_mouse() {
+    let mut tui = Tui::new(&initial_position(), Color::Red, Orientation::HomeRight);
+    tui.start_turn((3, 1));
+    assert_eq!(click(&mut tui, Slot::Point(8)), Outcome::Continue);
+    assert_eq!(tui.selected, Some(Slot::Point(8)));
+    let canvas = tui.draw();
+    let target_x = tui.slot_x(Slot::Point(5));
+    assert_eq!(canvas.cell(target_x, 11).paper, Paper::Target);
+    assert_eq!(canvas.cell(tui.slot_x(Slot::Point(8)), 11).paper, Paper::Selected);
+    assert_eq!(canvas.cell(tui.slot_x(Slot::Point(4)), 11).paper, Paper::Plain);
+
+    click(&mut tui, Slot::Point(5));
+    assert_eq!(tui.selected, None);
+    // A point with no checkers to move can't be selected.
+    click(&mut tui, Slot::Point(20));
+    assert_eq!(tui.selected, None);
+    assert_eq!(tui.message, "no legal move from there");
+    click(&mut tui, Slot::Point(6));
+    click(&mut tui, Slot::Point(5));
+    let mov = match tui.handle(Input::Select) {
+        Outcome::Move(mov) => mov,
+        outcome => panic!("{:?}", outcome),
+    };
+    assert!(initial_position().can_do_move(Color::Red, (3, 1), &mov));
+    tui.play(Color::Red, (3, 1), &mov);
+    assert_eq!(tui.log[0], (Color::Red, " 1. X 31 8/5 6/5".to_string()));
+    assert_eq!(tui.draw().line(1), "| X           O    |   | O              X |   |   1. X 31 8/5 6/5");
+}
+
+#[test]
+fn test_keyboard_cursor() {
+    let mut tui = Tui::new(&initial_position(), Color::Red, Orientation::HomeRight);
+    tui.start_turn((6, 5));
+    assert_eq!(tui.cursor, Slot::Point(13));
+    tui.handle(Input::Left);
+    assert_eq!(tui.cursor, Slot::Point(13));
+    tui.handle(Input::Down);
+    assert_eq!(tui.cursor, Slot::Point(12));
+    for _ in 0..7 {
+        tui.handle(Input::Right);
+    }
+    // The bar is only in the top row.
+    assert_eq!(tui.cursor, Slot::Point(5));
+    tui.handle(Input::Up);
+    assert_eq!(tui.cursor, Slot::Point(20));
+    for _ in 0..4 {
+        tui.handle(Input::Right);
+    }
+    assert_eq!(tui.cursor, Slot::Point(24));
+    tui.handle(Input::Select);
+    assert_eq!(tui.selected, Some(Slot::Point(24)));
+    tui.handle(Input::Cancel);
+    assert_eq!(tui.selected, None);
+}
@@ -0,0 +1,184 @@
+extern crate rustgammon;
+
+use std::io::BufReader;
+use std::io::Read;
+use std::io::Write;
+use std::net::SocketAddr;
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::thread;
+
+use rustgammon::randomplayer::RandomPlayer;
+use rustgammon::rustgammon::*;
+use rustgammon::web::*;
+
+fn request(method: &str, path: &str, body: &str) -> Request {
+    return Request { method: method.to_string(), path: path.to_string(), query: String::new(),
+                     host: Some("localhost:8080".to_string()), origin: None, body: body.to_string() };
+}
+
+// A computer player that never moves, even when it must.
+struct StuckPlayer {
+    color: Color,
+}
+
+impl Player for StuckPlayer {
+    fn get_color(&self) -> Color {
+        return self.color;
+    }
+
+    fn make_move(&self, _: &Backgammon, _: DiceRoll) -> Move {
+        return Move { submoves: Vec::new() };
+    }
+}
+
+// Send a request to the server and return the whole response.
+fn fetch(addr: SocketAddr, method: &str, target: &str, body: &str) -> String {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    write!(stream, "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}", method, target,
+           body.len(), body).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    return response;
+}
+
+// The number after the key in a JSON text.
+fn json_number(json: &str, key: &str) -> u64 {
+    let start = json.find(&format!("\"{}\":", key)).unwrap() + key.len() + 3;
+    let end = start + json[start..].find(|c: char| !c.is_ascii_digit()).unwrap();
+    return json[start..end].parse().unwrap();
+}
+
+#[test]
+fn test_json_string() {
+    assert_eq!(json_string("plain"), "\"plain\"");
+    assert_eq!(json_string("<svg a=\"1\">\n\\"), "\"<svg a=\\\"1\\\">\\n\\\\\"");
+    assert_eq!(json_string("\u{1}"), "\"\\u0001\"");
+}
+
+#[test]
+fn test_read_request() {
+    let text = "POST /click?version=3 HTTP/1.1\r\nHost: x\r\nORIGIN: http://x\r\ncontent-length: 2\r\n\r\n13";
+    let request = read_request(&mut BufReader::new(text.as_bytes())).unwrap();
+    assert_eq!(request.method, "POST");
+    assert_eq!(request.path, "/click");
+    assert_eq!(request.query, "version=3");
+    assert_eq!(request.host, Some("x".to_string()));
+    assert_eq!(request.origin, Some("http://x".to_string()));
+    assert_eq!(request.body, "13");
+    assert!(read_request(&mut BufReader::new("\r\n".as_bytes())).is_err());
+    let too_long = "POST / HTTP/1.1\r\nContent-Length: 100000\r\n\r\n";
+    assert!(read_request(&mut BufReader::new(too_long.as_bytes())).is_err());
+}
+
+#[test]
+fn test_play_a_move_by_clicking() {
+    let mut session = Session::new(Color::Red);
+    session.begin_turn((3, 1));
+    assert!(session.is_human_turn());
+    session.click(8);
+    assert_eq!(session.selected, Some(17));
+    let json = session.to_json();
+    assert!(json.contains("{\"point\":8,"));
+    assert!(json.contains("\"state\":\"selected\""));
+    assert!(json.contains("\"state\":\"target\""));
+    assert!(json.contains("\"canPlay\":false"));
+    session.click(5);
+    session.click(6);
+    session.click(5);
+    assert!(session.to_json().contains("\"canPlay\":true"));
+    session.undo();
+    assert!(session.to_json().contains("\"canPlay\":false"));
+    session.click(6);
+    session.click(5);
+    let version = session.version;
+    session.play();
+    assert!(version < session.version);
+    assert!(!session.is_human_turn());
+    assert_eq!(session.to_move, Color::White);
+    assert_eq!(session.log, vec!("red 3-1: 8/5 6/5".to_string()));
+    assert_eq!(session.record.turns.len(), 1);
+    // Clicks out of turn change nothing.
+    session.click(8);
+    assert_eq!(session.selected, None);
+}
+
+#[test]
+fn test_routes() {
+    let shared = Shared::new(Color::Red);
+    let page = handle(&shared, &request("GET", "/", ""));
+    assert_eq!(page.status, "200 OK");
+    assert!(page.body.starts_with("<!DOCTYPE html>"));
+    let state = handle(&shared, &request("GET", "/state", ""));
+    assert_eq!(state.content_type, "application/json");
+    assert!(state.body.contains("\"human\":\"red\""));
+    assert!(state.body.starts_with("{\"version\":0,"));
+    assert_eq!(handle(&shared, &request("POST", "/click", "x")).status, "400 Bad Request");
+    assert_eq!(handle(&shared, &request("POST", "/click", "26")).status, "400 Bad Request");
+    assert_eq!(handle(&shared, &request("GET", "/play", "")).status, "405 Method Not Allowed");
+    assert_eq!(handle(&shared, &request("GET", "/favicon.ico", "")).status, "404 Not Found");
+    assert_eq!(handle(&shared, &request("POST", "/new", "")).status, "200 OK");
+    assert_eq!(shared.session.lock().unwrap().game_number, 1);
+}
+
+#[test]
+fn test_requests_from_other_sites_are_forbidden() {
+    let shared = Shared::new(Color::Red);
+    let from = |host: Option<&str>, origin: Option<&str>| {
+        let request = Request { host: host.map(str::to_string), origin: origin.map(str::to_string),
+                                ..request("POST", "/new", "") };
+        return handle(&shared, &request).status;
+    };
+    assert_eq!(from(Some("127.0.0.1:8080"), Some("http://127.0.0.1:8080")), "200 OK");
+    assert_eq!(from(Some("localhost"), None), "200 OK");
+    assert_eq!(from(Some("[::1]:8080"), Some("http://[::1]:8080")), "200 OK");
+    assert_eq!(from(Some("[::1]"), None), "200 OK");
+    assert_eq!(from(Some("localhost:8080"), Some("http://evil.example")), "403 Forbidden");
+    assert_eq!(from(Some("localhost:8080"), Some("http://localhost:9090")), "403 Forbidden");
+    // A name that resolves to this machine through another site's DNS.
+    assert_eq!(from(Some("evil.example:8080"), None), "403 Forbidden");
+    assert_eq!(from(None, None), "403 Forbidden");
+    assert_eq!(shared.session.lock().unwrap().game_number, 4);
+}
+
+#[test]
+fn test_long_polling_server() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || serve(vec!(listener), &RandomPlayer { color: Color::White }));
+    let response = fetch(addr, "GET", "/state", "");
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+    let version = json_number(&response, "version");
+    // The poll waits for the new game.
+    let poll = thread::spawn(move || fetch(addr, "GET", &format!("/state?version={}", version), ""));
+    assert!(fetch(addr, "POST", "/new", "").ends_with("\r\n\r\nok"));
+    let response = poll.join().unwrap();
+    assert!(version < json_number(&response, "version"));
+}
+
+#[test]
+fn test_illegal_computer_moves_are_replaced() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || serve(vec!(listener), &StuckPlayer { color: Color::Red }));
+    // Red moves first, and the server plays a legal move for it.
+    let mut response = fetch(addr, "GET", "/state", "");
+    while !response.contains("\"toMove\":\"white\"") {
+        let version = json_number(&response, "version");
+        response = fetch(addr, "GET", &format!("/state?version={}", version), "");
+    }
+    assert!(response.contains("\"log\":[\"red "));
+    assert!(!response.contains("cannot move"));
+}
+
+#[test]
+fn test_idle_connections_time_out() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || serve(vec!(listener), &RandomPlayer { color: Color::White }));
+    // A connection that never sends its request is answered and closed.
+    let mut idle = TcpStream::connect(addr).unwrap();
+    let mut response = String::new();
+    idle.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 400 Bad Request\r\n"), "{}", response);
+}